@@ -0,0 +1,16 @@
+##
+# This file is part of the IVMS Online.
+#
+# @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+##
+
+Feature: Inventory batch management
+
+    Scenario: Batch creating inventory
+        Given There is an inventory "test40" of type "pc" for vessel "00000000-0000-0000-0000-000000000044" of customer "00000000-0000-0000-0000-000000000045" with serial number "qwerdp", AWS instance ID "abdo" and creation date "2011-01-30T14:58:00+01:00"
+        When I batch create inventories "test39" and "test40" of type "pc" for vessel "00000000-0000-0000-0000-000000000044" of customer "00000000-0000-0000-0000-000000000045"
+        Then Batch result at position 0 is successful
+        And Batch result at position 1 failed with "INVENTORY_ALREADY_EXISTS"
+        And Inventory "test40" of type "pc" for vessel "00000000-0000-0000-0000-000000000044" of customer "00000000-0000-0000-0000-000000000045" has serial number "qwerdp" and creation date "2011-01-30T14:58:00+01:00"
+        When I fetch inventory "test39" of type "pc" for vessel "00000000-0000-0000-0000-000000000044" of customer "00000000-0000-0000-0000-000000000045"
+        Then I can read inventory ID as "test39"
@@ -10,3 +10,33 @@ Feature: Inventory management
         When I create inventory "test8" of type "pc" for vessel "00000000-0000-0000-0000-000000000001" of customer "00000000-0000-0000-0000-000000000005" with serial number "qwerts" and AWS instance ID "abcj"
         Then I can read inventory key
         And Inventory with that key exists with serial number "qwerts", AWS instance ID "abcj" and creation date
+
+    Scenario: Creating duplicated inventory
+        Given There is an inventory "test12" of type "pc" for vessel "00000000-0000-0000-0000-000000000012" of customer "00000000-0000-0000-0000-000000000013" with serial number "qwertc", AWS instance ID "abcm" and creation date "2011-01-30T14:58:00+01:00"
+        When I create inventory "test12" of type "pc" for vessel "00000000-0000-0000-0000-000000000012" of customer "00000000-0000-0000-0000-000000000013" with serial number "asdfgj" and AWS instance ID "abcn"
+        Then I get "INVENTORY_ALREADY_EXISTS" API error response
+        And Inventory "test12" of type "pc" for vessel "00000000-0000-0000-0000-000000000012" of customer "00000000-0000-0000-0000-000000000013" has serial number "qwertc" and creation date "2011-01-30T14:58:00+01:00"
+
+    Scenario: Creating inventory with already assigned AWS instance ID
+        When I create inventory "test19" of type "pc" for vessel "00000000-0000-0000-0000-00000000001d" of customer "00000000-0000-0000-0000-00000000001e" with serial number "qwertp" and AWS instance ID "abcv"
+        And I create inventory "test20" of type "pc" for vessel "00000000-0000-0000-0000-00000000001d" of customer "00000000-0000-0000-0000-00000000001e" with serial number "qwerta" and AWS instance ID "abcv"
+        Then I get "AWS_INSTANCE_ID_ALREADY_ASSIGNED" API error response
+        And Inventory "test20" of type "pc" for vessel "00000000-0000-0000-0000-00000000001d" of customer "00000000-0000-0000-0000-00000000001e" does not exist
+
+    Scenario: Creating inventory with invalid ID
+        When I create inventory "" of type "pc" for vessel "00000000-0000-0000-0000-000000000026" of customer "00000000-0000-0000-0000-000000000027" with serial number "qwertx" and AWS instance ID "abdc"
+        Then I get "VALIDATION_FAILED" API error response
+
+    Scenario: Creating inventory of unknown type
+        When I create inventory "test28" of type "PC" for vessel "00000000-0000-0000-0000-00000000002a" of customer "00000000-0000-0000-0000-00000000002b" with serial number "qwertw" and AWS instance ID "abde"
+        Then I get "VALIDATION_FAILED" API error response
+
+    Scenario: Creating inventory with attributes
+        When I create inventory "test29" of type "pc" for vessel "00000000-0000-0000-0000-00000000002c" of customer "00000000-0000-0000-0000-00000000002d" with AWS instance ID "abdf" and "manufacturer" attribute "Dell"
+        And I fetch inventory "test29" of type "pc" for vessel "00000000-0000-0000-0000-00000000002c" of customer "00000000-0000-0000-0000-00000000002d"
+        Then I can read inventory "manufacturer" attribute as "Dell"
+
+    Scenario: Creating inventory with attribute of invalid type
+        When I create inventory "test30" of type "pc" for vessel "00000000-0000-0000-0000-00000000002e" of customer "00000000-0000-0000-0000-00000000002f" with AWS instance ID "abdg" and "rackPosition" attribute "3"
+        Then I get "VALIDATION_FAILED" API error response
+        And Inventory "test30" of type "pc" for vessel "00000000-0000-0000-0000-00000000002e" of customer "00000000-0000-0000-0000-00000000002f" does not exist
@@ -9,9 +9,29 @@ Feature: Inventory management
     Scenario: Deleting inventory
         Given There is an inventory "test0" of type "pc" for vessel "00000000-0000-0000-0000-000000000000" of customer "00000000-0000-0000-0000-000000000001" with serial number "qwerta", AWS instance ID "abci" and creation date "2011-01-30T14:58:00+01:00"
         When I delete inventory "test0" of type "pc" for vessel "00000000-0000-0000-0000-000000000000" of customer "00000000-0000-0000-0000-000000000001"
-        Then Inventory "test0" of type "pc" for vessel "00000000-0000-0000-0000-000000000000" of customer "00000000-0000-0000-0000-000000000001" does not exist
+        Then Inventory "test0" of type "pc" for vessel "00000000-0000-0000-0000-000000000000" of customer "00000000-0000-0000-0000-000000000001" is marked as deleted
+        And I can read inventory ID as "test0"
+        And I can read inventory serial number as "qwerta"
 
     Scenario: Deleting non-existing inventory
         Given There is no inventory "test1" of type "pc" for vessel "00000000-0000-0000-0000-000000000002" of customer "00000000-0000-0000-0000-000000000003"
         When I delete inventory "test1" of type "pc" for vessel "00000000-0000-0000-0000-000000000002" of customer "00000000-0000-0000-0000-000000000003"
         Then Inventory "test1" of type "pc" for vessel "00000000-0000-0000-0000-000000000002" of customer "00000000-0000-0000-0000-000000000003" does not exist
+
+    Scenario: Deleting non-existing inventory when it is expected to exist
+        Given There is no inventory "test37" of type "pc" for vessel "00000000-0000-0000-0000-000000000040" of customer "00000000-0000-0000-0000-000000000041"
+        When I delete inventory "test37" of type "pc" for vessel "00000000-0000-0000-0000-000000000040" of customer "00000000-0000-0000-0000-000000000041" failing if it is missing
+        Then I get "INVENTORY_NOT_FOUND" API error response
+
+    Scenario: Fetching deleted inventory
+        Given There is an inventory "test35" of type "pc" for vessel "00000000-0000-0000-0000-000000000036" of customer "00000000-0000-0000-0000-000000000037" with serial number "qwerdl", AWS instance ID "abdl" and creation date "2011-01-30T14:58:00+01:00"
+        When I delete inventory "test35" of type "pc" for vessel "00000000-0000-0000-0000-000000000036" of customer "00000000-0000-0000-0000-000000000037"
+        And I fetch inventory "test35" of type "pc" for vessel "00000000-0000-0000-0000-000000000036" of customer "00000000-0000-0000-0000-000000000037"
+        Then I get "INVENTORY_NOT_FOUND" API error response
+
+    Scenario: Restoring deleted inventory
+        Given There is an inventory "test36" of type "pc" for vessel "00000000-0000-0000-0000-000000000038" of customer "00000000-0000-0000-0000-000000000039" with serial number "qwerdm", AWS instance ID "abdm" and creation date "2011-01-30T14:58:00+01:00"
+        When I delete inventory "test36" of type "pc" for vessel "00000000-0000-0000-0000-000000000038" of customer "00000000-0000-0000-0000-000000000039"
+        And I restore inventory "test36" of type "pc" for vessel "00000000-0000-0000-0000-000000000038" of customer "00000000-0000-0000-0000-000000000039"
+        Then I can read inventory ID as "test36"
+        And I can read inventory AWS instance ID as "abdm"
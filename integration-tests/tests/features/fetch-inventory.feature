@@ -18,4 +18,12 @@ Feature: Inventory management
     Scenario: Fetching non-existing inventory
         Given There is no inventory "test3" of type "pc" for vessel "00000000-0000-0000-0000-000000000008" of customer "00000000-0000-0000-0000-000000000009"
         When I fetch inventory "test3" of type "pc" for vessel "00000000-0000-0000-0000-000000000008" of customer "00000000-0000-0000-0000-000000000009"
-        Then I get "Inventory not found." API error response
+        Then I get "INVENTORY_NOT_FOUND" API error response
+
+    Scenario: Fetching multiple inventories
+        Given There is an inventory "test41" of type "pc" for vessel "00000000-0000-0000-0000-000000000046" of customer "00000000-0000-0000-0000-000000000047" with serial number "qwerdq", AWS instance ID "abdp" and creation date "2011-01-30T14:58:00+01:00"
+        And There is no inventory "test42" of type "pc" for vessel "00000000-0000-0000-0000-000000000046" of customer "00000000-0000-0000-0000-000000000047"
+        When I fetch inventories "test41" and "test42" of type "pc" for vessel "00000000-0000-0000-0000-000000000046" of customer "00000000-0000-0000-0000-000000000047"
+        Then I can read list of 1 inventories
+        And Inventory at position 0 has ID "test41" and type "pc"
+        And Inventory "test42" of type "pc" is reported as missing
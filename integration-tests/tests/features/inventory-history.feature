@@ -0,0 +1,18 @@
+##
+# This file is part of the IVMS Online.
+#
+# @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+##
+
+Feature: Inventory management
+
+    Scenario: Reading inventory history
+        Given There is an inventory "test38" of type "pc" for vessel "00000000-0000-0000-0000-000000000042" of customer "00000000-0000-0000-0000-000000000043" with serial number "qwerdn", AWS instance ID "abdn" and creation date "2011-01-30T14:58:00+01:00"
+        When I update inventory "test38" of type "pc" for vessel "00000000-0000-0000-0000-000000000042" of customer "00000000-0000-0000-0000-000000000043" with serial number "qwerdo"
+        And I change status of inventory "test38" of type "pc" for vessel "00000000-0000-0000-0000-000000000042" of customer "00000000-0000-0000-0000-000000000043" to "faulty"
+        And I delete inventory "test38" of type "pc" for vessel "00000000-0000-0000-0000-000000000042" of customer "00000000-0000-0000-0000-000000000043"
+        And I read history of inventory "test38" of type "pc" for vessel "00000000-0000-0000-0000-000000000042" of customer "00000000-0000-0000-0000-000000000043"
+        Then I can read history of 3 entries
+        And History entry at position 0 has change type "updated"
+        And History entry at position 1 has change type "statusChanged"
+        And History entry at position 2 has change type "deleted"
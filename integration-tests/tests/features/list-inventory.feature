@@ -14,9 +14,41 @@ Feature: Inventory management
         And Inventory at position 0 has ID "test4" and type "pc"
         And Inventory at position 1 has ID "test5" and type "pc"
 
-    Scenario: Listing inventory next page
+    Scenario: Listing inventory with forged page token
         Given There is an inventory "test6" of type "pc" for vessel "00000000-0000-0000-0000-00000000000c" of customer "00000000-0000-0000-0000-00000000000d" with serial number "qwerti", AWS instance ID "abcf" and creation date "2017-11-11T16:00:00+02:00"
         And There is an inventory "test7" of type "pc" for vessel "00000000-0000-0000-0000-00000000000c" of customer "00000000-0000-0000-0000-00000000000d" with serial number "qwerto", AWS instance ID "abcg" and creation date "2009-03-23T10:00:00+02:00"
         When I list inventory for vessel "00000000-0000-0000-0000-00000000000c" of customer "00000000-0000-0000-0000-00000000000d" with page token "pc:test6"
+        Then I get "INVALID_PAGE_TOKEN" API error response
+
+    Scenario: Listing inventory of given type
+        Given There is an inventory "test14" of type "pc" for vessel "00000000-0000-0000-0000-000000000016" of customer "00000000-0000-0000-0000-000000000017" with serial number "qwerte", AWS instance ID "abcp" and creation date "2011-01-30T14:58:00+01:00"
+        And There is an inventory "test15" of type "radar" for vessel "00000000-0000-0000-0000-000000000016" of customer "00000000-0000-0000-0000-000000000017" with serial number "qwertf", AWS instance ID "abcq" and creation date "2015-07-02T03:20:00+02:00"
+        When I list inventory of type "radar" for vessel "00000000-0000-0000-0000-000000000016" of customer "00000000-0000-0000-0000-000000000017"
         Then I can read list of 1 inventories
-        And Inventory at position 0 has ID "test7" and type "pc"
+        And Inventory at position 0 has ID "test15" and type "radar"
+
+    Scenario: Listing inventory of all customer's vessels
+        Given There is an inventory "test21" of type "pc" for vessel "00000000-0000-0000-0000-00000000001f" of customer "00000000-0000-0000-0000-000000000020" with serial number "qwertg", AWS instance ID "abcw" and creation date "2011-01-30T14:58:00+01:00"
+        And There is an inventory "test22" of type "radar" for vessel "00000000-0000-0000-0000-000000000021" of customer "00000000-0000-0000-0000-000000000020" with serial number "qwerth", AWS instance ID "abcx" and creation date "2015-07-02T03:20:00+02:00"
+        When I list inventory of all vessels of customer "00000000-0000-0000-0000-000000000020"
+        Then I can read list of 2 inventories
+        And Inventory at position 0 has ID "test21" and type "pc"
+        And Inventory at position 0 is located on vessel "00000000-0000-0000-0000-00000000001f"
+        And Inventory at position 1 has ID "test22" and type "radar"
+        And Inventory at position 1 is located on vessel "00000000-0000-0000-0000-000000000021"
+
+    Scenario: Listing inventory next page
+        Given There is an inventory "test23" of type "pc" for vessel "00000000-0000-0000-0000-000000000022" of customer "00000000-0000-0000-0000-000000000023" with serial number "qwertj", AWS instance ID "abcy" and creation date "2017-11-11T16:00:00+02:00"
+        And There is an inventory "test24" of type "pc" for vessel "00000000-0000-0000-0000-000000000022" of customer "00000000-0000-0000-0000-000000000023" with serial number "qwertk", AWS instance ID "abcz" and creation date "2009-03-23T10:00:00+02:00"
+        When I list inventory for vessel "00000000-0000-0000-0000-000000000022" of customer "00000000-0000-0000-0000-000000000023" with limit 1
+        And I list next page of inventory for vessel "00000000-0000-0000-0000-000000000022" of customer "00000000-0000-0000-0000-000000000023" with limit 1
+        Then I can read list of 1 inventories
+        And Inventory at position 0 has ID "test24" and type "pc"
+
+    Scenario: Listing inventory in descending order
+        Given There is an inventory "test25" of type "pc" for vessel "00000000-0000-0000-0000-000000000024" of customer "00000000-0000-0000-0000-000000000025" with serial number "qwertl", AWS instance ID "abda" and creation date "2017-11-11T16:00:00+02:00"
+        And There is an inventory "test26" of type "radar" for vessel "00000000-0000-0000-0000-000000000024" of customer "00000000-0000-0000-0000-000000000025" with serial number "qwertz", AWS instance ID "abdb" and creation date "2009-03-23T10:00:00+02:00"
+        When I list inventory for vessel "00000000-0000-0000-0000-000000000024" of customer "00000000-0000-0000-0000-000000000025" in descending order
+        Then I can read list of 2 inventories
+        And Inventory at position 0 has ID "test26" and type "radar"
+        And Inventory at position 1 has ID "test25" and type "pc"
@@ -0,0 +1,15 @@
+##
+# This file is part of the IVMS Online.
+#
+# @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+##
+
+Feature: Inventory management
+
+    Scenario: Locating inventory by serial number
+        Given There is an inventory "test16" of type "radar" for vessel "00000000-0000-0000-0000-000000000018" of customer "00000000-0000-0000-0000-000000000019" with serial number "rma-0001", AWS instance ID "abcr" and creation date "2011-01-30T14:58:00+01:00"
+        And There is an inventory "test17" of type "radar" for vessel "00000000-0000-0000-0000-000000000018" of customer "00000000-0000-0000-0000-00000000001a" with serial number "rma-0001", AWS instance ID "abcs" and creation date "2015-07-02T03:20:00+02:00"
+        When I locate inventory with serial number "rma-0001" of customer "00000000-0000-0000-0000-000000000019"
+        Then I can read list of 1 inventories
+        And Inventory at position 0 has ID "test16" and type "radar"
+        And Inventory at position 0 is located on vessel "00000000-0000-0000-0000-000000000018"
@@ -0,0 +1,18 @@
+##
+# This file is part of the IVMS Online.
+#
+# @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+##
+
+Feature: Inventory management
+
+    Scenario: Resolving AWS instance ID
+        Given There is an inventory "test18" of type "pc" for vessel "00000000-0000-0000-0000-00000000001b" of customer "00000000-0000-0000-0000-00000000001c" with serial number "qwerti", AWS instance ID "abct" and creation date "2011-01-30T14:58:00+01:00"
+        When I resolve AWS instance ID "abct"
+        Then I can read vessel "00000000-0000-0000-0000-00000000001b" of customer "00000000-0000-0000-0000-00000000001c"
+        And I can read inventory type as "pc"
+        And I can read inventory ID as "test18"
+
+    Scenario: Resolving unknown AWS instance ID
+        When I resolve AWS instance ID "abcu"
+        Then I get "AWS_INSTANCE_NOT_FOUND" API error response
@@ -0,0 +1,21 @@
+##
+# This file is part of the IVMS Online.
+#
+# @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+##
+
+Feature: Inventory management
+
+    Scenario: Transferring inventory to another vessel
+        Given There is an inventory "test43" of type "pc" for vessel "00000000-0000-0000-0000-000000000048" of customer "00000000-0000-0000-0000-000000000049" with serial number "qwerdr", AWS instance ID "abdq" and creation date "2011-01-30T14:58:00+01:00"
+        When I transfer inventory "test43" of type "pc" for vessel "00000000-0000-0000-0000-000000000048" of customer "00000000-0000-0000-0000-000000000049" to vessel "00000000-0000-0000-0000-000000000050" of customer "00000000-0000-0000-0000-000000000049"
+        Then I can read vessel "00000000-0000-0000-0000-000000000050" of customer "00000000-0000-0000-0000-000000000049"
+        And I can read inventory creation date as "2011-01-30T14:58:00+01:00"
+        And Inventory "test43" of type "pc" for vessel "00000000-0000-0000-0000-000000000048" of customer "00000000-0000-0000-0000-000000000049" does not exist
+        And Inventory "test43" of type "pc" for vessel "00000000-0000-0000-0000-000000000050" of customer "00000000-0000-0000-0000-000000000049" has serial number "qwerdr" and creation date "2011-01-30T14:58:00+01:00"
+
+    Scenario: Transferring inventory to another customer
+        Given There is an inventory "test44" of type "pc" for vessel "00000000-0000-0000-0000-000000000051" of customer "00000000-0000-0000-0000-000000000052" with serial number "qwerds", AWS instance ID "abdr" and creation date "2011-01-30T14:58:00+01:00"
+        When I transfer inventory "test44" of type "pc" for vessel "00000000-0000-0000-0000-000000000051" of customer "00000000-0000-0000-0000-000000000052" to vessel "00000000-0000-0000-0000-000000000053" of customer "00000000-0000-0000-0000-000000000054"
+        Then I get "TRANSFER_ACROSS_CUSTOMERS" API error response
+        And Inventory "test44" of type "pc" for vessel "00000000-0000-0000-0000-000000000051" of customer "00000000-0000-0000-0000-000000000052" has serial number "qwerds" and creation date "2011-01-30T14:58:00+01:00"
@@ -0,0 +1,25 @@
+##
+# This file is part of the IVMS Online.
+#
+# @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+##
+
+Feature: Inventory management
+
+    Scenario: Changing inventory status
+        Given There is an inventory "test31" of type "pc" for vessel "00000000-0000-0000-0000-000000000030" of customer "00000000-0000-0000-0000-000000000031" with serial number "qwertu", AWS instance ID "abdh" and creation date "2011-01-30T14:58:00+01:00"
+        When I change status of inventory "test31" of type "pc" for vessel "00000000-0000-0000-0000-000000000030" of customer "00000000-0000-0000-0000-000000000031" to "faulty"
+        Then I can read inventory status as "faulty"
+
+    Scenario: Listing inventory by status
+        Given There is an inventory "test32" of type "pc" for vessel "00000000-0000-0000-0000-000000000032" of customer "00000000-0000-0000-0000-000000000033" with serial number "qwerti", AWS instance ID "abdi" and creation date "2011-01-30T14:58:00+01:00"
+        And There is an inventory "test33" of type "pc" for vessel "00000000-0000-0000-0000-000000000032" of customer "00000000-0000-0000-0000-000000000033" with serial number "qwerto", AWS instance ID "abdj" and creation date "2015-07-02T03:20:00+02:00"
+        When I change status of inventory "test33" of type "pc" for vessel "00000000-0000-0000-0000-000000000032" of customer "00000000-0000-0000-0000-000000000033" to "decommissioned"
+        And I list inventory with status "decommissioned" for vessel "00000000-0000-0000-0000-000000000032" of customer "00000000-0000-0000-0000-000000000033"
+        Then I can read list of 1 inventories
+        And Inventory at position 0 has ID "test33" and type "pc"
+
+    Scenario: Changing inventory status against lifecycle
+        Given There is an inventory "test34" of type "pc" for vessel "00000000-0000-0000-0000-000000000034" of customer "00000000-0000-0000-0000-000000000035" with serial number "qwertl", AWS instance ID "abdk" and creation date "2011-01-30T14:58:00+01:00"
+        When I change status of inventory "test34" of type "pc" for vessel "00000000-0000-0000-0000-000000000034" of customer "00000000-0000-0000-0000-000000000035" to "ordered"
+        Then I get "INVALID_STATUS_TRANSITION" API error response
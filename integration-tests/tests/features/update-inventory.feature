@@ -0,0 +1,36 @@
+##
+# This file is part of the IVMS Online.
+#
+# @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+##
+
+Feature: Inventory management
+
+    Scenario: Updating inventory
+        Given There is an inventory "test9" of type "pc" for vessel "00000000-0000-0000-0000-00000000000e" of customer "00000000-0000-0000-0000-00000000000f" with serial number "qwerta", AWS instance ID "abck" and creation date "2011-01-30T14:58:00+01:00"
+        When I update inventory "test9" of type "pc" for vessel "00000000-0000-0000-0000-00000000000e" of customer "00000000-0000-0000-0000-00000000000f" with serial number "asdfgh"
+        Then I can read inventory serial number as "asdfgh"
+        And I can read inventory AWS instance ID as "abck"
+        And Inventory "test9" of type "pc" for vessel "00000000-0000-0000-0000-00000000000e" of customer "00000000-0000-0000-0000-00000000000f" has serial number "asdfgh" and creation date "2011-01-30T14:58:00+01:00"
+
+    Scenario: Clearing inventory field
+        Given There is an inventory "test10" of type "station" for vessel "00000000-0000-0000-0000-00000000000e" of customer "00000000-0000-0000-0000-00000000000f" with serial number "qwertb", AWS instance ID "abcl" and creation date "2015-07-02T03:20:00+02:00"
+        When I clear AWS instance ID of inventory "test10" of type "station" for vessel "00000000-0000-0000-0000-00000000000e" of customer "00000000-0000-0000-0000-00000000000f"
+        Then Inventory "test10" of type "station" for vessel "00000000-0000-0000-0000-00000000000e" of customer "00000000-0000-0000-0000-00000000000f" has no AWS instance ID
+
+    Scenario: Updating non-existing inventory
+        Given There is no inventory "test11" of type "pc" for vessel "00000000-0000-0000-0000-000000000010" of customer "00000000-0000-0000-0000-000000000011"
+        When I update inventory "test11" of type "pc" for vessel "00000000-0000-0000-0000-000000000010" of customer "00000000-0000-0000-0000-000000000011" with serial number "asdfgh"
+        Then I get "INVENTORY_NOT_FOUND" API error response
+        And Inventory "test11" of type "pc" for vessel "00000000-0000-0000-0000-000000000010" of customer "00000000-0000-0000-0000-000000000011" does not exist
+
+    Scenario: Updating outdated inventory
+        Given There is an inventory "test13" of type "pc" for vessel "00000000-0000-0000-0000-000000000014" of customer "00000000-0000-0000-0000-000000000015" with serial number "qwertd", AWS instance ID "abco" and creation date "2011-01-30T14:58:00+01:00"
+        When I update inventory "test13" of type "pc" for vessel "00000000-0000-0000-0000-000000000014" of customer "00000000-0000-0000-0000-000000000015" with serial number "asdfgk" expecting version 4
+        Then I get "VERSION_CONFLICT" API error response
+        And Inventory "test13" of type "pc" for vessel "00000000-0000-0000-0000-000000000014" of customer "00000000-0000-0000-0000-000000000015" has serial number "qwertd" and creation date "2011-01-30T14:58:00+01:00"
+
+    Scenario: Clearing required inventory field
+        Given There is an inventory "test27" of type "pc" for vessel "00000000-0000-0000-0000-000000000028" of customer "00000000-0000-0000-0000-000000000029" with serial number "qwertv", AWS instance ID "abdd" and creation date "2015-07-02T03:20:00+02:00"
+        When I clear AWS instance ID of inventory "test27" of type "pc" for vessel "00000000-0000-0000-0000-000000000028" of customer "00000000-0000-0000-0000-000000000029"
+        Then I get "VALIDATION_FAILED" API error response
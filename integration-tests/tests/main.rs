@@ -8,6 +8,7 @@
 
 use aws_config::load_from_env;
 use aws_sdk_dynamodb::types::AttributeValue::S;
+use aws_sdk_dynamodb::types::ReturnValue;
 use aws_sdk_dynamodb::Client as DynamoDbClient;
 use aws_sdk_lambda::error::SdkError;
 use aws_sdk_lambda::operation::invoke::{InvokeError, InvokeOutput};
@@ -15,7 +16,7 @@ use aws_sdk_lambda::Client as LambdaClient;
 use aws_smithy_types::Blob;
 use cucumber::{given, then, when, World};
 use futures::future::join_all;
-use serde_json::{from_slice, json, to_vec, Value};
+use serde_json::{from_slice, from_str, json, to_vec, Value};
 use std::collections::HashMap;
 use std::env::{var, VarError};
 use std::future::join;
@@ -34,10 +35,18 @@ macro_rules! serialize_blob {
 struct TestWorld {
     // initialization scope
     inventory_table: String,
+    batch_creator_lambda: String,
     creator_lambda: String,
     deleter_lambda: String,
     fetcher_lambda: String,
+    history_lambda: String,
     lister_lambda: String,
+    locator_lambda: String,
+    resolver_lambda: String,
+    restorer_lambda: String,
+    transferrer_lambda: String,
+    transitioner_lambda: String,
+    updater_lambda: String,
     dynamodb: DynamoDbClient,
     lambda: LambdaClient,
     // test run scope
@@ -55,10 +64,18 @@ impl TestWorld {
 
         Ok(Self {
             inventory_table: var("INVENTORY_TABLE")?,
+            batch_creator_lambda: var("BATCH_CREATOR_LAMBDA")?,
             creator_lambda: var("CREATOR_LAMBDA")?,
             deleter_lambda: var("DELETER_LAMBDA")?,
             fetcher_lambda: var("FETCHER_LAMBDA")?,
+            history_lambda: var("HISTORY_LAMBDA")?,
             lister_lambda: var("LISTER_LAMBDA")?,
+            locator_lambda: var("LOCATOR_LAMBDA")?,
+            resolver_lambda: var("RESOLVER_LAMBDA")?,
+            restorer_lambda: var("RESTORER_LAMBDA")?,
+            transferrer_lambda: var("TRANSFERRER_LAMBDA")?,
+            transitioner_lambda: var("TRANSITIONER_LAMBDA")?,
+            updater_lambda: var("UPDATER_LAMBDA")?,
             dynamodb: DynamoDbClient::new(config),
             lambda: LambdaClient::new(config),
             cleanup_keys: vec![],
@@ -81,15 +98,63 @@ async fn delete_inventory(
     if let (Some(customer_id), Some(vessel_id), Some(inventory_type), Some(inventory_id)) =
         (customer_id, vessel_id, inventory_type, inventory_id)
     {
-        world
+        let deleted = world
             .dynamodb
             .delete_item()
             .table_name(world.inventory_table.as_str())
             .key("customerAndVesselId", S(format!("{customer_id}:{vessel_id}")))
             .key("inventoryKey", S(format!("{inventory_type}:{inventory_id}")))
+            .return_values(ReturnValue::AllOld)
+            .send()
+            .await
+            .unwrap()
+            .attributes;
+
+        // audit trail of the inventory
+        let history = world
+            .dynamodb
+            .query()
+            .table_name(world.inventory_table.as_str())
+            .key_condition_expression("customerAndVesselId = :customerAndVesselId")
+            .expression_attribute_values(
+                ":customerAndVesselId",
+                S(format!(
+                    "history:{customer_id}:{vessel_id}:{inventory_type}:{inventory_id}"
+                )),
+            )
             .send()
             .await
-            .unwrap();
+            .unwrap()
+            .items
+            .unwrap_or_default();
+        for entry in history {
+            world
+                .dynamodb
+                .delete_item()
+                .table_name(world.inventory_table.as_str())
+                .key("customerAndVesselId", entry["customerAndVesselId"].clone())
+                .key("inventoryKey", entry["inventoryKey"].clone())
+                .send()
+                .await
+                .unwrap();
+        }
+
+        // releases AWS instance ID reservation
+        if let Some(aws_instance_id) = deleted
+            .as_ref()
+            .and_then(|item| item.get("awsInstanceId"))
+            .and_then(|value| value.as_s().ok())
+        {
+            world
+                .dynamodb
+                .delete_item()
+                .table_name(world.inventory_table.as_str())
+                .key("customerAndVesselId", S(format!("awsInstanceId:{aws_instance_id}")))
+                .key("inventoryKey", S("awsInstanceId".into()))
+                .send()
+                .await
+                .unwrap();
+        }
     }
 }
 
@@ -97,7 +162,10 @@ async fn list_inventory(
     world: &TestWorld,
     customer_id: String,
     vessel_id: String,
+    inventory_type: Option<String>,
     page_token: Option<String>,
+    limit: Option<u32>,
+    order: Option<&str>,
 ) -> Result<InvokeOutput, SdkError<InvokeError>> {
     world
         .lambda
@@ -106,13 +174,16 @@ async fn list_inventory(
         .payload(serialize_blob!({
             "customerId": customer_id,
             "vesselId": vessel_id,
+            "inventoryType": inventory_type,
             "pageToken": page_token,
+            "limit": limit,
+            "order": order,
         }))
         .send()
         .await
 }
 
-fn extract_list(response: &Option<Result<InvokeOutput, SdkError<InvokeError>>>) -> Vec<Value> {
+fn extract_list(response: &Option<Result<InvokeOutput, SdkError<InvokeError>>>, key: &str) -> Vec<Value> {
     let response: HashMap<String, Value> = from_slice(
         response
             .as_ref()
@@ -123,7 +194,21 @@ fn extract_list(response: &Option<Result<InvokeOutput, SdkError<InvokeError>>>)
     )
     .unwrap();
 
-    response["inventory"].as_array().unwrap().to_owned()
+    response[key].as_array().unwrap().to_owned()
+}
+
+fn extract_page_token(response: &Option<Result<InvokeOutput, SdkError<InvokeError>>>) -> Option<String> {
+    let response: HashMap<String, Value> = from_slice(
+        response
+            .as_ref()
+            .and_then(|response| response.as_ref().ok())
+            .and_then(|response| response.payload())
+            .unwrap()
+            .as_ref(),
+    )
+    .unwrap();
+
+    response["pageToken"].as_str().map(String::from)
 }
 
 #[tokio_main]
@@ -189,6 +274,10 @@ async fn there_is_an_inventory(
         .table_name(world.inventory_table.as_str())
         .item("customerAndVesselId", S(format!("{customer_id}:{vessel_id}")))
         .item("inventoryKey", S(format!("{inventory_type}:{inventory_id}")))
+        .item(
+            "vesselAndInventoryKey",
+            S(format!("{vessel_id}#{inventory_type}:{inventory_id}")),
+        )
         .item("customerId", S(customer_id))
         .item("vesselId", S(vessel_id))
         .item("inventoryType", S(inventory_type))
@@ -245,6 +334,53 @@ async fn i_delete_inventory(
     );
 }
 
+#[when(
+    expr = "I batch create inventories {string} and {string} of type {string} for vessel {string} of customer {string}"
+)]
+async fn i_batch_create_inventories(
+    world: &mut TestWorld,
+    first_inventory_id: String,
+    second_inventory_id: String,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+) {
+    for inventory_id in [&first_inventory_id, &second_inventory_id] {
+        world.cleanup_keys.push((
+            customer_id.clone(),
+            vessel_id.clone(),
+            inventory_type.clone(),
+            inventory_id.clone(),
+        ));
+    }
+
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.batch_creator_lambda.to_string())
+            .payload(serialize_blob!({
+                "customerId": customer_id,
+                "vesselId": vessel_id,
+                "inventory": [
+                    {
+                        "inventoryType": inventory_type,
+                        "inventoryId": first_inventory_id,
+                    },
+                    {
+                        "inventoryType": inventory_type,
+                        "inventoryId": second_inventory_id,
+                    },
+                ],
+            }))
+            .send()
+            .await,
+    );
+
+    world.customer_id = Some(customer_id);
+    world.vessel_id = Some(vessel_id);
+}
+
 #[when(
     expr = "I create inventory {string} of type {string} for vessel {string} of customer {string} with serial number {string} and AWS instance ID {string}"
 )]
@@ -257,6 +393,13 @@ async fn i_create_inventory(
     serial_number: String,
     aws_instance_id: String,
 ) {
+    world.cleanup_keys.push((
+        customer_id.clone(),
+        vessel_id.clone(),
+        inventory_type.clone(),
+        inventory_id.clone(),
+    ));
+
     world.invoke_response = Some(
         world
             .lambda
@@ -278,6 +421,51 @@ async fn i_create_inventory(
     world.vessel_id = Some(vessel_id);
 }
 
+#[when(
+    expr = "I create inventory {string} of type {string} for vessel {string} of customer {string} with AWS instance ID {string} and {string} attribute {string}"
+)]
+async fn i_create_inventory_with_attribute(
+    world: &mut TestWorld,
+    inventory_id: String,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+    aws_instance_id: String,
+    attribute: String,
+    value: String,
+) {
+    world.cleanup_keys.push((
+        customer_id.clone(),
+        vessel_id.clone(),
+        inventory_type.clone(),
+        inventory_id.clone(),
+    ));
+
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.creator_lambda.to_string())
+            .payload(serialize_blob!({
+                "customerId": customer_id,
+                "vesselId": vessel_id,
+                "inventoryType": inventory_type,
+                "inventoryId": inventory_id,
+                "awsInstanceId": aws_instance_id,
+                "attributes": {
+                    attribute: {
+                        "string": value,
+                    },
+                },
+            }))
+            .send()
+            .await,
+    );
+
+    world.customer_id = Some(customer_id);
+    world.vessel_id = Some(vessel_id);
+}
+
 #[when(expr = "I fetch inventory {string} of type {string} for vessel {string} of customer {string}")]
 async fn i_fetch_inventory(
     world: &mut TestWorld,
@@ -302,14 +490,337 @@ async fn i_fetch_inventory(
     );
 }
 
+#[when(expr = "I fetch inventories {string} and {string} of type {string} for vessel {string} of customer {string}")]
+async fn i_fetch_inventories(
+    world: &mut TestWorld,
+    first_inventory_id: String,
+    second_inventory_id: String,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+) {
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.fetcher_lambda.to_string())
+            .payload(serialize_blob!({
+                "customerId": customer_id,
+                "vesselId": vessel_id,
+                "keys": [
+                    {
+                        "inventoryType": inventory_type,
+                        "inventoryId": first_inventory_id,
+                    },
+                    {
+                        "inventoryType": inventory_type,
+                        "inventoryId": second_inventory_id,
+                    },
+                ],
+            }))
+            .send()
+            .await,
+    );
+}
+
 #[when(expr = "I list inventory for vessel {string} of customer {string}")]
 async fn i_list_inventory(world: &mut TestWorld, vessel_id: String, customer_id: String) {
-    world.invoke_response = Some(list_inventory(world, customer_id, vessel_id, None).await);
+    world.invoke_response = Some(list_inventory(world, customer_id, vessel_id, None, None, None, None).await);
+}
+
+#[when(expr = "I list inventory of type {string} for vessel {string} of customer {string}")]
+async fn i_list_inventory_by_type(
+    world: &mut TestWorld,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+) {
+    world.invoke_response =
+        Some(list_inventory(world, customer_id, vessel_id, Some(inventory_type), None, None, None).await);
 }
 
 #[when(expr = "I list inventory for vessel {string} of customer {string} with page token {string}")]
 async fn i_list_inventory_page(world: &mut TestWorld, vessel_id: String, customer_id: String, page_token: String) {
-    world.invoke_response = Some(list_inventory(world, customer_id, vessel_id, Some(page_token)).await);
+    world.invoke_response =
+        Some(list_inventory(world, customer_id, vessel_id, None, Some(page_token), None, None).await);
+}
+
+#[when(expr = "I list inventory for vessel {string} of customer {string} with limit {int}")]
+async fn i_list_inventory_limit(world: &mut TestWorld, vessel_id: String, customer_id: String, limit: u32) {
+    world.invoke_response = Some(list_inventory(world, customer_id, vessel_id, None, None, Some(limit), None).await);
+}
+
+#[when(expr = "I list next page of inventory for vessel {string} of customer {string} with limit {int}")]
+async fn i_list_inventory_next_page(world: &mut TestWorld, vessel_id: String, customer_id: String, limit: u32) {
+    let page_token = extract_page_token(&world.invoke_response);
+
+    world.invoke_response =
+        Some(list_inventory(world, customer_id, vessel_id, None, page_token, Some(limit), None).await);
+}
+
+#[when(expr = "I list inventory for vessel {string} of customer {string} in descending order")]
+async fn i_list_inventory_descending(world: &mut TestWorld, vessel_id: String, customer_id: String) {
+    world.invoke_response = Some(list_inventory(world, customer_id, vessel_id, None, None, None, Some("desc")).await);
+}
+
+#[when(expr = "I list inventory of all vessels of customer {string}")]
+async fn i_list_customer_inventory(world: &mut TestWorld, customer_id: String) {
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.lister_lambda.to_string())
+            .payload(serialize_blob!({
+                "customerId": customer_id,
+                "scope": "customer",
+            }))
+            .send()
+            .await,
+    );
+}
+
+#[when(expr = "I list inventory with status {string} for vessel {string} of customer {string}")]
+async fn i_list_inventory_by_status(world: &mut TestWorld, status: String, vessel_id: String, customer_id: String) {
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.lister_lambda.to_string())
+            .payload(serialize_blob!({
+                "customerId": customer_id,
+                "vesselId": vessel_id,
+                "status": status,
+            }))
+            .send()
+            .await,
+    );
+}
+
+#[when(expr = "I locate inventory with serial number {string} of customer {string}")]
+async fn i_locate_inventory(world: &mut TestWorld, serial_number: String, customer_id: String) {
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.locator_lambda.to_string())
+            .payload(serialize_blob!({
+                "customerId": customer_id,
+                "serialNumber": serial_number,
+            }))
+            .send()
+            .await,
+    );
+}
+
+#[when(expr = "I resolve AWS instance ID {string}")]
+async fn i_resolve_aws_instance_id(world: &mut TestWorld, aws_instance_id: String) {
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.resolver_lambda.to_string())
+            .payload(serialize_blob!({
+                "awsInstanceId": aws_instance_id,
+            }))
+            .send()
+            .await,
+    );
+}
+
+#[when(
+    expr = "I delete inventory {string} of type {string} for vessel {string} of customer {string} failing if it is missing"
+)]
+async fn i_delete_inventory_fail_if_missing(
+    world: &mut TestWorld,
+    inventory_id: String,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+) {
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.deleter_lambda.to_string())
+            .payload(serialize_blob!({
+                "customerId": customer_id,
+                "vesselId": vessel_id,
+                "inventoryType": inventory_type,
+                "inventoryId": inventory_id,
+                "failIfMissing": true,
+            }))
+            .send()
+            .await,
+    );
+}
+
+#[when(expr = "I read history of inventory {string} of type {string} for vessel {string} of customer {string}")]
+async fn i_read_inventory_history(
+    world: &mut TestWorld,
+    inventory_id: String,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+) {
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.history_lambda.to_string())
+            .payload(serialize_blob!({
+                "customerId": customer_id,
+                "vesselId": vessel_id,
+                "inventoryType": inventory_type,
+                "inventoryId": inventory_id,
+            }))
+            .send()
+            .await,
+    );
+}
+
+#[when(expr = "I restore inventory {string} of type {string} for vessel {string} of customer {string}")]
+async fn i_restore_inventory(
+    world: &mut TestWorld,
+    inventory_id: String,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+) {
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.restorer_lambda.to_string())
+            .payload(serialize_blob!({
+                "customerId": customer_id,
+                "vesselId": vessel_id,
+                "inventoryType": inventory_type,
+                "inventoryId": inventory_id,
+            }))
+            .send()
+            .await,
+    );
+}
+
+#[when(
+    expr = "I transfer inventory {string} of type {string} for vessel {string} of customer {string} to vessel {string} of customer {string}"
+)]
+async fn i_transfer_inventory(
+    world: &mut TestWorld,
+    inventory_id: String,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+    target_vessel_id: String,
+    target_customer_id: String,
+) {
+    world.cleanup_keys.push((
+        target_customer_id.clone(),
+        target_vessel_id.clone(),
+        inventory_type.clone(),
+        inventory_id.clone(),
+    ));
+
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.transferrer_lambda.to_string())
+            .payload(serialize_blob!({
+                "customerId": customer_id,
+                "vesselId": vessel_id,
+                "inventoryType": inventory_type,
+                "inventoryId": inventory_id,
+                "targetCustomerId": target_customer_id,
+                "targetVesselId": target_vessel_id,
+            }))
+            .send()
+            .await,
+    );
+}
+
+#[when(
+    expr = "I change status of inventory {string} of type {string} for vessel {string} of customer {string} to {string}"
+)]
+async fn i_change_inventory_status(
+    world: &mut TestWorld,
+    inventory_id: String,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+    status: String,
+) {
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.transitioner_lambda.to_string())
+            .payload(serialize_blob!({
+                "customerId": customer_id,
+                "vesselId": vessel_id,
+                "inventoryType": inventory_type,
+                "inventoryId": inventory_id,
+                "status": status,
+                "reason": "Lifecycle test.",
+            }))
+            .send()
+            .await,
+    );
+}
+
+#[when(
+    expr = "I update inventory {string} of type {string} for vessel {string} of customer {string} with serial number {string}"
+)]
+async fn i_update_inventory(
+    world: &mut TestWorld,
+    inventory_id: String,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+    serial_number: String,
+) {
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.updater_lambda.to_string())
+            .payload(serialize_blob!({
+                "customerId": customer_id,
+                "vesselId": vessel_id,
+                "inventoryType": inventory_type,
+                "inventoryId": inventory_id,
+                "serialNumber": serial_number,
+            }))
+            .send()
+            .await,
+    );
+}
+
+#[when(
+    expr = "I clear AWS instance ID of inventory {string} of type {string} for vessel {string} of customer {string}"
+)]
+async fn i_clear_inventory_aws_instance_id(
+    world: &mut TestWorld,
+    inventory_id: String,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+) {
+    world.invoke_response = Some(
+        world
+            .lambda
+            .invoke()
+            .function_name(world.updater_lambda.to_string())
+            .payload(serialize_blob!({
+                "customerId": customer_id,
+                "vesselId": vessel_id,
+                "inventoryType": inventory_type,
+                "inventoryId": inventory_id,
+                "awsInstanceId": null,
+            }))
+            .send()
+            .await,
+    );
 }
 
 // Then …
@@ -335,8 +846,83 @@ async fn inventory_does_not_exist(
         .is_none())
 }
 
+#[then(expr = "Inventory {string} of type {string} for vessel {string} of customer {string} is marked as deleted")]
+async fn inventory_is_marked_as_deleted(
+    world: &mut TestWorld,
+    inventory_id: String,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+) {
+    let item = world
+        .dynamodb
+        .get_item()
+        .table_name(world.inventory_table.as_str())
+        .key("customerAndVesselId", S(format!("{customer_id}:{vessel_id}")))
+        .key("inventoryKey", S(format!("{inventory_type}:{inventory_id}")))
+        .send()
+        .await
+        .unwrap()
+        .item
+        .unwrap();
+
+    assert!(item.contains_key("deletedAt"));
+    assert!(item.contains_key("expiresAt"));
+}
+
+#[then(
+    expr = "Inventory {string} of type {string} for vessel {string} of customer {string} has serial number {string} and creation date {string}"
+)]
+async fn inventory_has_serial_number(
+    world: &mut TestWorld,
+    inventory_id: String,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+    serial_number: String,
+    created_at: String,
+) {
+    let inventory = world
+        .dynamodb
+        .get_item()
+        .table_name(world.inventory_table.as_str())
+        .key("customerAndVesselId", S(format!("{customer_id}:{vessel_id}")))
+        .key("inventoryKey", S(format!("{inventory_type}:{inventory_id}")))
+        .send()
+        .await
+        .unwrap()
+        .item
+        .unwrap();
+
+    assert_eq!(serial_number, *inventory["serialNumber"].as_s().unwrap());
+    assert_eq!(created_at, *inventory["createdAt"].as_s().unwrap());
+}
+
+#[then(expr = "Inventory {string} of type {string} for vessel {string} of customer {string} has no AWS instance ID")]
+async fn inventory_has_no_aws_instance_id(
+    world: &mut TestWorld,
+    inventory_id: String,
+    inventory_type: String,
+    vessel_id: String,
+    customer_id: String,
+) {
+    let inventory = world
+        .dynamodb
+        .get_item()
+        .table_name(world.inventory_table.as_str())
+        .key("customerAndVesselId", S(format!("{customer_id}:{vessel_id}")))
+        .key("inventoryKey", S(format!("{inventory_type}:{inventory_id}")))
+        .send()
+        .await
+        .unwrap()
+        .item
+        .unwrap();
+
+    assert!(!inventory.contains_key("awsInstanceId"));
+}
+
 #[then(expr = "I get {string} API error response")]
-async fn i_get_api_error(world: &mut TestWorld, message: String) {
+async fn i_get_api_error(world: &mut TestWorld, code: String) {
     let response: HashMap<String, String> = from_slice(
         world
             .invoke_response
@@ -347,8 +933,29 @@ async fn i_get_api_error(world: &mut TestWorld, message: String) {
             .as_ref(),
     )
     .unwrap();
+    let envelope: HashMap<String, Value> = from_str(response["errorMessage"].as_str()).unwrap();
 
-    assert_eq!(message, response["errorMessage"]);
+    assert_eq!(code.as_str(), envelope["code"].as_str().unwrap());
+    assert!(!envelope["message"].as_str().unwrap().is_empty());
+    assert!(envelope["details"].is_object());
+    assert!(envelope["requestId"].is_string());
+}
+
+#[then(expr = "I can read vessel {string} of customer {string}")]
+async fn i_can_read_vessel_of_customer(world: &mut TestWorld, vessel_id: String, customer_id: String) {
+    let response: HashMap<String, Value> = from_slice(
+        world
+            .invoke_response
+            .as_ref()
+            .and_then(|response| response.as_ref().ok())
+            .and_then(|response| response.payload())
+            .unwrap()
+            .as_ref(),
+    )
+    .unwrap();
+
+    assert_eq!(customer_id.as_str(), response["customerId"].as_str().unwrap());
+    assert_eq!(vessel_id.as_str(), response["vesselId"].as_str().unwrap());
 }
 
 #[then(expr = "I can read inventory type as {string}")]
@@ -399,6 +1006,25 @@ async fn i_can_read_inventory_serial_number(world: &mut TestWorld, serial_number
     assert_eq!(serial_number.as_str(), response["serialNumber"].as_str().unwrap());
 }
 
+#[then(expr = "I can read inventory {string} attribute as {string}")]
+async fn i_can_read_inventory_attribute(world: &mut TestWorld, attribute: String, value: String) {
+    let response: HashMap<String, Value> = from_slice(
+        world
+            .invoke_response
+            .as_ref()
+            .and_then(|response| response.as_ref().ok())
+            .and_then(|response| response.payload())
+            .unwrap()
+            .as_ref(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        value.as_str(),
+        response["attributes"][attribute.as_str()]["string"].as_str().unwrap()
+    );
+}
+
 #[then(expr = "I can read inventory AWS instance ID as {string}")]
 async fn i_can_read_inventory_aws_instance_id(world: &mut TestWorld, aws_instance_id: String) {
     let response: HashMap<String, Value> = from_slice(
@@ -415,6 +1041,23 @@ async fn i_can_read_inventory_aws_instance_id(world: &mut TestWorld, aws_instanc
     assert_eq!(aws_instance_id.as_str(), response["awsInstanceId"].as_str().unwrap());
 }
 
+#[then(expr = "I can read inventory status as {string}")]
+async fn i_can_read_inventory_status(world: &mut TestWorld, status: String) {
+    let response: HashMap<String, Value> = from_slice(
+        world
+            .invoke_response
+            .as_ref()
+            .and_then(|response| response.as_ref().ok())
+            .and_then(|response| response.payload())
+            .unwrap()
+            .as_ref(),
+    )
+    .unwrap();
+
+    assert_eq!(status.as_str(), response["status"].as_str().unwrap());
+    assert_eq!(status.as_str(), response["statusChange"]["status"].as_str().unwrap());
+}
+
 #[then(expr = "I can read inventory creation date as {string}")]
 async fn i_can_read_inventory_creation_date(world: &mut TestWorld, created_at: String) {
     let response: HashMap<String, Value> = from_slice(
@@ -491,11 +1134,61 @@ async fn inventory_with_that_key_exists(world: &mut TestWorld, serial_number: St
 
 #[then(expr = "I can read list of {int} inventories")]
 async fn i_can_read_list_of_inventories(world: &mut TestWorld, count: usize) {
-    let inventories = extract_list(&world.invoke_response);
+    let inventories = extract_list(&world.invoke_response, "inventory");
 
     assert_eq!(count, inventories.len());
 }
 
+#[then(expr = "Inventory {string} of type {string} is reported as missing")]
+async fn inventory_is_reported_as_missing(world: &mut TestWorld, inventory_id: String, inventory_type: String) {
+    let missing = extract_list(&world.invoke_response, "missing");
+
+    assert!(missing.iter().any(|key| {
+        key["inventoryType"].as_str() == Some(inventory_type.as_str())
+            && key["inventoryId"].as_str() == Some(inventory_id.as_str())
+    }));
+}
+
+#[then(expr = "I can read history of {int} entries")]
+async fn i_can_read_history_of_entries(world: &mut TestWorld, count: usize) {
+    let history = extract_list(&world.invoke_response, "history");
+
+    assert_eq!(count, history.len());
+}
+
+#[then(expr = "History entry at position {int} has change type {string}")]
+async fn history_entry_at_position_has_change_type(world: &mut TestWorld, position: usize, change_type: String) {
+    let history = extract_list(&world.invoke_response, "history");
+    let entry = history[position].as_object().unwrap();
+
+    assert_eq!(change_type, entry["changeType"].as_str().unwrap());
+}
+
+#[then(expr = "Batch result at position {int} is successful")]
+async fn batch_result_at_position_is_successful(world: &mut TestWorld, position: usize) {
+    let results = extract_list(&world.invoke_response, "inventory");
+    let entry = results[position].as_object().unwrap();
+
+    assert!(entry["success"].as_bool().unwrap());
+}
+
+#[then(expr = "Batch result at position {int} failed with {string}")]
+async fn batch_result_at_position_failed_with(world: &mut TestWorld, position: usize, code: String) {
+    let results = extract_list(&world.invoke_response, "inventory");
+    let entry = results[position].as_object().unwrap();
+
+    assert!(!entry["success"].as_bool().unwrap());
+    assert_eq!(code, entry["error"]["code"].as_str().unwrap());
+}
+
+#[then(expr = "Inventory at position {int} is located on vessel {string}")]
+async fn inventory_at_position_is_located_on_vessel(world: &mut TestWorld, position: usize, vessel_id: String) {
+    let inventory = extract_list(&world.invoke_response, "inventory");
+    let entry = inventory[position].as_object().unwrap();
+
+    assert_eq!(vessel_id, entry["vesselId"].as_str().unwrap());
+}
+
 #[then(expr = "Inventory at position {int} has ID {string} and type {string}")]
 async fn inventory_at_position_has_key(
     world: &mut TestWorld,
@@ -503,7 +1196,7 @@ async fn inventory_at_position_has_key(
     inventory_id: String,
     inventory_type: String,
 ) {
-    let inventory = extract_list(&world.invoke_response);
+    let inventory = extract_list(&world.invoke_response, "inventory");
     let entry = inventory[position].as_object().unwrap();
 
     assert_eq!(inventory_type, entry["inventoryType"].as_str().unwrap());
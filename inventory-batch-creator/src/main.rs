@@ -0,0 +1,239 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+#![feature(future_join)]
+
+use chrono::Utc;
+use inventory_core::{
+    run_lambda, validate_attributes, validate_inventory_key, validate_optional_value, ApiError, ErrorEnvelope,
+    Inventory, InventoryAttribute, InventoryDao, InventoryRepository, InventoryStatus, TypeRegistry,
+};
+use lambda_runtime::{Error, LambdaEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::main as tokio_main;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RequestItem {
+    inventory_type: String,
+    inventory_id: String,
+    serial_number: Option<String>,
+    aws_instance_id: Option<String>,
+    #[serde(default)]
+    attributes: HashMap<String, InventoryAttribute>,
+    status: Option<InventoryStatus>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    customer_id: Uuid,
+    vessel_id: Uuid,
+    inventory: Vec<RequestItem>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResponseItem {
+    inventory_type: String,
+    inventory_id: String,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorEnvelope>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    inventory: Vec<ResponseItem>,
+}
+
+impl ResponseItem {
+    fn new(inventory_type: String, inventory_id: String, result: Result<(), ApiError>) -> Self {
+        Self {
+            inventory_type,
+            inventory_id,
+            success: result.is_ok(),
+            error: result.err().map(ErrorEnvelope::from),
+        }
+    }
+}
+
+fn validate(registry: &TypeRegistry, inventory: &Inventory) -> Result<(), ApiError> {
+    validate_inventory_key(&inventory.inventory_type, &inventory.inventory_id)?;
+    validate_optional_value("serialNumber", inventory.serial_number.as_ref())?;
+    validate_optional_value("awsInstanceId", inventory.aws_instance_id.as_ref())?;
+    validate_attributes(&inventory.attributes)?;
+    registry.validate_inventory(inventory)
+}
+
+async fn handle(
+    repository: &dyn InventoryRepository,
+    registry: &TypeRegistry,
+    request: Request,
+) -> Result<Response, ApiError> {
+    let created_at = Utc::now().fixed_offset();
+    let inventories: Vec<Inventory> = request
+        .inventory
+        .into_iter()
+        .map(|item| Inventory {
+            customer_id: request.customer_id,
+            vessel_id: request.vessel_id,
+            inventory_type: item.inventory_type,
+            inventory_id: item.inventory_id,
+            serial_number: item.serial_number,
+            aws_instance_id: item.aws_instance_id,
+            attributes: item.attributes,
+            status: item.status.unwrap_or(InventoryStatus::Ordered),
+            status_change: None,
+            created_at,
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 0,
+        })
+        .collect();
+
+    // invalid entries are reported without stopping the rest of the batch
+    let mut results: Vec<Option<Result<(), ApiError>>> = vec![];
+    let mut valid = vec![];
+    for inventory in &inventories {
+        match validate(registry, inventory) {
+            Ok(_) => {
+                results.push(None);
+                valid.push(inventory.clone());
+            }
+            Err(error) => results.push(Some(Err(error))),
+        }
+    }
+
+    let mut created = repository.batch_create(valid).await?.into_iter();
+
+    Ok(Response {
+        inventory: inventories
+            .into_iter()
+            .zip(results)
+            .map(|(inventory, result)| {
+                ResponseItem::new(
+                    inventory.inventory_type,
+                    inventory.inventory_id,
+                    result.or_else(|| created.next()).unwrap_or(Ok(())),
+                )
+            })
+            .collect(),
+    })
+}
+
+#[tokio_main]
+async fn main() -> Result<(), Error> {
+    let dao = &InventoryDao::load_from_env().await?;
+    let registry = &TypeRegistry::load_from_env()?;
+
+    run_lambda!(move |event: LambdaEvent<Request>| handle(dao, registry, event.payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{handle, Request, Response, ResponseItem};
+    use inventory_core::{
+        ApiError, ErrorCode, InventoryAttribute, InventoryRepository, InventoryStatus, MemoryRepository, TypeRegistry,
+    };
+    use serde_json::{from_str, to_string};
+    use tokio::test as tokio_test;
+    use uuid::{uuid, Uuid};
+
+    const CUSTOMER_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
+    const VESSEL_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000001");
+    const INVENTORY_TYPE: &str = "station";
+    const INVENTORY_ID: &str = "123";
+    const SERIAL_NUMBER: &str = "abc";
+
+    #[test]
+    fn deserialize_request() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventory\":[{{\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\"}},{{\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"456\",\"serialNumber\":\"{SERIAL_NUMBER}\",\"status\":\"installed\",\"attributes\":{{\"manufacturer\":{{\"string\":\"Furuno\"}}}}}}]}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(CUSTOMER_ID, request.customer_id);
+        assert_eq!(VESSEL_ID, request.vessel_id);
+        assert_eq!(2, request.inventory.len());
+        assert_eq!(INVENTORY_TYPE, request.inventory[0].inventory_type);
+        assert_eq!(INVENTORY_ID, request.inventory[0].inventory_id);
+        assert!(request.inventory[0].serial_number.is_none());
+        assert!(request.inventory[0].attributes.is_empty());
+        assert!(request.inventory[0].status.is_none());
+        assert_eq!(Some(SERIAL_NUMBER.to_string()), request.inventory[1].serial_number);
+        assert_eq!(Some(InventoryStatus::Installed), request.inventory[1].status);
+        assert_eq!(
+            InventoryAttribute::String("Furuno".into()),
+            request.inventory[1].attributes["manufacturer"]
+        );
+    }
+
+    #[test]
+    fn serialize_response() {
+        let output = to_string(&Response {
+            inventory: vec![
+                ResponseItem::new(INVENTORY_TYPE.into(), INVENTORY_ID.into(), Ok(())),
+                ResponseItem::new(
+                    INVENTORY_TYPE.into(),
+                    "456".into(),
+                    Err(ApiError::InventoryAlreadyExists(INVENTORY_TYPE.into(), "456".into())),
+                ),
+            ],
+        })
+        .unwrap();
+
+        assert_eq!(
+            format!("{{\"inventory\":[{{\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"success\":true}},{{\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"456\",\"success\":false,\"error\":{{\"code\":\"INVENTORY_ALREADY_EXISTS\",\"message\":\"Inventory already exists.\",\"details\":{{\"inventoryId\":\"456\",\"inventoryType\":\"{INVENTORY_TYPE}\"}},\"retryable\":false,\"requestId\":null}}}}]}}"),
+            output
+        );
+    }
+
+    #[tokio_test]
+    async fn handle_batch() {
+        let repository = MemoryRepository::default();
+        let request: Request = from_str(&format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventory\":[{{\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\"}},{{\"inventoryType\":\"unknown\",\"inventoryId\":\"456\"}},{{\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\"}},{{\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"789\",\"serialNumber\":\"{SERIAL_NUMBER}\",\"status\":\"installed\"}}]}}"
+        ))
+        .unwrap();
+
+        let response = handle(&repository, &TypeRegistry::bundled().unwrap(), request)
+            .await
+            .unwrap();
+        let results: Vec<(&str, bool, Option<ErrorCode>)> = response
+            .inventory
+            .iter()
+            .map(|item| {
+                (
+                    item.inventory_id.as_str(),
+                    item.success,
+                    item.error.as_ref().map(|error| error.code),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (INVENTORY_ID, true, None),
+                ("456", false, Some(ErrorCode::ValidationFailed)),
+                (INVENTORY_ID, false, Some(ErrorCode::InventoryAlreadyExists)),
+                ("789", true, None),
+            ],
+            results
+        );
+
+        let created = repository
+            .get_inventory(CUSTOMER_ID, VESSEL_ID, INVENTORY_TYPE.into(), "789".into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(Some(SERIAL_NUMBER.to_string()), created.serial_number);
+        assert_eq!(InventoryStatus::Installed, created.status);
+    }
+}
@@ -0,0 +1,17 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+use inventory_core::{InventoryDao, RuntimeError};
+use tokio::main as tokio_main;
+
+// one-off migration of existing table, safe to re-run - see docs/developer-guide/db.md
+#[tokio_main(flavor = "current_thread")]
+async fn main() -> Result<(), RuntimeError> {
+    let updated = InventoryDao::load_from_env().await?.backfill_customer_index().await?;
+    println!("Records updated: {updated}");
+
+    Ok(())
+}
@@ -4,28 +4,80 @@
  * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
  */
 
-use crate::RuntimeError;
+use crate::{ErrorKind, InventoryStatus, RuntimeError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum ApiError {
     #[error(transparent)]
     RuntimeError(Box<RuntimeError>),
+    #[error("Request rate limit exceeded, retry later.")]
+    Throttled(#[source] Box<RuntimeError>),
+    #[error("Request did not complete in time, retry later.")]
+    Timeout(#[source] Box<RuntimeError>),
+    #[error("Inventory is being modified concurrently, retry the request.")]
+    ConcurrentModification(#[source] Box<RuntimeError>),
+    #[error("Inventory does not meet the request precondition.")]
+    PreconditionFailed(#[source] Box<RuntimeError>),
+    #[error("Required service resource does not exist.")]
+    ResourceNotFound(#[source] Box<RuntimeError>),
+    #[error("Service is temporarily unavailable, retry later.")]
+    ServiceUnavailable(#[source] Box<RuntimeError>),
     #[error("Inventory not found.")]
     InventoryNotFound(String, String),
+    #[error("Inventory already exists.")]
+    InventoryAlreadyExists(String, String),
+    #[error("Inventory was modified in the meantime, current version is {0}.")]
+    VersionConflict(u64),
+    #[error("AWS instance ID is already assigned to another inventory.")]
+    AwsInstanceIdAlreadyAssigned(String),
+    #[error("AWS instance not found.")]
+    AwsInstanceNotFound(String),
+    #[error("Vessel ID is required for vessel scope listing.")]
+    VesselIdRequired,
+    #[error("Invalid page token.")]
+    InvalidPageToken,
+    #[error("Status can not be changed from {0} to {1}.")]
+    InvalidStatusTransition(InventoryStatus, InventoryStatus),
+    #[error("Inventory can not be transferred to another customer.")]
+    TransferAcrossCustomers,
+    #[error("Invalid {field}: {reason}.")]
+    ValidationFailed { field: String, reason: String },
 }
 
 impl From<RuntimeError> for ApiError {
     fn from(error: RuntimeError) -> Self {
-        ApiError::RuntimeError(error.into())
+        match error.kind() {
+            ErrorKind::Throttled => ApiError::Throttled(error.into()),
+            ErrorKind::Timeout => ApiError::Timeout(error.into()),
+            ErrorKind::Conflict => ApiError::ConcurrentModification(error.into()),
+            ErrorKind::ConditionFailed => ApiError::PreconditionFailed(error.into()),
+            ErrorKind::Transient => ApiError::ServiceUnavailable(error.into()),
+            ErrorKind::NotFound => ApiError::ResourceNotFound(error.into()),
+            ErrorKind::Fatal => ApiError::RuntimeError(error.into()),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{ApiError, RuntimeError};
+    use aws_sdk_dynamodb::operation::update_item::UpdateItemError;
+    use aws_smithy_http::body::SdkBody;
+    use aws_smithy_http::operation::Response;
+    use aws_smithy_http::result::SdkError;
+    use aws_smithy_types::error::ErrorMetadata;
+    use http::Response as HttpResponse;
     use std::env::VarError;
 
+    fn service_error(code: &str) -> RuntimeError {
+        SdkError::service_error(
+            UpdateItemError::generic(ErrorMetadata::builder().code(code).build()),
+            Response::new(HttpResponse::builder().status(400).body(SdkBody::empty()).unwrap()),
+        )
+        .into()
+    }
+
     #[test]
     fn runtime_api_error() {
         match ApiError::from(RuntimeError::ClientConfigLoadingError(VarError::NotPresent)) {
@@ -35,4 +87,44 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn throttled_api_error() {
+        match ApiError::from(RuntimeError::UnprocessedItem("pc:test0".into())) {
+            ApiError::Throttled(_) => {}
+            _ => {
+                panic!("Invalid error type.");
+            }
+        }
+    }
+
+    #[test]
+    fn transient_api_error() {
+        match ApiError::from(RuntimeError::EventRejected("abc".into())) {
+            ApiError::ServiceUnavailable(_) => {}
+            _ => {
+                panic!("Invalid error type.");
+            }
+        }
+    }
+
+    #[test]
+    fn precondition_failed_api_error() {
+        match ApiError::from(service_error("ConditionalCheckFailedException")) {
+            ApiError::PreconditionFailed(_) => {}
+            _ => {
+                panic!("Invalid error type.");
+            }
+        }
+    }
+
+    #[test]
+    fn resource_not_found_api_error() {
+        match ApiError::from(service_error("ResourceNotFoundException")) {
+            ApiError::ResourceNotFound(_) => {}
+            _ => {
+                panic!("Invalid error type.");
+            }
+        }
+    }
 }
@@ -0,0 +1,271 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+use crate::{ApiError, RuntimeError};
+use serde::Serialize;
+use serde_json::{json, to_string, Map, Value};
+use std::fmt::{Display, Formatter, Result};
+
+// codes are a part of the API contract - never rename existing ones, see docs/developer-guide/errors.md
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InventoryNotFound,
+    InventoryAlreadyExists,
+    VersionConflict,
+    AwsInstanceIdAlreadyAssigned,
+    AwsInstanceNotFound,
+    VesselIdRequired,
+    InvalidPageToken,
+    InvalidStatusTransition,
+    TransferAcrossCustomers,
+    ValidationFailed,
+    Throttled,
+    Timeout,
+    ConcurrentModification,
+    PreconditionFailed,
+    ResourceNotFound,
+    ServiceUnavailable,
+    ConfigurationError,
+    StorageError,
+    DataError,
+    EventPublishingError,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorEnvelope {
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Map<String, Value>,
+    pub retryable: bool,
+    pub request_id: Option<String>,
+}
+
+impl ErrorEnvelope {
+    fn new(code: ErrorCode, message: String, details: Value, retryable: bool) -> Self {
+        Self {
+            code,
+            message,
+            details: match details {
+                Value::Object(details) => details,
+                _ => Map::new(),
+            },
+            retryable,
+            request_id: None,
+        }
+    }
+
+    pub fn with_request_id(self, request_id: String) -> Self {
+        Self {
+            request_id: Some(request_id),
+            ..self
+        }
+    }
+}
+
+// Lambda runtime reports error's `Display` as `errorMessage`, so the envelope goes there as JSON document
+impl Display for ErrorEnvelope {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result {
+        match to_string(self) {
+            Ok(json) => write!(formatter, "{json}"),
+            Err(_) => write!(formatter, "{}", self.message),
+        }
+    }
+}
+
+impl From<RuntimeError> for ErrorEnvelope {
+    fn from(error: RuntimeError) -> Self {
+        ApiError::from(error).into()
+    }
+}
+
+// unclassified failure - reported by the area it comes from
+fn runtime_envelope(error: RuntimeError) -> ErrorEnvelope {
+    let (code, details) = match &error {
+        RuntimeError::ClientConfigLoadingError(_)
+        | RuntimeError::TypeRegistryReadError(_)
+        | RuntimeError::TypeRegistryParseError(_) => (ErrorCode::ConfigurationError, Value::Null),
+        RuntimeError::ConfigurationParseError(setting, _) | RuntimeError::ConfigurationValueError(setting) => {
+            (ErrorCode::ConfigurationError, json!({ "setting": setting }))
+        }
+        RuntimeError::UnknownEventSink(sink) => (ErrorCode::ConfigurationError, json!({ "sink": sink })),
+        RuntimeError::BatchGetItemError(_)
+        | RuntimeError::GetItemError(_)
+        | RuntimeError::QueryError(_)
+        | RuntimeError::ScanError(_)
+        | RuntimeError::TransactWriteItemsError(_)
+        | RuntimeError::UpdateItemError(_)
+        | RuntimeError::UnprocessedItem(_)
+        | RuntimeError::DeadlineExceeded => (ErrorCode::StorageError, Value::Null),
+        RuntimeError::DataError(_, _)
+        | RuntimeError::SerializationError(_)
+        | RuntimeError::EventSerializationError(_) => (ErrorCode::DataError, Value::Null),
+        RuntimeError::PutEventsError(_) | RuntimeError::PublishError(_) => {
+            (ErrorCode::EventPublishingError, Value::Null)
+        }
+        RuntimeError::EventRejected(event_id) => (ErrorCode::EventPublishingError, json!({ "eventId": event_id })),
+    };
+    let retryable = error.kind().is_retryable();
+
+    ErrorEnvelope::new(code, error.to_string(), details, retryable)
+}
+
+impl From<ApiError> for ErrorEnvelope {
+    fn from(error: ApiError) -> Self {
+        let message = error.to_string();
+        let (code, details, retryable) = match error {
+            ApiError::RuntimeError(error) => return runtime_envelope(*error),
+            ApiError::Throttled(_) => (ErrorCode::Throttled, Value::Null, true),
+            ApiError::Timeout(_) => (ErrorCode::Timeout, Value::Null, true),
+            ApiError::ConcurrentModification(_) => (ErrorCode::ConcurrentModification, Value::Null, true),
+            ApiError::PreconditionFailed(_) => (ErrorCode::PreconditionFailed, Value::Null, false),
+            ApiError::ResourceNotFound(_) => (ErrorCode::ResourceNotFound, Value::Null, false),
+            ApiError::ServiceUnavailable(_) => (ErrorCode::ServiceUnavailable, Value::Null, true),
+            ApiError::InventoryNotFound(inventory_type, inventory_id) => (
+                ErrorCode::InventoryNotFound,
+                json!({ "inventoryType": inventory_type, "inventoryId": inventory_id }),
+                false,
+            ),
+            ApiError::InventoryAlreadyExists(inventory_type, inventory_id) => (
+                ErrorCode::InventoryAlreadyExists,
+                json!({ "inventoryType": inventory_type, "inventoryId": inventory_id }),
+                false,
+            ),
+            ApiError::VersionConflict(version) => {
+                (ErrorCode::VersionConflict, json!({ "currentVersion": version }), false)
+            }
+            ApiError::AwsInstanceIdAlreadyAssigned(aws_instance_id) => (
+                ErrorCode::AwsInstanceIdAlreadyAssigned,
+                json!({ "awsInstanceId": aws_instance_id }),
+                false,
+            ),
+            ApiError::AwsInstanceNotFound(aws_instance_id) => (
+                ErrorCode::AwsInstanceNotFound,
+                json!({ "awsInstanceId": aws_instance_id }),
+                false,
+            ),
+            ApiError::VesselIdRequired => (ErrorCode::VesselIdRequired, Value::Null, false),
+            ApiError::InvalidPageToken => (ErrorCode::InvalidPageToken, Value::Null, false),
+            ApiError::InvalidStatusTransition(from, to) => (
+                ErrorCode::InvalidStatusTransition,
+                json!({ "from": from, "to": to }),
+                false,
+            ),
+            ApiError::TransferAcrossCustomers => (ErrorCode::TransferAcrossCustomers, Value::Null, false),
+            ApiError::ValidationFailed { field, reason } => (
+                ErrorCode::ValidationFailed,
+                json!({ "field": field, "reason": reason }),
+                false,
+            ),
+        };
+
+        Self::new(code, message, details, retryable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ApiError, ErrorCode, ErrorEnvelope, InventoryStatus, RuntimeError};
+    use aws_sdk_dynamodb::operation::query::QueryError;
+    use aws_sdk_dynamodb::types::AttributeValue::S;
+    use aws_smithy_http::body::SdkBody;
+    use aws_smithy_http::operation::Response;
+    use aws_smithy_http::result::SdkError;
+    use aws_smithy_types::error::ErrorMetadata;
+    use http::Response as HttpResponse;
+    use serde_json::{from_str, json, to_string, Value};
+    use std::env::VarError;
+
+    #[test]
+    fn serialize_envelope() {
+        let envelope =
+            ErrorEnvelope::from(ApiError::InventoryNotFound("pc".into(), "test0".into())).with_request_id("abc".into());
+
+        assert_eq!(
+            "{\"code\":\"INVENTORY_NOT_FOUND\",\"message\":\"Inventory not found.\",\"details\":{\"inventoryId\":\"test0\",\"inventoryType\":\"pc\"},\"retryable\":false,\"requestId\":\"abc\"}",
+            to_string(&envelope).unwrap()
+        );
+    }
+
+    #[test]
+    fn display_envelope() {
+        let envelope = ErrorEnvelope::from(ApiError::VesselIdRequired);
+        let output: Value = from_str(&envelope.to_string()).unwrap();
+
+        assert_eq!("VESSEL_ID_REQUIRED", output["code"]);
+        assert_eq!("Vessel ID is required for vessel scope listing.", output["message"]);
+        assert_eq!(json!({}), output["details"]);
+        assert_eq!(Value::Null, output["requestId"]);
+    }
+
+    #[test]
+    fn api_error_details() {
+        let envelope = ErrorEnvelope::from(ApiError::InvalidStatusTransition(
+            InventoryStatus::InService,
+            InventoryStatus::Ordered,
+        ));
+
+        assert_eq!(ErrorCode::InvalidStatusTransition, envelope.code);
+        assert_eq!("inService", envelope.details["from"]);
+        assert_eq!("ordered", envelope.details["to"]);
+        assert!(!envelope.retryable);
+
+        let envelope = ErrorEnvelope::from(ApiError::VersionConflict(3));
+
+        assert_eq!(ErrorCode::VersionConflict, envelope.code);
+        assert_eq!(3, envelope.details["currentVersion"]);
+    }
+
+    #[test]
+    fn wrapped_runtime_error() {
+        let envelope = ErrorEnvelope::from(ApiError::from(RuntimeError::DataError(
+            S("abc".into()),
+            "version".into(),
+        )));
+
+        assert_eq!(ErrorCode::DataError, envelope.code);
+        assert_eq!("Stored inventory data is malformed.", envelope.message);
+        assert!(envelope.details.is_empty());
+        assert!(!envelope.retryable);
+    }
+
+    #[test]
+    fn classified_runtime_error() {
+        let envelope = ErrorEnvelope::from(RuntimeError::UnprocessedItem("pc:test0".into()));
+
+        assert_eq!(ErrorCode::Throttled, envelope.code);
+        assert_eq!("Request rate limit exceeded, retry later.", envelope.message);
+        assert!(envelope.retryable);
+    }
+
+    #[test]
+    fn resource_not_found_runtime_error() {
+        let envelope = ErrorEnvelope::from(RuntimeError::from(SdkError::service_error(
+            QueryError::generic(ErrorMetadata::builder().code("ResourceNotFoundException").build()),
+            Response::new(HttpResponse::builder().status(400).body(SdkBody::empty()).unwrap()),
+        )));
+
+        assert_eq!(ErrorCode::ResourceNotFound, envelope.code);
+        assert_eq!("Required service resource does not exist.", envelope.message);
+        assert!(!envelope.retryable);
+    }
+
+    #[test]
+    fn runtime_error_details() {
+        let envelope = ErrorEnvelope::from(RuntimeError::ClientConfigLoadingError(VarError::NotPresent));
+
+        assert_eq!(ErrorCode::ConfigurationError, envelope.code);
+        assert_eq!("Service configuration could not be loaded.", envelope.message);
+        assert!(!envelope.retryable);
+
+        let envelope = ErrorEnvelope::from(RuntimeError::UnknownEventSink("kafka".into()));
+
+        assert_eq!(ErrorCode::ConfigurationError, envelope.code);
+        assert_eq!("kafka", envelope.details["sink"]);
+        assert!(!envelope.retryable);
+    }
+}
@@ -0,0 +1,208 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+use crate::events::InventoryEvent;
+use crate::runtime_error::RuntimeError;
+use async_trait::async_trait;
+use aws_config::load_from_env;
+use aws_sdk_eventbridge::types::PutEventsRequestEntry;
+use aws_sdk_eventbridge::Client as EventBridgeClient;
+use aws_sdk_sns::types::MessageAttributeValue;
+use aws_sdk_sns::Client as SnsClient;
+use serde_json::to_string;
+use std::env::var;
+use std::sync::Mutex;
+
+static EVENT_SOURCE: &str = "ivms.inventory";
+
+#[async_trait]
+#[doc = "Destination of published domain events."]
+pub trait EventSink: Send + Sync {
+    async fn publish(&self, event: &InventoryEvent) -> Result<(), RuntimeError>;
+}
+
+/**
+Optional environment variables:
+<dl>
+    <dt><code>EVENT_SINK</code></dt>
+    <dd>Kind of the sink - <code>eventbridge</code> (default) or <code>sns</code>.</dd>
+</dl>
+ */
+pub async fn load_event_sink_from_env() -> Result<Box<dyn EventSink>, RuntimeError> {
+    match var("EVENT_SINK").as_deref() {
+        Ok("eventbridge") | Err(_) => Ok(Box::new(EventBridgeSink::load_from_env().await?)),
+        Ok("sns") => Ok(Box::new(SnsSink::load_from_env().await?)),
+        Ok(sink) => Err(RuntimeError::UnknownEventSink(sink.into())),
+    }
+}
+
+#[doc = "Publishes events to EventBridge bus, with event type as detail type."]
+pub struct EventBridgeSink {
+    client: Box<EventBridgeClient>,
+    event_bus_name: String,
+}
+
+/**
+Required environment variables:
+<dl>
+    <dt><code>EVENT_BUS_NAME</code></dt>
+    <dd>Name of EventBridge bus.</dd>
+</dl>
+ */
+impl EventBridgeSink {
+    pub async fn load_from_env() -> Result<Self, RuntimeError> {
+        let config = &load_from_env().await;
+
+        var("EVENT_BUS_NAME")
+            .map(|event_bus_name| Self::new(EventBridgeClient::new(config), event_bus_name))
+            .map_err(RuntimeError::ClientConfigLoadingError)
+    }
+
+    pub fn new(client: EventBridgeClient, event_bus_name: String) -> Self {
+        Self {
+            client: Box::new(client),
+            event_bus_name,
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for EventBridgeSink {
+    async fn publish(&self, event: &InventoryEvent) -> Result<(), RuntimeError> {
+        let output = self
+            .client
+            .put_events()
+            .entries(
+                PutEventsRequestEntry::builder()
+                    .event_bus_name(self.event_bus_name.as_str())
+                    .source(EVENT_SOURCE)
+                    .detail_type(event.event_type.name())
+                    .detail(to_string(event).map_err(RuntimeError::EventSerializationError)?)
+                    .build(),
+            )
+            .send()
+            .await?;
+
+        // single-entry request can fail only as a whole, but it is still reported as a partial failure
+        if output.failed_entry_count() > 0 {
+            return Err(RuntimeError::EventRejected(event.event_id.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+#[doc = "Publishes events to SNS topic, with event type as message attribute."]
+pub struct SnsSink {
+    client: Box<SnsClient>,
+    topic_arn: String,
+}
+
+/**
+Required environment variables:
+<dl>
+    <dt><code>EVENT_TOPIC_ARN</code></dt>
+    <dd>ARN of SNS topic.</dd>
+</dl>
+ */
+impl SnsSink {
+    pub async fn load_from_env() -> Result<Self, RuntimeError> {
+        let config = &load_from_env().await;
+
+        var("EVENT_TOPIC_ARN")
+            .map(|topic_arn| Self::new(SnsClient::new(config), topic_arn))
+            .map_err(RuntimeError::ClientConfigLoadingError)
+    }
+
+    pub fn new(client: SnsClient, topic_arn: String) -> Self {
+        Self {
+            client: Box::new(client),
+            topic_arn,
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for SnsSink {
+    async fn publish(&self, event: &InventoryEvent) -> Result<(), RuntimeError> {
+        self.client
+            .publish()
+            .topic_arn(self.topic_arn.as_str())
+            .message(to_string(event).map_err(RuntimeError::EventSerializationError)?)
+            .message_attributes(
+                "eventType",
+                MessageAttributeValue::builder()
+                    .data_type("String")
+                    .string_value(event.event_type.name())
+                    .build(),
+            )
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+#[doc = "Keeps published events in memory - meant for tests and local runs."]
+pub struct MemorySink {
+    events: Mutex<Vec<InventoryEvent>>,
+}
+
+impl MemorySink {
+    pub fn events(&self) -> Vec<InventoryEvent> {
+        self.events.lock().map(|events| events.clone()).unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl EventSink for MemorySink {
+    async fn publish(&self, event: &InventoryEvent) -> Result<(), RuntimeError> {
+        if let Ok(mut events) = self.events.lock() {
+            events.push(event.clone());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event_sink::{EventSink, MemorySink};
+    use crate::events::{InventoryEvent, InventoryEventType, EVENT_SCHEMA_VERSION};
+    use chrono::{FixedOffset, TimeZone, Utc};
+    use tokio::test as tokio_test;
+    use uuid::uuid;
+
+    #[tokio_test]
+    async fn memory_sink() {
+        let sink = MemorySink::default();
+
+        sink.publish(&InventoryEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            event_id: "abc".into(),
+            event_type: InventoryEventType::InventoryDeleted,
+            occurred_at: Utc
+                .with_ymd_and_hms(2019, 5, 12, 9, 30, 0)
+                .unwrap()
+                .with_timezone(&FixedOffset::east_opt(7200).unwrap()),
+            customer_id: uuid!("00000000-0000-0000-0000-000000000000"),
+            vessel_id: uuid!("00000000-0000-0000-0000-000000000001"),
+            inventory_type: "pc".into(),
+            inventory_id: "012".into(),
+            version: 3,
+            before: None,
+            after: None,
+        })
+        .await
+        .unwrap();
+
+        let events = sink.events();
+        assert_eq!(1, events.len());
+        assert_eq!("abc", events[0].event_id);
+        assert_eq!(InventoryEventType::InventoryDeleted, events[0].event_type);
+    }
+}
@@ -0,0 +1,337 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+use crate::inventory_dao::is_inventory_key;
+use crate::model::Inventory;
+use crate::runtime_error::RuntimeError;
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_dynamo::{from_item, Item};
+use uuid::Uuid;
+
+#[doc = "Version of published events structure, increased with every incompatible change."]
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[doc = "Kind of domain event."]
+pub enum InventoryEventType {
+    InventoryCreated,
+    InventoryUpdated,
+    InventoryDeleted,
+}
+
+impl InventoryEventType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            InventoryEventType::InventoryCreated => "InventoryCreated",
+            InventoryEventType::InventoryUpdated => "InventoryUpdated",
+            InventoryEventType::InventoryDeleted => "InventoryDeleted",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+#[doc = "Domain event published for every inventory change."]
+pub struct InventoryEvent {
+    #[doc = "Version of the event structure."]
+    pub schema_version: u32,
+    #[doc = "Unique event ID (stream record ID), allows consumers to de-duplicate re-deliveries."]
+    pub event_id: String,
+    #[doc = "Kind of the event."]
+    pub event_type: InventoryEventType,
+    #[doc = "Approximate date of the change."]
+    pub occurred_at: DateTime<FixedOffset>,
+    #[doc = "Owner ID."]
+    pub customer_id: Uuid,
+    #[doc = "Vessel ID."]
+    pub vessel_id: Uuid,
+    #[doc = "Inventory type."]
+    pub inventory_type: String,
+    #[doc = "Inventory ID (within given type)."]
+    pub inventory_id: String,
+    #[doc = "Record version produced by the change."]
+    pub version: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[doc = "State before the change."]
+    pub before: Option<Inventory>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[doc = "State after the change."]
+    pub after: Option<Inventory>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+#[doc = "Kind of DynamoDB stream record."]
+pub enum StreamEventName {
+    Insert,
+    Modify,
+    Remove,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+#[doc = "Data modification captured by DynamoDB stream."]
+pub struct StreamChange {
+    #[doc = "Primary key of modified item."]
+    pub keys: Item,
+    #[doc = "Item state after the modification."]
+    pub new_image: Option<Item>,
+    #[doc = "Item state before the modification."]
+    pub old_image: Option<Item>,
+    #[doc = "Position of the record within shard."]
+    pub sequence_number: String,
+    #[doc = "Approximate date of the modification (Unix timestamp)."]
+    pub approximate_creation_date_time: f64,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+#[doc = "Single DynamoDB stream record."]
+pub struct StreamRecord {
+    #[serde(rename = "eventID")]
+    #[doc = "Unique record ID."]
+    pub event_id: String,
+    #[doc = "Kind of the modification."]
+    pub event_name: StreamEventName,
+    #[doc = "Modification details."]
+    pub dynamodb: StreamChange,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordKey {
+    customer_and_vessel_id: String,
+}
+
+fn image_of(image: &Option<Item>) -> Result<Option<Inventory>, RuntimeError> {
+    image
+        .clone()
+        .map(from_item::<_, Inventory>)
+        .transpose()
+        .map_err(RuntimeError::from)
+}
+
+impl InventoryEvent {
+    #[doc = "Classifies stream record - `None` for auxiliary records and changes that are not visible to clients."]
+    pub fn from_stream_record(record: &StreamRecord) -> Result<Option<Self>, RuntimeError> {
+        let key: RecordKey = from_item(record.dynamodb.keys.clone())?;
+        if !is_inventory_key(&key.customer_and_vessel_id) {
+            return Ok(None);
+        }
+
+        let before = image_of(&record.dynamodb.old_image)?;
+        let after = image_of(&record.dynamodb.new_image)?;
+        let was_visible = before.as_ref().is_some_and(|inventory| inventory.deleted_at.is_none());
+        let is_visible = after.as_ref().is_some_and(|inventory| inventory.deleted_at.is_none());
+
+        // soft deletion and restoration are modifications, while retention purge removes already deleted record
+        let event_type = match (record.event_name, was_visible, is_visible) {
+            (StreamEventName::Insert, _, _) | (StreamEventName::Modify, false, true) => {
+                InventoryEventType::InventoryCreated
+            }
+            (StreamEventName::Modify, true, true) => InventoryEventType::InventoryUpdated,
+            (StreamEventName::Modify, true, false) | (StreamEventName::Remove, true, _) => {
+                InventoryEventType::InventoryDeleted
+            }
+            _ => return Ok(None),
+        };
+
+        let Some(current) = after.as_ref().or(before.as_ref()) else {
+            return Ok(None);
+        };
+
+        let seconds = record.dynamodb.approximate_creation_date_time;
+        let occurred_at = Utc
+            .timestamp_opt(seconds.trunc() as i64, (seconds.fract() * 1e9) as u32)
+            .single()
+            .unwrap_or_default()
+            .fixed_offset();
+
+        Ok(Some(Self {
+            schema_version: EVENT_SCHEMA_VERSION,
+            event_id: record.event_id.clone(),
+            event_type,
+            occurred_at,
+            customer_id: current.customer_id,
+            vessel_id: current.vessel_id,
+            inventory_type: current.inventory_type.clone(),
+            inventory_id: current.inventory_id.clone(),
+            version: current.version,
+            before,
+            after,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::events::{InventoryEvent, InventoryEventType, StreamEventName, StreamRecord, EVENT_SCHEMA_VERSION};
+    use serde_json::{from_str, to_string};
+
+    static INVENTORY: &str = r#"{
+        "customerAndVesselId": {"S": "00000000-0000-0000-0000-000000000000:00000000-0000-0000-0000-000000000001"},
+        "inventoryKey": {"S": "pc:012"},
+        "customerId": {"S": "00000000-0000-0000-0000-000000000000"},
+        "vesselId": {"S": "00000000-0000-0000-0000-000000000001"},
+        "inventoryType": {"S": "pc"},
+        "inventoryId": {"S": "012"},
+        "createdAt": {"S": "2011-01-30T14:58:00+01:00"},
+        "version": {"N": "2"}
+    }"#;
+
+    static DELETED_INVENTORY: &str = r#"{
+        "customerAndVesselId": {"S": "00000000-0000-0000-0000-000000000000:00000000-0000-0000-0000-000000000001"},
+        "inventoryKey": {"S": "pc:012"},
+        "customerId": {"S": "00000000-0000-0000-0000-000000000000"},
+        "vesselId": {"S": "00000000-0000-0000-0000-000000000001"},
+        "inventoryType": {"S": "pc"},
+        "inventoryId": {"S": "012"},
+        "createdAt": {"S": "2011-01-30T14:58:00+01:00"},
+        "deletedAt": {"S": "2011-02-01T10:00:00+01:00"},
+        "version": {"N": "3"}
+    }"#;
+
+    static INVENTORY_KEYS: &str = r#"{
+        "customerAndVesselId": {"S": "00000000-0000-0000-0000-000000000000:00000000-0000-0000-0000-000000000001"},
+        "inventoryKey": {"S": "pc:012"}
+    }"#;
+
+    fn record(event_name: &str, keys: &str, old_image: Option<&str>, new_image: Option<&str>) -> StreamRecord {
+        let mut images = String::new();
+        if let Some(image) = old_image {
+            images.push_str(&format!(",\"OldImage\":{image}"));
+        }
+        if let Some(image) = new_image {
+            images.push_str(&format!(",\"NewImage\":{image}"));
+        }
+
+        from_str(&format!(
+            "{{\"eventID\":\"abc\",\"eventName\":\"{event_name}\",\"eventSource\":\"aws:dynamodb\",\"dynamodb\":{{\"ApproximateCreationDateTime\":1.6e9,\"Keys\":{keys},\"SequenceNumber\":\"111\"{images}}}}}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn deserialize_stream_record() {
+        let record = record("MODIFY", INVENTORY_KEYS, Some(INVENTORY), Some(DELETED_INVENTORY));
+
+        assert_eq!("abc", record.event_id);
+        assert_eq!(StreamEventName::Modify, record.event_name);
+        assert_eq!("111", record.dynamodb.sequence_number);
+        assert!(record.dynamodb.old_image.is_some());
+        assert!(record.dynamodb.new_image.is_some());
+    }
+
+    #[test]
+    fn classify_insert() {
+        let event = InventoryEvent::from_stream_record(&record("INSERT", INVENTORY_KEYS, None, Some(INVENTORY)))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(EVENT_SCHEMA_VERSION, event.schema_version);
+        assert_eq!("abc", event.event_id);
+        assert_eq!(InventoryEventType::InventoryCreated, event.event_type);
+        assert_eq!("pc", event.inventory_type);
+        assert_eq!("012", event.inventory_id);
+        assert_eq!(2, event.version);
+        assert_eq!(1_600_000_000, event.occurred_at.timestamp());
+        assert!(event.before.is_none());
+        assert!(event.after.is_some());
+    }
+
+    #[test]
+    fn classify_modify() {
+        let event =
+            InventoryEvent::from_stream_record(&record("MODIFY", INVENTORY_KEYS, Some(INVENTORY), Some(INVENTORY)))
+                .unwrap()
+                .unwrap();
+
+        assert_eq!(InventoryEventType::InventoryUpdated, event.event_type);
+        assert!(event.before.is_some());
+        assert!(event.after.is_some());
+    }
+
+    #[test]
+    fn classify_soft_delete() {
+        let event = InventoryEvent::from_stream_record(&record(
+            "MODIFY",
+            INVENTORY_KEYS,
+            Some(INVENTORY),
+            Some(DELETED_INVENTORY),
+        ))
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(InventoryEventType::InventoryDeleted, event.event_type);
+        assert_eq!(3, event.version);
+    }
+
+    #[test]
+    fn classify_restore() {
+        let event = InventoryEvent::from_stream_record(&record(
+            "MODIFY",
+            INVENTORY_KEYS,
+            Some(DELETED_INVENTORY),
+            Some(INVENTORY),
+        ))
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(InventoryEventType::InventoryCreated, event.event_type);
+    }
+
+    #[test]
+    fn classify_remove() {
+        let event = InventoryEvent::from_stream_record(&record("REMOVE", INVENTORY_KEYS, Some(INVENTORY), None))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(InventoryEventType::InventoryDeleted, event.event_type);
+        assert!(event.before.is_some());
+        assert!(event.after.is_none());
+    }
+
+    #[test]
+    fn classify_purge() {
+        let event =
+            InventoryEvent::from_stream_record(&record("REMOVE", INVENTORY_KEYS, Some(DELETED_INVENTORY), None))
+                .unwrap();
+
+        assert!(event.is_none());
+    }
+
+    #[test]
+    fn classify_auxiliary() {
+        let marker = r#"{"customerAndVesselId": {"S": "awsInstanceId:i-123"}, "inventoryKey": {"S": "awsInstanceId"}}"#;
+        let history = r#"{"customerAndVesselId": {"S": "history:a:b:pc:012"}, "inventoryKey": {"S": "2011#1"}}"#;
+
+        assert!(
+            InventoryEvent::from_stream_record(&record("INSERT", marker, None, Some(marker)))
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            InventoryEvent::from_stream_record(&record("INSERT", history, None, Some(history)))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn serialize_event() {
+        let event = InventoryEvent::from_stream_record(&record("INSERT", INVENTORY_KEYS, None, Some(INVENTORY)))
+            .unwrap()
+            .unwrap();
+        let output = to_string(&event).unwrap();
+
+        assert!(output.contains(&format!("\"schemaVersion\":{EVENT_SCHEMA_VERSION}")));
+        assert!(output.contains("\"eventType\":\"InventoryCreated\""));
+        assert!(output.contains("\"inventoryId\":\"012\""));
+        assert!(!output.contains("\"before\""));
+        assert!(output.contains("\"after\":{"));
+    }
+}
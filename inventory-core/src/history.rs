@@ -0,0 +1,90 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+use crate::model::Inventory;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[doc = "Kind of inventory modification."]
+pub enum ChangeType {
+    Created,
+    Updated,
+    StatusChanged,
+    Deleted,
+    Restored,
+    Transferred,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+#[doc = "Single entry of inventory audit trail."]
+pub struct HistoryEntry {
+    #[doc = "Date of the change."]
+    pub changed_at: DateTime<FixedOffset>,
+    #[doc = "Kind of the change."]
+    pub change_type: ChangeType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[doc = "Identity of the caller who made the change."]
+    pub actor: Option<String>,
+    #[doc = "Record version produced by the change."]
+    pub version: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[doc = "State before the change (none for newly created inventory)."]
+    pub before: Option<Inventory>,
+    #[doc = "State after the change."]
+    pub after: Inventory,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::{ChangeType, HistoryEntry};
+    use crate::{Inventory, InventoryStatus};
+    use chrono::{FixedOffset, TimeZone, Utc};
+    use serde_json::to_string;
+    use std::collections::HashMap;
+    use uuid::uuid;
+
+    #[test]
+    fn serialize_entry() {
+        let changed_at = Utc
+            .with_ymd_and_hms(2019, 5, 12, 9, 30, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+
+        let output = to_string(&HistoryEntry {
+            changed_at,
+            change_type: ChangeType::StatusChanged,
+            actor: None,
+            version: 1,
+            before: None,
+            after: Inventory {
+                customer_id: uuid!("00000000-0000-0000-0000-000000000000"),
+                vessel_id: uuid!("00000000-0000-0000-0000-000000000001"),
+                inventory_type: "pc".into(),
+                inventory_id: "012".into(),
+                serial_number: None,
+                aws_instance_id: None,
+                attributes: HashMap::new(),
+                status: InventoryStatus::Installed,
+                status_change: None,
+                created_at: changed_at,
+                deleted_at: None,
+                deleted_by: None,
+                transferred_at: None,
+                version: 1,
+            },
+        })
+        .unwrap();
+
+        assert!(output.contains("\"changeType\":\"statusChanged\""));
+        assert!(output.contains("\"changedAt\":\"2019-05-12T11:30:00+02:00\""));
+        assert!(!output.contains("\"actor\""));
+        assert!(!output.contains("\"before\""));
+        assert!(output.contains("\"after\":{"));
+    }
+}
@@ -4,49 +4,354 @@
  * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
  */
 
-use crate::model::{DynamoResultsPage, Inventory};
-use crate::runtime_error::RuntimeError;
-use std::collections::HashMap;
+use crate::api_error::ApiError;
+use crate::history::{ChangeType, HistoryEntry};
+use crate::lambda::{current_actor, remaining_time};
+use crate::lifecycle::{InventoryStatus, StatusChange};
+use crate::model::{Deletion, DynamoResultsPage, Inventory, InventoryChanges, InventoryFilter, SortOrder};
+use crate::page_token::PageToken;
+use crate::runtime_error::{ErrorKind, RuntimeError};
+use std::collections::{HashMap, HashSet};
 
 use aws_config::load_from_env;
-use aws_sdk_dynamodb::types::AttributeValue::S;
+use aws_sdk_dynamodb::config::retry::RetryConfig;
+use aws_sdk_dynamodb::config::timeout::TimeoutConfig;
+use aws_sdk_dynamodb::config::Builder;
+use aws_sdk_dynamodb::operation::query::builders::QueryFluentBuilder;
+use aws_sdk_dynamodb::operation::transact_write_items::TransactWriteItemsError;
+use aws_sdk_dynamodb::types::AttributeValue::{N, S};
+use aws_sdk_dynamodb::types::{AttributeValue, Delete, KeysAndAttributes, Put, TransactWriteItem, Update};
 use aws_sdk_dynamodb::Client;
-use serde_dynamo::{from_item, from_items, to_item};
+use aws_smithy_http::result::SdkError;
+use chrono::{DateTime, Duration, FixedOffset, SecondsFormat, Utc};
+use serde_dynamo::{from_item, from_items, to_attribute_value, to_item};
 use std::env::var;
+use std::future::Future;
+use std::mem::take;
+use std::num::ParseIntError;
+use std::str::FromStr;
+use std::time::Duration as StdDuration;
+use tokio::time::{sleep, timeout};
 use tracing::{Instrument, Span};
 use uuid::Uuid;
 use xray::aws_metadata;
 
+static SERIAL_NUMBER_INDEX: &str = "SerialNumberIndex";
+static AWS_INSTANCE_ID_INDEX: &str = "AwsInstanceIdIndex";
+static CUSTOMER_INDEX: &str = "CustomerIndex";
+static AWS_INSTANCE_MARKER: &str = "awsInstanceId";
+
+// upper bound of listing page size, also used when client does not specify any
+static MAX_PAGE_SIZE: u32 = 100;
+
+// DynamoDB limits of single transaction and batch request
+static TRANSACTION_SIZE: usize = 25;
+static BATCH_GET_SIZE: usize = 100;
+
+// unprocessed batch items are retried with exponential backoff
+static BATCH_MAX_ATTEMPTS: u32 = 5;
+static BATCH_BACKOFF_BASE_MS: u64 = 50;
+
+// how long soft-deleted records are kept before they are purged, unless configured otherwise
+static DEFAULT_RETENTION_DAYS: i64 = 30;
+
+// time left before invocation deadline to still report the failure, unless configured otherwise
+static DEFAULT_DEADLINE_MARGIN_MS: u64 = 500;
+
+// attributes skipped from serialization when empty, that need to be removed explicitly on overwrite
+static OPTIONAL_ATTRIBUTES: [&str; 6] = [
+    "serialNumber",
+    "awsInstanceId",
+    "attributes",
+    "deletedAt",
+    "deletedBy",
+    "transferredAt",
+];
+
 pub struct InventoryDao {
     client: Box<Client>,
     table_name: String,
+    retention: Duration,
+    deadline_margin: StdDuration,
 }
 
 #[inline(always)]
-fn hash_key_of(customer_id: &Uuid, vessel_id: &Uuid) -> String {
+pub(crate) fn hash_key_of(customer_id: &Uuid, vessel_id: &Uuid) -> String {
     format!("{customer_id}:{vessel_id}")
 }
 
 #[inline(always)]
-fn sort_key_of(inventory_type: &String, inventory_id: &String) -> String {
+pub(crate) fn sort_key_of(inventory_type: &String, inventory_id: &String) -> String {
     format!("{inventory_type}:{inventory_id}")
 }
 
+#[inline(always)]
+pub(crate) fn customer_sort_key_of(vessel_id: &Uuid, inventory_type: &String, inventory_id: &String) -> String {
+    format!("{vessel_id}#{}", sort_key_of(inventory_type, inventory_id))
+}
+
+#[inline(always)]
+fn aws_instance_key_of(aws_instance_id: &String) -> String {
+    format!("awsInstanceId:{aws_instance_id}")
+}
+
+#[inline(always)]
+pub(crate) fn history_key_of(
+    customer_id: &Uuid,
+    vessel_id: &Uuid,
+    inventory_type: &String,
+    inventory_id: &String,
+) -> String {
+    format!(
+        "history:{}:{}",
+        hash_key_of(customer_id, vessel_id),
+        sort_key_of(inventory_type, inventory_id)
+    )
+}
+
+// chronological order, version distinguishes changes made within the same instant
+#[inline(always)]
+pub(crate) fn history_entry_key_of(changed_at: &DateTime<FixedOffset>, version: u64) -> String {
+    format!(
+        "{}#{version:020}",
+        changed_at
+            .with_timezone(&Utc)
+            .to_rfc3339_opts(SecondsFormat::Micros, true)
+    )
+}
+
+#[inline(always)]
+fn key_of(hash_key: String, sort_key: String) -> HashMap<String, AttributeValue> {
+    HashMap::from([
+        ("customerAndVesselId".into(), S(hash_key)),
+        ("inventoryKey".into(), S(sort_key)),
+    ])
+}
+
+fn setting_from_env<T: FromStr<Err = ParseIntError>>(name: &str) -> Result<Option<T>, RuntimeError> {
+    var(name)
+        .ok()
+        // deployment templates pass empty value for settings left unset
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|error| RuntimeError::ConfigurationParseError(name.into(), error))
+        })
+        .transpose()
+}
+
+#[inline(always)]
+fn backoff(attempt: u32) -> StdDuration {
+    StdDuration::from_millis(BATCH_BACKOFF_BASE_MS << attempt)
+}
+
+// AWS instance ID markers and history entries share the table with inventory records
+pub(crate) fn is_inventory_key(hash_key: &str) -> bool {
+    !hash_key.starts_with("awsInstanceId:") && !hash_key.starts_with("history:")
+}
+
+#[inline(always)]
+pub(crate) fn page_size(limit: Option<u32>) -> i32 {
+    limit.unwrap_or(MAX_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE) as i32
+}
+
+#[inline(always)]
+fn version_condition(expected_version: u64) -> &'static str {
+    // records created before versioning was introduced have no version attribute at all
+    if expected_version == 0 {
+        "(attribute_not_exists(#version) OR #version = :expectedVersion)"
+    } else {
+        "#version = :expectedVersion"
+    }
+}
+
+#[inline(always)]
+fn status_condition(status: InventoryStatus) -> &'static str {
+    // records created before statuses were introduced have no status attribute at all
+    if status == InventoryStatus::default() {
+        "(attribute_not_exists(#status) OR #status = :status)"
+    } else {
+        "#status = :status"
+    }
+}
+
+// conditions are evaluated after reading the page - limit applies before filtering, so pages may be shorter
+fn apply_filter(
+    mut request: QueryFluentBuilder,
+    filter: &InventoryFilter,
+    mut conditions: Vec<&'static str>,
+) -> QueryFluentBuilder {
+    if let Some(status) = filter.status {
+        conditions.push(status_condition(status));
+        request = request
+            .expression_attribute_names("#status", "status")
+            .expression_attribute_values(":status", S(status.name().into()));
+    }
+
+    if !filter.include_deleted {
+        conditions.push("attribute_not_exists(deletedAt)");
+    }
+
+    if conditions.is_empty() {
+        request
+    } else {
+        request.filter_expression(conditions.join(" AND "))
+    }
+}
+
+// checks whether given item of cancelled transaction was rejected by its condition
+#[inline(always)]
+fn failed_condition(error: &SdkError<TransactWriteItemsError>, index: usize) -> bool {
+    match error {
+        SdkError::ServiceError(error) => match error.err() {
+            TransactWriteItemsError::TransactionCanceledException(exception) => exception
+                .cancellation_reasons()
+                .and_then(|reasons| reasons.get(index))
+                .and_then(|reason| reason.code())
+                .is_some_and(|code| code == "ConditionalCheckFailed"),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn inventory_item(inventory: &Inventory) -> Result<HashMap<String, AttributeValue>, RuntimeError> {
+    let mut item: HashMap<String, AttributeValue> = to_item(inventory)?;
+    item.extend(key_of(
+        hash_key_of(&inventory.customer_id, &inventory.vessel_id),
+        sort_key_of(&inventory.inventory_type, &inventory.inventory_id),
+    ));
+    item.insert(
+        "vesselAndInventoryKey".into(),
+        S(customer_sort_key_of(
+            &inventory.vessel_id,
+            &inventory.inventory_type,
+            &inventory.inventory_id,
+        )),
+    );
+    Ok(item)
+}
+
+#[derive(Default)]
+struct UpdateExpression {
+    set: Vec<String>,
+    remove: Vec<String>,
+    names: HashMap<String, String>,
+    values: HashMap<String, AttributeValue>,
+}
+
+impl UpdateExpression {
+    fn assign(&mut self, attribute: &str, change: Option<Option<AttributeValue>>) {
+        match change {
+            Some(Some(value)) => self.set(attribute, value),
+            Some(None) => self.remove(attribute),
+            None => {}
+        }
+    }
+
+    fn set(&mut self, attribute: &str, value: AttributeValue) {
+        self.names.insert(format!("#{attribute}"), attribute.into());
+        self.values.insert(format!(":{attribute}"), value);
+        self.set.push(format!("#{attribute} = :{attribute}"));
+    }
+
+    fn increment(&mut self, attribute: &str) {
+        self.names.insert(format!("#{attribute}"), attribute.into());
+        self.values.insert(":zero".into(), N("0".into()));
+        self.values.insert(":one".into(), N("1".into()));
+        self.set
+            .push(format!("#{attribute} = if_not_exists(#{attribute}, :zero) + :one"));
+    }
+
+    fn remove(&mut self, attribute: &str) {
+        self.names.insert(format!("#{attribute}"), attribute.into());
+        self.remove.push(format!("#{attribute}"));
+    }
+
+    // records written before CustomerIndex was introduced get its keys with their first change
+    fn customer_index_keys(&mut self, inventory: &Inventory) {
+        self.set("customerId", S(inventory.customer_id.to_string()));
+        self.set(
+            "vesselAndInventoryKey",
+            S(customer_sort_key_of(
+                &inventory.vessel_id,
+                &inventory.inventory_type,
+                &inventory.inventory_id,
+            )),
+        );
+    }
+
+    fn expression(&self) -> String {
+        let mut clauses = vec![];
+        if !self.set.is_empty() {
+            clauses.push(format!("SET {}", self.set.join(", ")));
+        }
+        if !self.remove.is_empty() {
+            clauses.push(format!("REMOVE {}", self.remove.join(", ")));
+        }
+        clauses.join(" ")
+    }
+}
+
 /**
 Required environment variables:
 <dl>
     <dt><code>INVENTORY_TABLE</code></dt>
     <dd>Name of DynamoDB licenses table.</dd>
 </dl>
+
+Optional environment variables:
+<dl>
+    <dt><code>INVENTORY_RETENTION_DAYS</code></dt>
+    <dd>Number of days after which soft-deleted inventory is purged, 30 if not set or empty.</dd>
+    <dt><code>INVENTORY_DAO_MAX_ATTEMPTS</code></dt>
+    <dd>Maximum number of attempts of single DynamoDB request (including the initial one), SDK default if not set or empty.</dd>
+    <dt><code>INVENTORY_DAO_ATTEMPT_TIMEOUT_MS</code></dt>
+    <dd>Time limit of single attempt of DynamoDB request, in milliseconds, unlimited if not set or empty.</dd>
+    <dt><code>INVENTORY_DAO_OPERATION_TIMEOUT_MS</code></dt>
+    <dd>Time limit of DynamoDB request including all retries, in milliseconds, unlimited if not set or empty.</dd>
+    <dt><code>INVENTORY_DAO_DEADLINE_MARGIN_MS</code></dt>
+    <dd>Time reserved before Lambda invocation deadline for reporting failure, in milliseconds, 500 if not set or empty.</dd>
+</dl>
  */
 impl InventoryDao {
     pub async fn load_from_env() -> Result<Self, RuntimeError> {
         let config = &load_from_env().await;
 
+        let retention_days = setting_from_env("INVENTORY_RETENTION_DAYS")?.unwrap_or(DEFAULT_RETENTION_DAYS);
+        let max_attempts: Option<u32> = setting_from_env("INVENTORY_DAO_MAX_ATTEMPTS")?;
+        let attempt_timeout: Option<u64> = setting_from_env("INVENTORY_DAO_ATTEMPT_TIMEOUT_MS")?;
+        let operation_timeout: Option<u64> = setting_from_env("INVENTORY_DAO_OPERATION_TIMEOUT_MS")?;
+        let deadline_margin =
+            setting_from_env("INVENTORY_DAO_DEADLINE_MARGIN_MS")?.unwrap_or(DEFAULT_DEADLINE_MARGIN_MS);
+
+        let mut builder = Builder::from(config);
+        if let Some(max_attempts) = max_attempts {
+            builder = builder.retry_config(RetryConfig::standard().with_max_attempts(max_attempts));
+        }
+
+        // only limits that are configured explicitly are overridden
+        let mut timeouts = config
+            .timeout_config()
+            .map(TimeoutConfig::to_builder)
+            .unwrap_or_default();
+        if let Some(attempt_timeout) = attempt_timeout {
+            timeouts = timeouts.operation_attempt_timeout(StdDuration::from_millis(attempt_timeout));
+        }
+        if let Some(operation_timeout) = operation_timeout {
+            timeouts = timeouts.operation_timeout(StdDuration::from_millis(operation_timeout));
+        }
+        builder = builder.timeout_config(timeouts.build());
+
         var("INVENTORY_TABLE")
             .map(|table_name| {
-                let client = Client::new(config);
-                Self::new(client, table_name)
+                let client = Client::from_conf(builder.build());
+                Self {
+                    retention: Duration::days(retention_days),
+                    deadline_margin: StdDuration::from_millis(deadline_margin),
+                    ..Self::new(client, table_name)
+                }
             })
             .map_err(RuntimeError::ClientConfigLoadingError)
     }
@@ -55,62 +360,444 @@ impl InventoryDao {
         Self {
             client: Box::new(client),
             table_name,
+            retention: Duration::days(DEFAULT_RETENTION_DAYS),
+            deadline_margin: StdDuration::from_millis(DEFAULT_DEADLINE_MARGIN_MS),
+        }
+    }
+
+    pub async fn create_inventory(&self, inventory: Inventory) -> Result<(), ApiError> {
+        let inventory_type = inventory.inventory_type.clone();
+        let inventory_id = inventory.inventory_id.clone();
+        let aws_instance_id = inventory.aws_instance_id.clone();
+        let inventory = Inventory {
+            version: 1,
+            ..inventory
+        };
+
+        let mut items = vec![self.creation_put(&inventory)?];
+        items.extend(self.aws_instance_changes(None, aws_instance_id.as_ref()));
+        let last = items.len() - 1;
+        items.push(self.history_record(ChangeType::Created, None, inventory)?);
+
+        match self.transact(items).await {
+            Ok(_) => Ok(()),
+            Err(error) if failed_condition(&error, 0) => {
+                Err(ApiError::InventoryAlreadyExists(inventory_type, inventory_id))
+            }
+            Err(error) if failed_condition(&error, last) => Err(ApiError::AwsInstanceIdAlreadyAssigned(
+                aws_instance_id.unwrap_or_default(),
+            )),
+            Err(error) => Err(RuntimeError::from(error).into()),
+        }
+    }
+
+    // existing inventory is never overwritten, also when it's created concurrently - conditional transactions instead
+    // of BatchWriteItem, as plain batch puts would silently overwrite records created in the meantime and could not
+    // reserve AWS instance IDs; each chunk writes records, history entries and AWS instance ID markers all-or-nothing
+    pub async fn batch_create(&self, inventories: Vec<Inventory>) -> Result<Vec<Result<(), ApiError>>, ApiError> {
+        let mut results: Vec<Result<(), ApiError>> = inventories.iter().map(|_| Ok(())).collect();
+        let mut seen = HashSet::new();
+        let mut reserved = HashSet::new();
+        let mut chunks = vec![];
+        let mut chunk = vec![];
+        let mut chunk_size = 0;
+
+        for (index, inventory) in inventories.into_iter().enumerate() {
+            if !seen.insert((
+                hash_key_of(&inventory.customer_id, &inventory.vessel_id),
+                sort_key_of(&inventory.inventory_type, &inventory.inventory_id),
+            )) {
+                results[index] = Err(ApiError::InventoryAlreadyExists(
+                    inventory.inventory_type,
+                    inventory.inventory_id,
+                ));
+                continue;
+            }
+            if let Some(aws_instance_id) = &inventory.aws_instance_id {
+                if !reserved.insert(aws_instance_id.clone()) {
+                    results[index] = Err(ApiError::AwsInstanceIdAlreadyAssigned(aws_instance_id.clone()));
+                    continue;
+                }
+            }
+
+            // every inventory takes its record, history entry and optional AWS instance ID marker
+            let size = if inventory.aws_instance_id.is_some() { 3 } else { 2 };
+            if chunk_size + size > TRANSACTION_SIZE {
+                chunks.push(take(&mut chunk));
+                chunk_size = 0;
+            }
+            chunk_size += size;
+            chunk.push((
+                index,
+                Inventory {
+                    version: 1,
+                    ..inventory
+                },
+            ));
         }
+        chunks.push(chunk);
+
+        for mut chunk in chunks {
+            let mut attempt = 0;
+
+            while !chunk.is_empty() {
+                let mut items = vec![];
+                let mut positions = vec![];
+                for (_, inventory) in &chunk {
+                    let record = items.len();
+                    items.push(self.creation_put(inventory)?);
+                    items.extend(self.aws_instance_changes(None, inventory.aws_instance_id.as_ref()));
+                    positions.push((record, inventory.aws_instance_id.as_ref().map(|_| record + 1)));
+                    items.push(self.history_record(ChangeType::Created, None, inventory.clone())?);
+                }
+
+                let error = match self.transact(items).await {
+                    Ok(_) => break,
+                    Err(error) => error,
+                };
+
+                // entries rejected by their conditions are reported and the transaction is repeated for the rest
+                let size = chunk.len();
+                let mut rest = vec![];
+                for ((index, inventory), (record, marker)) in chunk.into_iter().zip(positions) {
+                    if failed_condition(&error, record) {
+                        results[index] = Err(ApiError::InventoryAlreadyExists(
+                            inventory.inventory_type,
+                            inventory.inventory_id,
+                        ));
+                    } else if marker.is_some_and(|marker| failed_condition(&error, marker)) {
+                        results[index] = Err(ApiError::AwsInstanceIdAlreadyAssigned(
+                            inventory.aws_instance_id.unwrap_or_default(),
+                        ));
+                    } else {
+                        rest.push((index, inventory));
+                    }
+                }
+                let rejected = rest.len() < size;
+                chunk = rest;
+                if rejected {
+                    continue;
+                }
+
+                // failure not caused by any particular entry - only throttling and transient errors are worth repeating
+                let error = RuntimeError::from(error);
+                if !error.kind().is_retryable() {
+                    return Err(error.into());
+                }
+
+                attempt += 1;
+                let exhausted = attempt >= BATCH_MAX_ATTEMPTS;
+                if exhausted || self.backoff_within_deadline(attempt).await.is_err() {
+                    // caller can repeat just the entries that were not created
+                    for (index, inventory) in chunk {
+                        results[index] = Err(if exhausted {
+                            RuntimeError::UnprocessedItem(sort_key_of(
+                                &inventory.inventory_type,
+                                &inventory.inventory_id,
+                            ))
+                        } else {
+                            RuntimeError::DeadlineExceeded
+                        }
+                        .into());
+                    }
+                    break;
+                }
+            }
+        }
+
+        Ok(results)
     }
 
-    pub async fn create_inventory(&self, inventory: Inventory) -> Result<(), RuntimeError> {
+    pub async fn upsert_inventory(&self, inventory: Inventory) -> Result<(), ApiError> {
         let hash_key = hash_key_of(&inventory.customer_id, &inventory.vessel_id);
         let sort_key = sort_key_of(&inventory.inventory_type, &inventory.inventory_id);
+        let customer_sort_key =
+            customer_sort_key_of(&inventory.vessel_id, &inventory.inventory_type, &inventory.inventory_id);
+        let customer_id = inventory.customer_id;
+        let vessel_id = inventory.vessel_id;
+        let inventory_type = inventory.inventory_type.clone();
+        let inventory_id = inventory.inventory_id.clone();
+        let aws_instance_id = inventory.aws_instance_id.clone();
 
-        self.client
-            .put_item()
-            .table_name(self.table_name.as_str())
-            .set_item(Some(to_item(inventory)?))
-            .item("customerAndVesselId", S(hash_key))
-            .item("inventoryKey", S(sort_key))
-            .send()
-            .instrument(self.instrumentation())
+        let current = self
+            .get_inventory(customer_id, vessel_id, inventory_type.clone(), inventory_id.clone())
             .await?;
-        Ok(())
+
+        // overwrites all of the attributes, but version needs to keep growing and status can only be changed by transition
+        let preserved: &[&str] = if current.is_some() {
+            &["version", "status", "statusChange"]
+        } else {
+            &["version"]
+        };
+        let item: HashMap<String, AttributeValue> = to_item(&inventory).map_err(RuntimeError::from)?;
+        let mut update = UpdateExpression::default();
+        OPTIONAL_ATTRIBUTES
+            .iter()
+            .filter(|attribute| !item.contains_key(**attribute))
+            .for_each(|attribute| update.remove(attribute));
+        // overwriting soft-deleted record brings it back
+        update.remove("expiresAt");
+        item.into_iter()
+            .filter(|(attribute, _)| !preserved.contains(&attribute.as_str()))
+            .for_each(|(attribute, value)| update.set(&attribute, value));
+        update.set("vesselAndInventoryKey", S(customer_sort_key));
+        update.increment("version");
+
+        // makes sure nothing changed since current state was read
+        let condition = match &current {
+            Some(current) => {
+                update
+                    .values
+                    .insert(":expectedVersion".into(), N(current.version.to_string()));
+                version_condition(current.version)
+            }
+            None => "attribute_not_exists(customerAndVesselId)",
+        };
+
+        let mut items = vec![TransactWriteItem::builder()
+            .update(
+                Update::builder()
+                    .table_name(self.table_name.as_str())
+                    .key("customerAndVesselId", S(hash_key))
+                    .key("inventoryKey", S(sort_key))
+                    .update_expression(update.expression())
+                    .condition_expression(condition)
+                    .set_expression_attribute_names(Some(update.names))
+                    .set_expression_attribute_values(Some(update.values))
+                    .build(),
+            )
+            .build()];
+        // soft-deleted record does not hold its AWS instance ID reservation anymore
+        items.extend(
+            self.aws_instance_changes(
+                current
+                    .as_ref()
+                    .filter(|current| current.deleted_at.is_none())
+                    .and_then(|current| current.aws_instance_id.as_ref()),
+                aws_instance_id.as_ref(),
+            ),
+        );
+        let last = items.len() - 1;
+        let after = match &current {
+            Some(current) => Inventory {
+                status: current.status,
+                status_change: current.status_change.clone(),
+                version: current.version + 1,
+                ..inventory
+            },
+            None => Inventory {
+                version: 1,
+                ..inventory
+            },
+        };
+        let change_type = if current.is_some() {
+            ChangeType::Updated
+        } else {
+            ChangeType::Created
+        };
+        items.push(self.history_record(change_type, current, after)?);
+
+        match self.transact(items).await {
+            Ok(_) => Ok(()),
+            Err(error) if failed_condition(&error, 0) => Err(self
+                .resolve_conflict(customer_id, vessel_id, inventory_type, inventory_id)
+                .await),
+            Err(error) if failed_condition(&error, last) => Err(ApiError::AwsInstanceIdAlreadyAssigned(
+                aws_instance_id.unwrap_or_default(),
+            )),
+            Err(error) => Err(RuntimeError::from(error).into()),
+        }
     }
 
     pub async fn list_inventory(
         &self,
         customer_id: Uuid,
         vessel_id: Uuid,
-        page_token: Option<String>,
-    ) -> Result<DynamoResultsPage<Inventory, String>, RuntimeError> {
+        filter: InventoryFilter,
+        page_token: Option<PageToken>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> Result<DynamoResultsPage<Inventory, PageToken>, ApiError> {
         let hash_key = hash_key_of(&customer_id, &vessel_id);
+        let exclusive_start_key = page_token
+            .map(|page_token| {
+                // token issued for another listing can not be used as a starting point
+                if page_token.partition == hash_key {
+                    Ok(HashMap::from([
+                        ("customerAndVesselId".into(), S(hash_key.clone())),
+                        ("inventoryKey".into(), S(page_token.key)),
+                    ]))
+                } else {
+                    Err(ApiError::InvalidPageToken)
+                }
+            })
+            .transpose()?;
 
-        let results = self
+        let mut request = self
             .client
             .query()
             .table_name(self.table_name.as_str())
             .key_condition_expression("customerAndVesselId = :customerAndVesselId")
             .expression_attribute_values(":customerAndVesselId", S(hash_key.clone()))
-            .set_exclusive_start_key(page_token.map(|inventory_key| {
-                HashMap::from([
-                    ("customerAndVesselId".into(), S(hash_key)),
-                    ("inventoryKey".into(), S(inventory_key)),
-                ])
-            }))
-            .send()
+            .limit(page_size(limit))
+            .scan_index_forward(order == SortOrder::Asc);
+
+        // sort key starts with the type, so type filtering is just a key prefix condition
+        if let Some(inventory_type) = &filter.inventory_type {
+            request = request
+                .key_condition_expression(
+                    "customerAndVesselId = :customerAndVesselId AND begins_with(inventoryKey, :inventoryType)",
+                )
+                .expression_attribute_values(":inventoryType", S(format!("{inventory_type}:")));
+        }
+
+        let results = self
+            .within_deadline(
+                apply_filter(request, &filter, vec![])
+                    .set_exclusive_start_key(exclusive_start_key)
+                    .send(),
+            )
             .instrument(self.instrumentation())
-            .await?;
+            .await
+            .map_err(RuntimeError::from)?;
 
         Ok(DynamoResultsPage {
             last_evaluated_key: results
                 .last_evaluated_key()
                 .and_then(|key| key["inventoryKey"].as_s().ok())
-                .cloned(),
+                .map(|inventory_key| PageToken::new(hash_key, inventory_key.clone())),
+            items: if let Some(items) = results.items {
+                from_items(items).map_err(RuntimeError::from)?
+            } else {
+                vec![]
+            },
+        })
+    }
+
+    pub async fn list_customer_inventory(
+        &self,
+        customer_id: Uuid,
+        filter: InventoryFilter,
+        page_token: Option<PageToken>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> Result<DynamoResultsPage<Inventory, PageToken>, ApiError> {
+        let partition = customer_id.to_string();
+        let exclusive_start_key = page_token
+            .map(|page_token| {
+                // index key contains the whole primary key of the record
+                match page_token.key.split_once('#') {
+                    Some((vessel_id, inventory_key)) if page_token.partition == partition => Ok(HashMap::from([
+                        ("customerAndVesselId".into(), S(format!("{customer_id}:{vessel_id}"))),
+                        ("inventoryKey".into(), S(inventory_key.into())),
+                        ("customerId".into(), S(partition.clone())),
+                        ("vesselAndInventoryKey".into(), S(page_token.key.clone())),
+                    ])),
+                    _ => Err(ApiError::InvalidPageToken),
+                }
+            })
+            .transpose()?;
+
+        let mut request = self
+            .client
+            .query()
+            .table_name(self.table_name.as_str())
+            .index_name(CUSTOMER_INDEX)
+            .key_condition_expression("customerId = :customerId")
+            .expression_attribute_values(":customerId", S(partition.clone()))
+            .limit(page_size(limit))
+            .scan_index_forward(order == SortOrder::Asc);
+
+        // across vessels type is not a key prefix anymore
+        let mut conditions = vec![];
+        if let Some(inventory_type) = &filter.inventory_type {
+            conditions.push("inventoryType = :inventoryType");
+            request = request.expression_attribute_values(":inventoryType", S(inventory_type.clone()));
+        }
+
+        let results = self
+            .within_deadline(
+                apply_filter(request, &filter, conditions)
+                    .set_exclusive_start_key(exclusive_start_key)
+                    .send(),
+            )
+            .instrument(self.instrumentation())
+            .await
+            .map_err(RuntimeError::from)?;
+
+        Ok(DynamoResultsPage {
+            last_evaluated_key: results
+                .last_evaluated_key()
+                .and_then(|key| key["vesselAndInventoryKey"].as_s().ok())
+                .map(|customer_sort_key| PageToken::new(partition, customer_sort_key.clone())),
             items: if let Some(items) = results.items {
-                from_items(items)?
+                from_items(items).map_err(RuntimeError::from)?
             } else {
                 vec![]
             },
         })
     }
 
+    // one-off migration of records written before CustomerIndex was introduced - returns number of updated records
+    pub async fn backfill_customer_index(&self) -> Result<usize, RuntimeError> {
+        let mut updated = 0;
+        let mut exclusive_start_key = None;
+
+        loop {
+            let results = self
+                .client
+                .scan()
+                .table_name(self.table_name.as_str())
+                .filter_expression("attribute_not_exists(vesselAndInventoryKey)")
+                .projection_expression("customerAndVesselId, inventoryKey")
+                .set_exclusive_start_key(exclusive_start_key)
+                .send()
+                .instrument(self.instrumentation())
+                .await?;
+
+            for item in results.items().unwrap_or_default() {
+                let (Some(S(hash_key)), Some(S(sort_key))) =
+                    (item.get("customerAndVesselId"), item.get("inventoryKey"))
+                else {
+                    continue;
+                };
+                let Some((customer_id, vessel_id)) = hash_key.split_once(':').filter(|_| is_inventory_key(hash_key))
+                else {
+                    continue;
+                };
+
+                let result = self
+                    .client
+                    .update_item()
+                    .table_name(self.table_name.as_str())
+                    .key("customerAndVesselId", S(hash_key.clone()))
+                    .key("inventoryKey", S(sort_key.clone()))
+                    .update_expression("SET customerId = :customerId, vesselAndInventoryKey = :vesselAndInventoryKey")
+                    // record may be deleted, or get the keys from regular write in the meantime
+                    .condition_expression(
+                        "attribute_exists(customerAndVesselId) AND attribute_not_exists(vesselAndInventoryKey)",
+                    )
+                    .expression_attribute_values(":customerId", S(customer_id.into()))
+                    .expression_attribute_values(":vesselAndInventoryKey", S(format!("{vessel_id}#{sort_key}")))
+                    .send()
+                    .instrument(self.instrumentation())
+                    .await
+                    .map_err(RuntimeError::from);
+
+                match result {
+                    Ok(_) => updated += 1,
+                    Err(error) if error.kind() == ErrorKind::ConditionFailed => {}
+                    Err(error) => return Err(error),
+                }
+            }
+
+            match results.last_evaluated_key {
+                Some(key) => exclusive_start_key = Some(key),
+                None => return Ok(updated),
+            }
+        }
+    }
+
     pub async fn get_inventory(
         &self,
         customer_id: Uuid,
@@ -118,74 +805,871 @@ impl InventoryDao {
         inventory_type: String,
         inventory_id: String,
     ) -> Result<Option<Inventory>, RuntimeError> {
-        self.client
-            .get_item()
-            .table_name(self.table_name.as_str())
-            .key("customerAndVesselId", S(hash_key_of(&customer_id, &vessel_id)))
-            .key("inventoryKey", S(sort_key_of(&inventory_type, &inventory_id)))
-            .send()
-            .instrument(self.instrumentation())
-            .await?
-            .item
-            .map(from_item::<_, Inventory>)
-            .map_or(Ok(None), |inventory| inventory.map(Some))
-            .map_err(RuntimeError::from)
+        self.within_deadline(
+            self.client
+                .get_item()
+                .table_name(self.table_name.as_str())
+                .key("customerAndVesselId", S(hash_key_of(&customer_id, &vessel_id)))
+                .key("inventoryKey", S(sort_key_of(&inventory_type, &inventory_id)))
+                .send(),
+        )
+        .instrument(self.instrumentation())
+        .await?
+        .item
+        .map(from_item::<_, Inventory>)
+        .map_or(Ok(None), |inventory| inventory.map(Some))
+        .map_err(RuntimeError::from)
     }
 
-    pub async fn delete_inventory(
+    // found records are returned in the order of requested keys, missing ones are skipped
+    pub async fn batch_get(
         &self,
         customer_id: Uuid,
         vessel_id: Uuid,
-        inventory_type: String,
-        inventory_id: String,
-    ) -> Result<(), RuntimeError> {
-        self.client
-            .delete_item()
-            .table_name(self.table_name.as_str())
-            .key("customerAndVesselId", S(hash_key_of(&customer_id, &vessel_id)))
-            .key("inventoryKey", S(sort_key_of(&inventory_type, &inventory_id)))
-            .send()
-            .instrument(self.instrumentation())
-            .await?;
-        Ok(())
+        keys: Vec<(String, String)>,
+    ) -> Result<Vec<Inventory>, RuntimeError> {
+        let hash_key = hash_key_of(&customer_id, &vessel_id);
+        let mut sort_keys = vec![];
+        for (inventory_type, inventory_id) in &keys {
+            let sort_key = sort_key_of(inventory_type, inventory_id);
+            // batch request can not contain the same key twice
+            if !sort_keys.contains(&sort_key) {
+                sort_keys.push(sort_key);
+            }
+        }
+
+        let mut found: HashMap<String, Inventory> = HashMap::new();
+        for item in self
+            .batch_get_items(
+                sort_keys
+                    .iter()
+                    .map(|sort_key| key_of(hash_key.clone(), sort_key.clone()))
+                    .collect(),
+            )
+            .await?
+        {
+            let inventory: Inventory = from_item(item)?;
+            found.insert(
+                sort_key_of(&inventory.inventory_type, &inventory.inventory_id),
+                inventory,
+            );
+        }
+
+        Ok(sort_keys
+            .into_iter()
+            .filter_map(|sort_key| found.remove(&sort_key))
+            .collect())
     }
 
-    fn instrumentation(&self) -> Span {
-        aws_metadata(
-            self.client.conf().region().map(|value| value.to_string()).as_deref(),
-            Some(self.table_name.as_str()),
+    pub async fn find_by_serial_number(
+        &self,
+        customer_id: Uuid,
+        serial_number: String,
+    ) -> Result<Vec<Inventory>, RuntimeError> {
+        let mut inventory = vec![];
+        let mut page_token = None;
+
+        // duplicates are exceptional, so fetch all of them at once
+        loop {
+            let results = self
+                .within_deadline(
+                    self.client
+                        .query()
+                        .table_name(self.table_name.as_str())
+                        .index_name(SERIAL_NUMBER_INDEX)
+                        .key_condition_expression("serialNumber = :serialNumber AND customerId = :customerId")
+                        .expression_attribute_values(":serialNumber", S(serial_number.clone()))
+                        .expression_attribute_values(":customerId", S(customer_id.to_string()))
+                        .filter_expression("attribute_not_exists(deletedAt)")
+                        .set_exclusive_start_key(page_token)
+                        .send(),
+                )
+                .instrument(self.instrumentation())
+                .await?;
+
+            if let Some(items) = results.items {
+                let page: Vec<Inventory> = from_items(items)?;
+                inventory.extend(page);
+            }
+
+            page_token = results.last_evaluated_key;
+            if page_token.is_none() {
+                return Ok(inventory);
+            }
+        }
+    }
+
+    pub async fn get_by_aws_instance_id(&self, aws_instance_id: String) -> Result<Option<Inventory>, RuntimeError> {
+        self.within_deadline(
+            self.client
+                .query()
+                .table_name(self.table_name.as_str())
+                .index_name(AWS_INSTANCE_ID_INDEX)
+                .key_condition_expression("awsInstanceId = :awsInstanceId")
+                .expression_attribute_values(":awsInstanceId", S(aws_instance_id))
+                // soft-deleted records keep the ID, but only one live record can hold it
+                .filter_expression("attribute_not_exists(deletedAt)")
+                .send(),
         )
+        .instrument(self.instrumentation())
+        .await?
+        .items
+        .and_then(|items| items.into_iter().next())
+        .map(from_item::<_, Inventory>)
+        .map_or(Ok(None), |inventory| inventory.map(Some))
+        .map_err(RuntimeError::from)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::inventory_dao::{hash_key_of, sort_key_of};
-    use crate::{Inventory, InventoryDao, RuntimeError};
-    use async_trait::async_trait;
-    use aws_config::load_from_env;
-    use aws_sdk_dynamodb::config::Builder;
-    use aws_sdk_dynamodb::operation::put_item::{PutItemError, PutItemOutput};
-    use aws_sdk_dynamodb::types::{
-        AttributeDefinition, AttributeValue::S, KeySchemaElement, KeyType, ProvisionedThroughput, ScalarAttributeType,
-    };
-    use aws_sdk_dynamodb::Client;
-    use aws_smithy_http::result::SdkError;
-    use chrono::{DateTime, FixedOffset, TimeZone, Utc};
-    use std::collections::HashMap;
-    use std::future::join;
-    use std::sync::atomic::{AtomicUsize, Ordering};
-    use test_context::{test_context, AsyncTestContext};
-    use tokio::test as tokio_test;
-    use uuid::{uuid, Uuid};
+    pub async fn list_history(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        page_token: Option<PageToken>,
+        limit: Option<u32>,
+    ) -> Result<DynamoResultsPage<HistoryEntry, PageToken>, ApiError> {
+        let hash_key = history_key_of(&customer_id, &vessel_id, &inventory_type, &inventory_id);
+        let exclusive_start_key = page_token
+            .map(|page_token| {
+                // token issued for another timeline can not be used as a starting point
+                if page_token.partition == hash_key {
+                    Ok(HashMap::from([
+                        ("customerAndVesselId".into(), S(hash_key.clone())),
+                        ("inventoryKey".into(), S(page_token.key)),
+                    ]))
+                } else {
+                    Err(ApiError::InvalidPageToken)
+                }
+            })
+            .transpose()?;
 
-    struct DynamoDbTestContext {
-        client: Box<Client>,
-        dao: Box<InventoryDao>,
-        table_name: String,
+        let results = self
+            .within_deadline(
+                self.client
+                    .query()
+                    .table_name(self.table_name.as_str())
+                    .key_condition_expression("customerAndVesselId = :customerAndVesselId")
+                    .expression_attribute_values(":customerAndVesselId", S(hash_key.clone()))
+                    .limit(page_size(limit))
+                    .set_exclusive_start_key(exclusive_start_key)
+                    .send(),
+            )
+            .instrument(self.instrumentation())
+            .await
+            .map_err(RuntimeError::from)?;
+
+        Ok(DynamoResultsPage {
+            last_evaluated_key: results
+                .last_evaluated_key()
+                .and_then(|key| key["inventoryKey"].as_s().ok())
+                .map(|entry_key| PageToken::new(hash_key, entry_key.clone())),
+            items: if let Some(items) = results.items {
+                from_items(items).map_err(RuntimeError::from)?
+            } else {
+                vec![]
+            },
+        })
     }
 
-    static NUMBER: AtomicUsize = AtomicUsize::new(0);
+    pub async fn update_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        changes: InventoryChanges,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        // nothing to change, but caller still expects current state (or not-found error)
+        if changes.serial_number.is_none() && changes.aws_instance_id.is_none() && changes.attributes.is_none() {
+            let current = self
+                .get_existing_inventory(customer_id, vessel_id, inventory_type, inventory_id)
+                .await?;
+
+            return if expected_version.is_some_and(|version| version != current.version) {
+                Err(ApiError::VersionConflict(current.version))
+            } else {
+                Ok(current)
+            };
+        }
+
+        // previous state is needed for the audit trail anyway, so it's always read-modify-write
+        self.reassign_inventory(
+            customer_id,
+            vessel_id,
+            inventory_type,
+            inventory_id,
+            changes,
+            expected_version,
+        )
+        .await
+    }
+
+    pub async fn delete_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        deletion: Deletion,
+        expected_version: Option<u64>,
+    ) -> Result<Option<Inventory>, ApiError> {
+        let current = match self
+            .get_inventory(customer_id, vessel_id, inventory_type.clone(), inventory_id.clone())
+            .await?
+        {
+            // deleting non-existing (or already deleted) record is not an error, caller decides how to treat it
+            None => return Ok(None),
+            Some(current) if current.deleted_at.is_some() => return Ok(None),
+            Some(current) => current,
+        };
+
+        if expected_version.is_some_and(|version| version != current.version) {
+            return Err(ApiError::VersionConflict(current.version));
+        }
+
+        // record is only marked as deleted, TTL attribute makes DynamoDB purge it after retention period
+        let mut update = UpdateExpression::default();
+        update.set(
+            "expiresAt",
+            N((deletion.deleted_at + self.retention).timestamp().to_string()),
+        );
+        update.set(
+            "deletedAt",
+            to_attribute_value(deletion.deleted_at).map_err(RuntimeError::from)?,
+        );
+        if let Some(deleted_by) = &deletion.deleted_by {
+            update.set("deletedBy", S(deleted_by.clone()));
+        }
+        update.customer_index_keys(&current);
+        update.increment("version");
+        update
+            .values
+            .insert(":expectedVersion".into(), N(current.version.to_string()));
+
+        let mut items = vec![TransactWriteItem::builder()
+            .update(
+                Update::builder()
+                    .table_name(self.table_name.as_str())
+                    .key("customerAndVesselId", S(hash_key_of(&customer_id, &vessel_id)))
+                    .key("inventoryKey", S(sort_key_of(&inventory_type, &inventory_id)))
+                    .update_expression(update.expression())
+                    .condition_expression(format!(
+                        "attribute_not_exists(#deletedAt) AND {}",
+                        version_condition(current.version)
+                    ))
+                    .set_expression_attribute_names(Some(update.names))
+                    .set_expression_attribute_values(Some(update.values))
+                    .build(),
+            )
+            .build()];
+        // deleted record releases its AWS instance ID, restoring reserves it again
+        items.extend(self.aws_instance_changes(current.aws_instance_id.as_ref(), None));
+        // transactional writes can not return old values, but the record is exactly what was checked by the condition
+        let after = Inventory {
+            deleted_at: Some(deletion.deleted_at),
+            deleted_by: deletion.deleted_by,
+            version: current.version + 1,
+            ..current.clone()
+        };
+        items.push(self.history_record(ChangeType::Deleted, Some(current), after.clone())?);
+
+        match self.transact(items).await {
+            Ok(_) => Ok(Some(after)),
+            Err(error) if failed_condition(&error, 0) => {
+                match self
+                    .resolve_conflict(customer_id, vessel_id, inventory_type, inventory_id)
+                    .await
+                {
+                    ApiError::InventoryNotFound(_, _) => Ok(None),
+                    error => Err(error),
+                }
+            }
+            Err(error) => Err(RuntimeError::from(error).into()),
+        }
+    }
+
+    pub async fn restore_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        let current = self
+            .get_inventory(customer_id, vessel_id, inventory_type.clone(), inventory_id.clone())
+            .await?
+            .ok_or_else(|| ApiError::InventoryNotFound(inventory_type.clone(), inventory_id.clone()))?;
+
+        if expected_version.is_some_and(|version| version != current.version) {
+            return Err(ApiError::VersionConflict(current.version));
+        }
+
+        // nothing to restore
+        if current.deleted_at.is_none() {
+            return Ok(current);
+        }
+
+        let mut update = UpdateExpression::default();
+        update.remove("deletedAt");
+        update.remove("deletedBy");
+        update.remove("expiresAt");
+        update.customer_index_keys(&current);
+        update.increment("version");
+        update
+            .values
+            .insert(":expectedVersion".into(), N(current.version.to_string()));
+
+        let mut items = vec![TransactWriteItem::builder()
+            .update(
+                Update::builder()
+                    .table_name(self.table_name.as_str())
+                    .key("customerAndVesselId", S(hash_key_of(&customer_id, &vessel_id)))
+                    .key("inventoryKey", S(sort_key_of(&inventory_type, &inventory_id)))
+                    .update_expression(update.expression())
+                    .condition_expression(format!(
+                        "attribute_exists(#deletedAt) AND {}",
+                        version_condition(current.version)
+                    ))
+                    .set_expression_attribute_names(Some(update.names))
+                    .set_expression_attribute_values(Some(update.values))
+                    .build(),
+            )
+            .build()];
+        items.extend(self.aws_instance_changes(None, current.aws_instance_id.as_ref()));
+        let last = items.len() - 1;
+        let after = Inventory {
+            deleted_at: None,
+            deleted_by: None,
+            version: current.version + 1,
+            ..current.clone()
+        };
+        items.push(self.history_record(ChangeType::Restored, Some(current.clone()), after.clone())?);
+
+        match self.transact(items).await {
+            Ok(_) => Ok(after),
+            // deleted record is not visible for regular conflict resolution
+            Err(error) if failed_condition(&error, 0) => Err(
+                match self
+                    .get_inventory(customer_id, vessel_id, inventory_type.clone(), inventory_id.clone())
+                    .await?
+                {
+                    Some(current) => ApiError::VersionConflict(current.version),
+                    None => ApiError::InventoryNotFound(inventory_type, inventory_id),
+                },
+            ),
+            Err(error) if failed_condition(&error, last) => Err(ApiError::AwsInstanceIdAlreadyAssigned(
+                current.aws_instance_id.unwrap_or_default(),
+            )),
+            Err(error) => Err(RuntimeError::from(error).into()),
+        }
+    }
+
+    pub async fn transition_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        change: StatusChange,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        let current = self
+            .get_existing_inventory(customer_id, vessel_id, inventory_type.clone(), inventory_id.clone())
+            .await?;
+
+        if expected_version.is_some_and(|version| version != current.version) {
+            return Err(ApiError::VersionConflict(current.version));
+        }
+
+        if !current.status.can_transition_to(change.status) {
+            return Err(ApiError::InvalidStatusTransition(current.status, change.status));
+        }
+
+        let mut update = UpdateExpression::default();
+        update.set("status", S(change.status.name().into()));
+        update.set("statusChange", to_attribute_value(&change).map_err(RuntimeError::from)?);
+        update.customer_index_keys(&current);
+        update.increment("version");
+        update
+            .values
+            .insert(":expectedVersion".into(), N(current.version.to_string()));
+
+        // version guards against concurrent transitions validated against the same source status
+        let after = Inventory {
+            status: change.status,
+            status_change: Some(change),
+            version: current.version + 1,
+            ..current.clone()
+        };
+        let items = vec![
+            TransactWriteItem::builder()
+                .update(
+                    Update::builder()
+                        .table_name(self.table_name.as_str())
+                        .key("customerAndVesselId", S(hash_key_of(&customer_id, &vessel_id)))
+                        .key("inventoryKey", S(sort_key_of(&inventory_type, &inventory_id)))
+                        .update_expression(update.expression())
+                        .condition_expression(format!(
+                            "attribute_exists(customerAndVesselId) AND {}",
+                            version_condition(current.version)
+                        ))
+                        .set_expression_attribute_names(Some(update.names))
+                        .set_expression_attribute_values(Some(update.values))
+                        .build(),
+                )
+                .build(),
+            self.history_record(ChangeType::StatusChanged, Some(current), after.clone())?,
+        ];
+
+        match self.transact(items).await {
+            Ok(_) => Ok(after),
+            Err(error) if failed_condition(&error, 0) => Err(self
+                .resolve_conflict(customer_id, vessel_id, inventory_type, inventory_id)
+                .await),
+            Err(error) => Err(RuntimeError::from(error).into()),
+        }
+    }
+
+    // vessel is a part of the primary key - record is moved by deleting it and re-creating under the new key
+    pub async fn transfer_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        target_vessel_id: Uuid,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        // single transaction can not touch the same item twice
+        if target_vessel_id == vessel_id {
+            return Err(ApiError::ValidationFailed {
+                field: "targetVesselId".into(),
+                reason: "must differ from current vessel".into(),
+            });
+        }
+
+        let current = self
+            .get_existing_inventory(customer_id, vessel_id, inventory_type.clone(), inventory_id.clone())
+            .await?;
+
+        if expected_version.is_some_and(|version| version != current.version) {
+            return Err(ApiError::VersionConflict(current.version));
+        }
+
+        let after = Inventory {
+            vessel_id: target_vessel_id,
+            transferred_at: Some(Utc::now().fixed_offset()),
+            version: current.version + 1,
+            ..current.clone()
+        };
+
+        // transfer entry closes the trail on the source vessel and opens it on the target one
+        let target_entry = self.history_item(ChangeType::Transferred, Some(current.clone()), after.clone())?;
+        let mut source_entry = target_entry.clone();
+        source_entry.insert(
+            "customerAndVesselId".into(),
+            S(history_key_of(&customer_id, &vessel_id, &inventory_type, &inventory_id)),
+        );
+
+        let items = vec![
+            TransactWriteItem::builder()
+                .delete(
+                    Delete::builder()
+                        .table_name(self.table_name.as_str())
+                        .key("customerAndVesselId", S(hash_key_of(&customer_id, &vessel_id)))
+                        .key("inventoryKey", S(sort_key_of(&inventory_type, &inventory_id)))
+                        .condition_expression(format!(
+                            "attribute_exists(customerAndVesselId) AND attribute_not_exists(deletedAt) AND {}",
+                            version_condition(current.version)
+                        ))
+                        .expression_attribute_names("#version", "version")
+                        .expression_attribute_values(":expectedVersion", N(current.version.to_string()))
+                        .build(),
+                )
+                .build(),
+            TransactWriteItem::builder()
+                .put(
+                    Put::builder()
+                        .table_name(self.table_name.as_str())
+                        .set_item(Some(inventory_item(&after)?))
+                        .condition_expression("attribute_not_exists(customerAndVesselId)")
+                        .build(),
+                )
+                .build(),
+            self.history_put(source_entry),
+            self.history_put(target_entry),
+        ];
+
+        match self.transact(items).await {
+            Ok(_) => Ok(after),
+            Err(error) if failed_condition(&error, 0) => Err(self
+                .resolve_conflict(customer_id, vessel_id, inventory_type, inventory_id)
+                .await),
+            Err(error) if failed_condition(&error, 1) => {
+                Err(ApiError::InventoryAlreadyExists(inventory_type, inventory_id))
+            }
+            Err(error) => Err(RuntimeError::from(error).into()),
+        }
+    }
+
+    async fn reassign_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        changes: InventoryChanges,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        let current = self
+            .get_existing_inventory(customer_id, vessel_id, inventory_type.clone(), inventory_id.clone())
+            .await?;
+
+        if expected_version.is_some_and(|version| version != current.version) {
+            return Err(ApiError::VersionConflict(current.version));
+        }
+
+        let aws_instance_id = match changes.aws_instance_id.clone() {
+            Some(aws_instance_id) => aws_instance_id,
+            None => current.aws_instance_id.clone(),
+        };
+
+        let mut update = UpdateExpression::default();
+        update.assign("serialNumber", changes.serial_number.clone().map(|value| value.map(S)));
+        update.assign("awsInstanceId", changes.aws_instance_id.map(|value| value.map(S)));
+        // empty attributes are not stored at all
+        update.assign(
+            "attributes",
+            match &changes.attributes {
+                Some(attributes) if attributes.is_empty() => Some(None),
+                Some(attributes) => Some(Some(to_attribute_value(attributes).map_err(RuntimeError::from)?)),
+                None => None,
+            },
+        );
+        update.customer_index_keys(&current);
+        update.increment("version");
+        update
+            .values
+            .insert(":expectedVersion".into(), N(current.version.to_string()));
+
+        let mut items = vec![TransactWriteItem::builder()
+            .update(
+                Update::builder()
+                    .table_name(self.table_name.as_str())
+                    .key("customerAndVesselId", S(hash_key_of(&customer_id, &vessel_id)))
+                    .key("inventoryKey", S(sort_key_of(&inventory_type, &inventory_id)))
+                    .update_expression(update.expression())
+                    .condition_expression(format!(
+                        "attribute_exists(customerAndVesselId) AND {}",
+                        version_condition(current.version)
+                    ))
+                    .set_expression_attribute_names(Some(update.names))
+                    .set_expression_attribute_values(Some(update.values))
+                    .build(),
+            )
+            .build()];
+        items.extend(self.aws_instance_changes(current.aws_instance_id.as_ref(), aws_instance_id.as_ref()));
+        let last = items.len() - 1;
+        // condition guarantees that the record was exactly in the state we read
+        let after = Inventory {
+            serial_number: changes.serial_number.unwrap_or(current.serial_number.clone()),
+            aws_instance_id: aws_instance_id.clone(),
+            attributes: changes.attributes.unwrap_or(current.attributes.clone()),
+            version: current.version + 1,
+            ..current.clone()
+        };
+        items.push(self.history_record(ChangeType::Updated, Some(current), after.clone())?);
+
+        match self.transact(items).await {
+            Ok(_) => Ok(after),
+            Err(error) if failed_condition(&error, 0) => Err(self
+                .resolve_conflict(customer_id, vessel_id, inventory_type, inventory_id)
+                .await),
+            Err(error) if failed_condition(&error, last) => Err(ApiError::AwsInstanceIdAlreadyAssigned(
+                aws_instance_id.unwrap_or_default(),
+            )),
+            Err(error) => Err(RuntimeError::from(error).into()),
+        }
+    }
+
+    fn creation_put(&self, inventory: &Inventory) -> Result<TransactWriteItem, RuntimeError> {
+        Ok(TransactWriteItem::builder()
+            .put(
+                Put::builder()
+                    .table_name(self.table_name.as_str())
+                    .set_item(Some(inventory_item(inventory)?))
+                    .condition_expression("attribute_not_exists(customerAndVesselId)")
+                    .build(),
+            )
+            .build())
+    }
+
+    // uniqueness of AWS instance ID is guarded by a dedicated marker record
+    fn aws_instance_changes(&self, current: Option<&String>, new: Option<&String>) -> Vec<TransactWriteItem> {
+        let mut items = vec![];
+
+        if current == new {
+            return items;
+        }
+
+        if let Some(aws_instance_id) = current {
+            items.push(
+                TransactWriteItem::builder()
+                    .delete(
+                        Delete::builder()
+                            .table_name(self.table_name.as_str())
+                            .key("customerAndVesselId", S(aws_instance_key_of(aws_instance_id)))
+                            .key("inventoryKey", S(AWS_INSTANCE_MARKER.into()))
+                            .build(),
+                    )
+                    .build(),
+            );
+        }
+
+        if let Some(aws_instance_id) = new {
+            items.push(
+                TransactWriteItem::builder()
+                    .put(
+                        Put::builder()
+                            .table_name(self.table_name.as_str())
+                            .item("customerAndVesselId", S(aws_instance_key_of(aws_instance_id)))
+                            .item("inventoryKey", S(AWS_INSTANCE_MARKER.into()))
+                            .condition_expression("attribute_not_exists(customerAndVesselId)")
+                            .build(),
+                    )
+                    .build(),
+            );
+        }
+
+        items
+    }
+
+    // audit trail entry is written in the same transaction as the change itself
+    fn history_record(
+        &self,
+        change_type: ChangeType,
+        before: Option<Inventory>,
+        after: Inventory,
+    ) -> Result<TransactWriteItem, RuntimeError> {
+        Ok(self.history_put(self.history_item(change_type, before, after)?))
+    }
+
+    fn history_put(&self, item: HashMap<String, AttributeValue>) -> TransactWriteItem {
+        TransactWriteItem::builder()
+            .put(
+                Put::builder()
+                    .table_name(self.table_name.as_str())
+                    .set_item(Some(item))
+                    // history is append-only
+                    .condition_expression("attribute_not_exists(customerAndVesselId)")
+                    .build(),
+            )
+            .build()
+    }
+
+    fn history_item(
+        &self,
+        change_type: ChangeType,
+        before: Option<Inventory>,
+        after: Inventory,
+    ) -> Result<HashMap<String, AttributeValue>, RuntimeError> {
+        let changed_at = Utc::now().fixed_offset();
+        let hash_key = history_key_of(
+            &after.customer_id,
+            &after.vessel_id,
+            &after.inventory_type,
+            &after.inventory_id,
+        );
+        let sort_key = history_entry_key_of(&changed_at, after.version);
+        let entry = HistoryEntry {
+            changed_at,
+            change_type,
+            actor: current_actor(),
+            version: after.version,
+            before,
+            after,
+        };
+
+        let mut item: HashMap<String, AttributeValue> = to_item(entry)?;
+        item.extend(key_of(hash_key, sort_key));
+        Ok(item)
+    }
+
+    async fn transact(&self, items: Vec<TransactWriteItem>) -> Result<(), SdkError<TransactWriteItemsError>> {
+        self.within_deadline(
+            self.client
+                .transact_write_items()
+                .set_transact_items(Some(items))
+                .send(),
+        )
+        .instrument(self.instrumentation())
+        .await?;
+        Ok(())
+    }
+
+    async fn batch_get_items(
+        &self,
+        keys: Vec<HashMap<String, AttributeValue>>,
+    ) -> Result<Vec<HashMap<String, AttributeValue>>, RuntimeError> {
+        let mut items = vec![];
+
+        for chunk in keys.chunks(BATCH_GET_SIZE) {
+            let mut keys = chunk.to_vec();
+            let mut attempt = 0;
+
+            while !keys.is_empty() {
+                if attempt >= BATCH_MAX_ATTEMPTS {
+                    return Err(RuntimeError::UnprocessedItem(format!("{} keys", keys.len())));
+                }
+                if attempt > 0 {
+                    self.backoff_within_deadline(attempt).await?;
+                }
+
+                let output = self
+                    .within_deadline(
+                        self.client
+                            .batch_get_item()
+                            .request_items(
+                                self.table_name.as_str(),
+                                KeysAndAttributes::builder().set_keys(Some(keys)).build(),
+                            )
+                            .send(),
+                    )
+                    .instrument(self.instrumentation())
+                    .await?;
+
+                items.extend(
+                    output
+                        .responses()
+                        .and_then(|responses| responses.get(self.table_name.as_str()))
+                        .cloned()
+                        .unwrap_or_default(),
+                );
+                keys = output
+                    .unprocessed_keys()
+                    .and_then(|unprocessed| unprocessed.get(self.table_name.as_str()))
+                    .and_then(|unprocessed| unprocessed.keys())
+                    .map(|keys| keys.to_vec())
+                    .unwrap_or_default();
+                attempt += 1;
+            }
+        }
+
+        Ok(items)
+    }
+
+    // soft-deleted records are not visible to modifications
+    async fn get_existing_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+    ) -> Result<Inventory, ApiError> {
+        self.get_inventory(customer_id, vessel_id, inventory_type.clone(), inventory_id.clone())
+            .await?
+            .filter(|current| current.deleted_at.is_none())
+            .ok_or(ApiError::InventoryNotFound(inventory_type, inventory_id))
+    }
+
+    // condition failed either because record does not exist, or because it was modified in the meantime
+    async fn resolve_conflict(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+    ) -> ApiError {
+        match self
+            .get_inventory(customer_id, vessel_id, inventory_type.clone(), inventory_id.clone())
+            .await
+        {
+            Ok(Some(current)) if current.deleted_at.is_none() => ApiError::VersionConflict(current.version),
+            Ok(_) => ApiError::InventoryNotFound(inventory_type, inventory_id),
+            Err(error) => error.into(),
+        }
+    }
+
+    // waiting for the next attempt must not eat the time needed to report the failure
+    async fn backoff_within_deadline(&self, attempt: u32) -> Result<(), RuntimeError> {
+        let delay = backoff(attempt);
+
+        if remaining_time().is_some_and(|remaining| delay >= remaining.saturating_sub(self.deadline_margin)) {
+            return Err(RuntimeError::DeadlineExceeded);
+        }
+
+        sleep(delay).await;
+        Ok(())
+    }
+
+    // request running past the invocation deadline is abandoned while there is still time to report the failure
+    async fn within_deadline<T, E>(
+        &self,
+        request: impl Future<Output = Result<T, SdkError<E>>>,
+    ) -> Result<T, SdkError<E>> {
+        let Some(remaining) = remaining_time() else {
+            return request.await;
+        };
+        let budget = remaining.saturating_sub(self.deadline_margin);
+
+        if budget.is_zero() {
+            return Err(SdkError::timeout_error("invocation deadline reached"));
+        }
+
+        match timeout(budget, request).await {
+            Ok(result) => result,
+            Err(_) => Err(SdkError::timeout_error("invocation deadline reached")),
+        }
+    }
+
+    fn instrumentation(&self) -> Span {
+        aws_metadata(
+            self.client.conf().region().map(|value| value.to_string()).as_deref(),
+            Some(self.table_name.as_str()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::inventory_dao::{
+        customer_sort_key_of, hash_key_of, history_entry_key_of, history_key_of, is_inventory_key, page_size,
+        sort_key_of, UpdateExpression,
+    };
+    use crate::lambda::{ACTOR, DEADLINE};
+    use crate::{
+        ApiError, ChangeType, Deletion, ErrorKind, Inventory, InventoryAttribute, InventoryChanges, InventoryDao,
+        InventoryFilter, InventoryStatus, PageToken, RuntimeError, SortOrder, StatusChange,
+    };
+    use async_trait::async_trait;
+    use aws_config::load_from_env;
+    use aws_sdk_dynamodb::config::Builder;
+    use aws_sdk_dynamodb::operation::put_item::{PutItemError, PutItemOutput};
+    use aws_sdk_dynamodb::types::{
+        AttributeDefinition, AttributeValue::N, AttributeValue::S, GlobalSecondaryIndex, KeySchemaElement, KeyType,
+        Projection, ProjectionType, ProvisionedThroughput, ScalarAttributeType,
+    };
+    use aws_sdk_dynamodb::Client;
+    use aws_smithy_http::result::SdkError;
+    use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+    use std::collections::HashMap;
+    use std::future::join;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration as StdDuration, SystemTime};
+    use test_context::{test_context, AsyncTestContext};
+    use tokio::test as tokio_test;
+    use uuid::{uuid, Uuid};
+
+    struct DynamoDbTestContext {
+        client: Box<Client>,
+        dao: Box<InventoryDao>,
+        table_name: String,
+    }
+
+    static NUMBER: AtomicUsize = AtomicUsize::new(0);
 
     // customers
     static ID_0: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
@@ -222,6 +1706,30 @@ mod tests {
                         .attribute_type(ScalarAttributeType::S)
                         .build(),
                 )
+                .attribute_definitions(
+                    AttributeDefinition::builder()
+                        .attribute_name("serialNumber")
+                        .attribute_type(ScalarAttributeType::S)
+                        .build(),
+                )
+                .attribute_definitions(
+                    AttributeDefinition::builder()
+                        .attribute_name("customerId")
+                        .attribute_type(ScalarAttributeType::S)
+                        .build(),
+                )
+                .attribute_definitions(
+                    AttributeDefinition::builder()
+                        .attribute_name("awsInstanceId")
+                        .attribute_type(ScalarAttributeType::S)
+                        .build(),
+                )
+                .attribute_definitions(
+                    AttributeDefinition::builder()
+                        .attribute_name("vesselAndInventoryKey")
+                        .attribute_type(ScalarAttributeType::S)
+                        .build(),
+                )
                 .key_schema(
                     KeySchemaElement::builder()
                         .attribute_name("customerAndVesselId")
@@ -234,211 +1742,2388 @@ mod tests {
                         .key_type(KeyType::Range)
                         .build(),
                 )
-                .provisioned_throughput(
-                    ProvisionedThroughput::builder()
-                        .read_capacity_units(1000)
-                        .write_capacity_units(1000)
-                        .build(),
-                )
+                .global_secondary_indexes(index("SerialNumberIndex", "serialNumber", Some("customerId")))
+                .global_secondary_indexes(index("AwsInstanceIdIndex", "awsInstanceId", None))
+                .global_secondary_indexes(index("CustomerIndex", "customerId", Some("vesselAndInventoryKey")))
+                .provisioned_throughput(throughput())
                 .send()
                 .await
                 .unwrap();
 
-            let context = DynamoDbTestContext {
-                client: Box::new(client.clone()),
-                dao: Box::new(InventoryDao::new(client, table_name.clone())),
-                table_name: table_name.clone(),
-            };
+            let context = DynamoDbTestContext {
+                client: Box::new(client.clone()),
+                dao: Box::new(InventoryDao::new(client, table_name.clone())),
+                table_name: table_name.clone(),
+            };
+
+            let (res1, res2, res3) = join!(
+                context.create_record(
+                    &ID_0,
+                    &ID_1,
+                    INVENTORY_TYPE_0,
+                    INVENTORY_ID_0,
+                    Some("q1w2e3"),
+                    None,
+                    "2011-01-30T14:58:00+01:00"
+                ),
+                context.create_record(
+                    &ID_0,
+                    &ID_1,
+                    INVENTORY_TYPE_0,
+                    INVENTORY_ID_1,
+                    None,
+                    Some("im-12345"),
+                    "2015-07-02T03:20:00+02:00"
+                ),
+                context.create_record(
+                    &ID_0,
+                    &ID_2,
+                    INVENTORY_TYPE_1,
+                    INVENTORY_ID_0,
+                    Some("r@nd0m"),
+                    None,
+                    "2017-11-11T16:00:00+02:00"
+                ),
+            )
+            .await;
+
+            res1.unwrap();
+            res2.unwrap();
+            res3.unwrap();
+
+            context
+        }
+
+        async fn teardown(self) {
+            self.client
+                .delete_table()
+                .table_name(self.table_name)
+                .send()
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn create_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let created_at = Utc
+            .with_ymd_and_hms(2015, 7, 2, 1, 20, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+
+        let save = ctx
+            .dao
+            .create_inventory(Inventory {
+                customer_id: ID_0,
+                vessel_id: ID_2,
+                inventory_type: INVENTORY_TYPE_1.to_string(),
+                inventory_id: INVENTORY_ID_1.to_string(),
+                serial_number: None,
+                aws_instance_id: None,
+                attributes: HashMap::new(),
+                status: InventoryStatus::InService,
+                status_change: None,
+                created_at,
+                deleted_at: None,
+                deleted_by: None,
+                transferred_at: None,
+                version: 0,
+            })
+            .await;
+        assert!(save.is_ok());
+
+        let inventory = ctx
+            .client
+            .get_item()
+            .table_name(ctx.table_name.as_str())
+            .key("customerAndVesselId", S(hash_key_of(&ID_0, &ID_2)))
+            .key(
+                "inventoryKey",
+                S(sort_key_of(&INVENTORY_TYPE_1.into(), &INVENTORY_ID_1.into())),
+            )
+            .send()
+            .await?;
+        assert!(inventory.item.is_some());
+
+        let item = inventory.item.unwrap();
+        assert_eq!("2015-07-02T03:20:00+02:00", item["createdAt"].as_s().unwrap());
+        assert_eq!("1", item["version"].as_n().unwrap());
+        assert_eq!(
+            &customer_sort_key_of(&ID_2, &INVENTORY_TYPE_1.into(), &INVENTORY_ID_1.into()),
+            item["vesselAndInventoryKey"].as_s().unwrap()
+        );
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn create_inventory_attributes(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let created_at = Utc
+            .with_ymd_and_hms(2015, 7, 2, 1, 20, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+        let installed_at = Utc
+            .with_ymd_and_hms(2014, 3, 1, 8, 0, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(3600).unwrap());
+        let attributes = HashMap::from([
+            ("manufacturer".into(), InventoryAttribute::String("Furuno".into())),
+            ("rackPosition".into(), InventoryAttribute::Number(3.5)),
+            ("primary".into(), InventoryAttribute::Bool(true)),
+            ("installedAt".into(), InventoryAttribute::Date(installed_at)),
+        ]);
+
+        ctx.dao
+            .create_inventory(Inventory {
+                customer_id: ID_0,
+                vessel_id: ID_2,
+                inventory_type: INVENTORY_TYPE_1.to_string(),
+                inventory_id: INVENTORY_ID_1.to_string(),
+                serial_number: None,
+                aws_instance_id: None,
+                attributes: attributes.clone(),
+                status: InventoryStatus::InService,
+                status_change: None,
+                created_at,
+                deleted_at: None,
+                deleted_by: None,
+                transferred_at: None,
+                version: 0,
+            })
+            .await
+            .unwrap();
+
+        let inventory = ctx
+            .dao
+            .get_inventory(ID_0, ID_2, INVENTORY_TYPE_1.into(), INVENTORY_ID_1.into())
+            .await?
+            .unwrap();
+        assert_eq!(attributes, inventory.attributes);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn upsert_inventory_clears_attributes(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let created_at = Utc
+            .with_ymd_and_hms(2015, 7, 2, 1, 20, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+        let inventory = |attributes| Inventory {
+            customer_id: ID_0,
+            vessel_id: ID_1,
+            inventory_type: INVENTORY_TYPE_0.to_string(),
+            inventory_id: INVENTORY_ID_0.to_string(),
+            serial_number: None,
+            aws_instance_id: None,
+            attributes,
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at,
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 0,
+        };
+
+        ctx.dao
+            .upsert_inventory(inventory(HashMap::from([(
+                "model".into(),
+                InventoryAttribute::String("X1".into()),
+            )])))
+            .await
+            .unwrap();
+        ctx.dao.upsert_inventory(inventory(HashMap::new())).await.unwrap();
+
+        let inventory = ctx
+            .dao
+            .get_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
+            .await?
+            .unwrap();
+        assert!(inventory.attributes.is_empty());
+        assert_eq!(2, inventory.version);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn create_inventory_duplicate(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let created_at = Utc
+            .with_ymd_and_hms(2017, 11, 11, 14, 0, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+
+        let save = ctx
+            .dao
+            .create_inventory(Inventory {
+                customer_id: ID_0,
+                vessel_id: ID_1,
+                inventory_type: INVENTORY_TYPE_0.to_string(),
+                inventory_id: INVENTORY_ID_0.to_string(),
+                serial_number: None,
+                aws_instance_id: None,
+                attributes: HashMap::new(),
+                status: InventoryStatus::InService,
+                status_change: None,
+                created_at,
+                deleted_at: None,
+                deleted_by: None,
+                transferred_at: None,
+                version: 0,
+            })
+            .await;
+
+        match save {
+            Err(ApiError::InventoryAlreadyExists(_, _)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        let inventory = ctx
+            .client
+            .get_item()
+            .table_name(ctx.table_name.as_str())
+            .key("customerAndVesselId", S(hash_key_of(&ID_0, &ID_1)))
+            .key(
+                "inventoryKey",
+                S(sort_key_of(&INVENTORY_TYPE_0.into(), &INVENTORY_ID_0.into())),
+            )
+            .send()
+            .await?;
+        assert_eq!(
+            "2011-01-30T14:58:00+01:00",
+            inventory.item.unwrap()["createdAt"].as_s().unwrap()
+        );
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn create_inventory_aws_instance_id_taken(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let created_at = Utc
+            .with_ymd_and_hms(2017, 11, 11, 14, 0, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+
+        let save = ctx
+            .dao
+            .create_inventory(Inventory {
+                customer_id: ID_0,
+                vessel_id: ID_3,
+                inventory_type: INVENTORY_TYPE_0.to_string(),
+                inventory_id: INVENTORY_ID_0.to_string(),
+                serial_number: None,
+                aws_instance_id: Some("mi-6789".into()),
+                attributes: HashMap::new(),
+                status: InventoryStatus::InService,
+                status_change: None,
+                created_at,
+                deleted_at: None,
+                deleted_by: None,
+                transferred_at: None,
+                version: 0,
+            })
+            .await;
+        assert!(save.is_ok());
+
+        let duplicate = ctx
+            .dao
+            .create_inventory(Inventory {
+                customer_id: ID_0,
+                vessel_id: ID_3,
+                inventory_type: INVENTORY_TYPE_0.to_string(),
+                inventory_id: INVENTORY_ID_1.to_string(),
+                serial_number: None,
+                aws_instance_id: Some("mi-6789".into()),
+                attributes: HashMap::new(),
+                status: InventoryStatus::InService,
+                status_change: None,
+                created_at,
+                deleted_at: None,
+                deleted_by: None,
+                transferred_at: None,
+                version: 0,
+            })
+            .await;
+
+        match duplicate {
+            Err(ApiError::AwsInstanceIdAlreadyAssigned(aws_instance_id)) => assert_eq!("mi-6789", aws_instance_id),
+            _ => panic!("Invalid error type."),
+        }
+
+        let inventory = ctx
+            .dao
+            .get_inventory(ID_0, ID_3, INVENTORY_TYPE_0.into(), INVENTORY_ID_1.into())
+            .await?;
+        assert!(inventory.is_none());
+
+        Ok(())
+    }
+
+    fn batch_inventory(vessel_id: Uuid, inventory_id: String, aws_instance_id: Option<&str>) -> Inventory {
+        Inventory {
+            customer_id: ID_0,
+            vessel_id,
+            inventory_type: INVENTORY_TYPE_0.to_string(),
+            inventory_id,
+            serial_number: None,
+            aws_instance_id: aws_instance_id.map(String::from),
+            attributes: HashMap::new(),
+            status: InventoryStatus::Ordered,
+            status_change: None,
+            created_at: Utc
+                .with_ymd_and_hms(2021, 3, 4, 8, 0, 0)
+                .unwrap()
+                .with_timezone(&FixedOffset::east_opt(3600).unwrap()),
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 0,
+        }
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn batch_create(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let results = ctx
+            .dao
+            .batch_create(vec![
+                batch_inventory(ID_3, "b0".into(), None),
+                batch_inventory(ID_3, "b0".into(), None),
+                batch_inventory(ID_1, INVENTORY_ID_0.into(), None),
+                batch_inventory(ID_3, "b1".into(), Some("i-batch")),
+                batch_inventory(ID_3, "b2".into(), Some("i-batch")),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(5, results.len());
+        assert!(results[0].is_ok());
+        match &results[1] {
+            Err(ApiError::InventoryAlreadyExists(_, inventory_id)) => assert_eq!("b0", inventory_id),
+            _ => panic!("Invalid error type."),
+        }
+        match &results[2] {
+            Err(ApiError::InventoryAlreadyExists(_, inventory_id)) => assert_eq!(INVENTORY_ID_0, inventory_id),
+            _ => panic!("Invalid error type."),
+        }
+        assert!(results[3].is_ok());
+        match &results[4] {
+            Err(ApiError::AwsInstanceIdAlreadyAssigned(aws_instance_id)) => assert_eq!("i-batch", aws_instance_id),
+            _ => panic!("Invalid error type."),
+        }
+
+        let created = ctx
+            .dao
+            .get_inventory(ID_0, ID_3, INVENTORY_TYPE_0.into(), "b0".into())
+            .await?
+            .unwrap();
+        assert_eq!(1, created.version);
+        assert_eq!(InventoryStatus::Ordered, created.status);
+
+        let reserved = ctx.dao.get_by_aws_instance_id("i-batch".into()).await?.unwrap();
+        assert_eq!("b1", reserved.inventory_id);
+
+        // existing record is not overwritten
+        let existing = ctx
+            .dao
+            .get_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
+            .await?
+            .unwrap();
+        assert_eq!(Some("q1w2e3".to_string()), existing.serial_number);
+
+        let history = ctx
+            .dao
+            .list_history(ID_0, ID_3, INVENTORY_TYPE_0.into(), "b0".into(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(1, history.items.len());
+        assert_eq!(ChangeType::Created, history.items[0].change_type);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn batch_create_chunks(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let results = ctx
+            .dao
+            .batch_create(
+                (0..30)
+                    .map(|index| batch_inventory(ID_3, format!("c{index:02}"), None))
+                    .collect(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(30, results.len());
+        assert!(results.iter().all(Result::is_ok));
+
+        let listed = ctx
+            .dao
+            .list_inventory(ID_0, ID_3, InventoryFilter::default(), None, None, SortOrder::Asc)
+            .await
+            .unwrap();
+        assert_eq!(30, listed.items.len());
+        assert_eq!("c00", listed.items[0].inventory_id);
+        assert_eq!("c29", listed.items[29].inventory_id);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn batch_create_chunks_aws_instance_ids(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        // markers take additional transaction items, so chunks hold fewer entries
+        let results = ctx
+            .dao
+            .batch_create(
+                (0..20)
+                    .map(|index| batch_inventory(ID_3, format!("m{index:02}"), Some(&format!("i-m{index:02}"))))
+                    .collect(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(20, results.len());
+        assert!(results.iter().all(Result::is_ok));
+
+        let reserved = ctx.dao.get_by_aws_instance_id("i-m19".into()).await?.unwrap();
+        assert_eq!("m19", reserved.inventory_id);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn batch_create_aws_instance_id_taken(ctx: &DynamoDbTestContext) -> Result<(), ApiError> {
+        ctx.dao
+            .create_inventory(batch_inventory(ID_1, "t0".into(), Some("i-taken")))
+            .await?;
+
+        let results = ctx
+            .dao
+            .batch_create(vec![
+                batch_inventory(ID_3, "t1".into(), Some("i-taken")),
+                batch_inventory(ID_3, "t2".into(), Some("i-free")),
+            ])
+            .await?;
+
+        match &results[0] {
+            Err(ApiError::AwsInstanceIdAlreadyAssigned(aws_instance_id)) => assert_eq!("i-taken", aws_instance_id),
+            _ => panic!("Invalid error type."),
+        }
+        assert!(results[1].is_ok());
+
+        // rejected entry is not created at all
+        assert!(ctx
+            .dao
+            .get_inventory(ID_0, ID_3, INVENTORY_TYPE_0.into(), "t1".into())
+            .await?
+            .is_none());
+        let reserved = ctx.dao.get_by_aws_instance_id("i-taken".into()).await?.unwrap();
+        assert_eq!("t0", reserved.inventory_id);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn batch_create_concurrent(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let (batch, single) = join!(
+            ctx.dao.batch_create(vec![
+                batch_inventory(ID_3, "r0".into(), None),
+                batch_inventory(ID_3, "r1".into(), None),
+            ]),
+            ctx.dao.create_inventory(Inventory {
+                serial_number: Some("concurrent".into()),
+                ..batch_inventory(ID_3, "r0".into(), None)
+            }),
+        )
+        .await;
+        let batch = batch.unwrap();
+
+        // only one of the racing requests creates the record, the other one never overwrites it
+        assert!(!(batch[0].is_ok() && single.is_ok()));
+        assert!(batch[1].is_ok());
+
+        let history = ctx
+            .dao
+            .list_history(ID_0, ID_3, INVENTORY_TYPE_0.into(), "r0".into(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            [&batch[0], &single].iter().filter(|result| result.is_ok()).count(),
+            history.items.len()
+        );
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn upsert_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let created_at = Utc
+            .with_ymd_and_hms(2015, 7, 2, 1, 20, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+
+        let save = ctx
+            .dao
+            .upsert_inventory(Inventory {
+                customer_id: ID_0,
+                vessel_id: ID_1,
+                inventory_type: INVENTORY_TYPE_0.to_string(),
+                inventory_id: INVENTORY_ID_0.to_string(),
+                serial_number: Some("z0x9c8".into()),
+                aws_instance_id: None,
+                attributes: HashMap::new(),
+                status: InventoryStatus::InService,
+                status_change: None,
+                created_at,
+                deleted_at: None,
+                deleted_by: None,
+                transferred_at: None,
+                version: 0,
+            })
+            .await;
+        assert!(save.is_ok());
+
+        let inventory = ctx
+            .client
+            .get_item()
+            .table_name(ctx.table_name.as_str())
+            .key("customerAndVesselId", S(hash_key_of(&ID_0, &ID_1)))
+            .key(
+                "inventoryKey",
+                S(sort_key_of(&INVENTORY_TYPE_0.into(), &INVENTORY_ID_0.into())),
+            )
+            .send()
+            .await?
+            .item
+            .unwrap();
+        assert_eq!("z0x9c8", inventory["serialNumber"].as_s().unwrap());
+        assert_eq!("2015-07-02T03:20:00+02:00", inventory["createdAt"].as_s().unwrap());
+        assert_eq!("1", inventory["version"].as_n().unwrap());
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn get_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let created_at = Utc
+            .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(3600).unwrap());
+
+        let inventory = ctx
+            .dao
+            .get_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
+            .await?
+            .unwrap();
+        assert!(inventory.aws_instance_id.is_none());
+        assert_eq!(created_at, inventory.created_at);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn get_inventory_unexisting(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let unexisting = ctx
+            .dao
+            .get_inventory(ID_0, ID_1, INVENTORY_TYPE_1.into(), INVENTORY_ID_1.into())
+            .await?;
+        assert!(unexisting.is_none());
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn get_inventory_within_deadline(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let inventory = DEADLINE
+            .scope(
+                SystemTime::now() + StdDuration::from_secs(10),
+                ctx.dao
+                    .get_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into()),
+            )
+            .await?;
+        assert!(inventory.is_some());
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn get_inventory_past_deadline(ctx: &DynamoDbTestContext) {
+        // remaining time is within the margin reserved for reporting the failure
+        let result = DEADLINE
+            .scope(
+                SystemTime::now() + StdDuration::from_millis(100),
+                ctx.dao
+                    .get_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into()),
+            )
+            .await;
+
+        match result {
+            Err(error) => assert_eq!(ErrorKind::Timeout, error.kind()),
+            Ok(_) => panic!("Request should be abandoned."),
+        }
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn backoff_within_deadline(ctx: &DynamoDbTestContext) {
+        let result = DEADLINE
+            .scope(
+                SystemTime::now() + StdDuration::from_secs(10),
+                ctx.dao.backoff_within_deadline(1),
+            )
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn backoff_past_deadline(ctx: &DynamoDbTestContext) {
+        // 800ms delay exceeds the time left after reserving the deadline margin
+        let result = DEADLINE
+            .scope(
+                SystemTime::now() + StdDuration::from_millis(1000),
+                ctx.dao.backoff_within_deadline(4),
+            )
+            .await;
+
+        match result {
+            Err(error) => assert_eq!(ErrorKind::Timeout, error.kind()),
+            Ok(_) => panic!("Backoff should be abandoned."),
+        }
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn batch_get(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let found = ctx
+            .dao
+            .batch_get(
+                ID_0,
+                ID_1,
+                vec![
+                    (INVENTORY_TYPE_0.into(), INVENTORY_ID_1.into()),
+                    (INVENTORY_TYPE_1.into(), INVENTORY_ID_0.into()),
+                    (INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into()),
+                    (INVENTORY_TYPE_0.into(), INVENTORY_ID_1.into()),
+                ],
+            )
+            .await?;
+
+        assert_eq!(2, found.len());
+        assert_eq!(INVENTORY_ID_1, found[0].inventory_id);
+        assert_eq!(Some("im-12345".to_string()), found[0].aws_instance_id);
+        assert_eq!(INVENTORY_ID_0, found[1].inventory_id);
+        assert_eq!(Some("q1w2e3".to_string()), found[1].serial_number);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn batch_get_chunks(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let results = ctx
+            .dao
+            .batch_create(
+                (0..120)
+                    .map(|index| batch_inventory(ID_3, format!("g{index:03}"), None))
+                    .collect(),
+            )
+            .await
+            .unwrap();
+        assert!(results.iter().all(Result::is_ok));
+
+        let found = ctx
+            .dao
+            .batch_get(
+                ID_0,
+                ID_3,
+                (0..130)
+                    .rev()
+                    .map(|index| (INVENTORY_TYPE_0.to_string(), format!("g{index:03}")))
+                    .collect(),
+            )
+            .await?;
+
+        assert_eq!(120, found.len());
+        assert_eq!("g119", found[0].inventory_id);
+        assert_eq!("g000", found[119].inventory_id);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn find_by_serial_number(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let results = ctx.dao.find_by_serial_number(ID_0, "r@nd0m".into()).await?;
+        assert_eq!(1, results.len());
+        assert_eq!(ID_2, results[0].vessel_id);
+        assert_eq!(INVENTORY_TYPE_1, results[0].inventory_type);
+        assert_eq!(INVENTORY_ID_0, results[0].inventory_id);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn find_by_serial_number_other_customer(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let results = ctx.dao.find_by_serial_number(ID_3, "r@nd0m".into()).await?;
+        assert!(results.is_empty());
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn get_by_aws_instance_id(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let inventory = ctx.dao.get_by_aws_instance_id("im-12345".into()).await?.unwrap();
+        assert_eq!(ID_0, inventory.customer_id);
+        assert_eq!(ID_1, inventory.vessel_id);
+        assert_eq!(INVENTORY_TYPE_0, inventory.inventory_type);
+        assert_eq!(INVENTORY_ID_1, inventory.inventory_id);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn get_by_aws_instance_id_unexisting(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let unexisting = ctx.dao.get_by_aws_instance_id("mi-0000".into()).await?;
+        assert!(unexisting.is_none());
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn update_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let created_at = Utc
+            .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(3600).unwrap());
+
+        let result = ctx
+            .dao
+            .update_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                InventoryChanges {
+                    serial_number: Some(None),
+                    aws_instance_id: Some(Some("mi-0123".into())),
+                    attributes: None,
+                },
+                Some(0),
+            )
+            .await;
+        assert!(result.is_ok());
+
+        let inventory = result.unwrap();
+        assert!(inventory.serial_number.is_none());
+        assert_eq!(Some("mi-0123".to_string()), inventory.aws_instance_id);
+        assert_eq!(created_at, inventory.created_at);
+        assert_eq!(1, inventory.version);
+
+        let item = ctx
+            .client
+            .get_item()
+            .table_name(ctx.table_name.as_str())
+            .key("customerAndVesselId", S(hash_key_of(&ID_0, &ID_1)))
+            .key(
+                "inventoryKey",
+                S(sort_key_of(&INVENTORY_TYPE_0.into(), &INVENTORY_ID_0.into())),
+            )
+            .send()
+            .await?
+            .item
+            .unwrap();
+        assert!(!item.contains_key("serialNumber"));
+        assert_eq!("mi-0123", item["awsInstanceId"].as_s().unwrap());
+        assert_eq!("2011-01-30T14:58:00+01:00", item["createdAt"].as_s().unwrap());
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn update_inventory_attributes(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let attributes = HashMap::from([("manufacturer".to_string(), InventoryAttribute::String("Furuno".into()))]);
+        let changes = |attributes| InventoryChanges {
+            attributes: Some(attributes),
+            ..InventoryChanges::default()
+        };
+
+        let inventory = ctx
+            .dao
+            .update_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                changes(attributes.clone()),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(attributes, inventory.attributes);
+        assert_eq!(1, inventory.version);
+
+        let stored = ctx
+            .dao
+            .get_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(attributes, stored.attributes);
+
+        let inventory = ctx
+            .dao
+            .update_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                changes(HashMap::new()),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(inventory.attributes.is_empty());
+        assert_eq!(2, inventory.version);
+
+        let item = ctx
+            .client
+            .get_item()
+            .table_name(ctx.table_name.as_str())
+            .key("customerAndVesselId", S(hash_key_of(&ID_0, &ID_1)))
+            .key(
+                "inventoryKey",
+                S(sort_key_of(&INVENTORY_TYPE_0.into(), &INVENTORY_ID_0.into())),
+            )
+            .send()
+            .await?
+            .item
+            .unwrap();
+        assert!(!item.contains_key("attributes"));
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn update_inventory_unexisting(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let unexisting = ctx
+            .dao
+            .update_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_1.into(),
+                INVENTORY_ID_1.into(),
+                InventoryChanges {
+                    serial_number: Some(Some("q1w2e3".into())),
+                    aws_instance_id: None,
+                    attributes: None,
+                },
+                None,
+            )
+            .await;
+
+        match unexisting {
+            Err(ApiError::InventoryNotFound(_, _)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn update_inventory_version_conflict(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let outdated = ctx
+            .dao
+            .update_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                InventoryChanges {
+                    serial_number: Some(Some("z0x9c8".into())),
+                    aws_instance_id: None,
+                    attributes: None,
+                },
+                Some(3),
+            )
+            .await;
+
+        match outdated {
+            Err(ApiError::VersionConflict(0)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        let inventory = ctx
+            .dao
+            .get_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
+            .await?
+            .unwrap();
+        assert_eq!(Some("q1w2e3".to_string()), inventory.serial_number);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn update_inventory_aws_instance_id_taken(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let result = ctx
+            .dao
+            .update_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                InventoryChanges {
+                    serial_number: None,
+                    aws_instance_id: Some(Some("mi-0123".into())),
+                    attributes: None,
+                },
+                None,
+            )
+            .await;
+        assert!(result.is_ok());
+
+        let taken = ctx
+            .dao
+            .update_inventory(
+                ID_0,
+                ID_2,
+                INVENTORY_TYPE_1.into(),
+                INVENTORY_ID_0.into(),
+                InventoryChanges {
+                    serial_number: None,
+                    aws_instance_id: Some(Some("mi-0123".into())),
+                    attributes: None,
+                },
+                None,
+            )
+            .await;
+
+        match taken {
+            Err(ApiError::AwsInstanceIdAlreadyAssigned(_)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        // releasing the ID makes it available again
+        let result = ctx
+            .dao
+            .update_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                InventoryChanges {
+                    serial_number: None,
+                    aws_instance_id: Some(None),
+                    attributes: None,
+                },
+                None,
+            )
+            .await;
+        assert!(result.is_ok());
+
+        let inventory = ctx
+            .dao
+            .update_inventory(
+                ID_0,
+                ID_2,
+                INVENTORY_TYPE_1.into(),
+                INVENTORY_ID_0.into(),
+                InventoryChanges {
+                    serial_number: None,
+                    aws_instance_id: Some(Some("mi-0123".into())),
+                    attributes: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(Some("mi-0123".to_string()), inventory.aws_instance_id);
+
+        Ok(())
+    }
+
+    fn status_change(status: InventoryStatus) -> StatusChange {
+        StatusChange {
+            status,
+            changed_at: Utc
+                .with_ymd_and_hms(2019, 5, 12, 9, 30, 0)
+                .unwrap()
+                .with_timezone(&FixedOffset::east_opt(7200).unwrap()),
+            changed_by: Some("technician".into()),
+            reason: Some("Display broken.".into()),
+        }
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn transition_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let inventory = ctx
+            .dao
+            .transition_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                status_change(InventoryStatus::Faulty),
+                Some(0),
+            )
+            .await
+            .unwrap();
+        assert_eq!(InventoryStatus::Faulty, inventory.status);
+        assert_eq!(Some(status_change(InventoryStatus::Faulty)), inventory.status_change);
+        assert_eq!(1, inventory.version);
+
+        let stored = ctx
+            .dao
+            .get_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
+            .await?
+            .unwrap();
+        assert_eq!(InventoryStatus::Faulty, stored.status);
+        assert_eq!(Some("technician".into()), stored.status_change.unwrap().changed_by);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn upsert_inventory_keeps_status(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        ctx.dao
+            .transition_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                status_change(InventoryStatus::Faulty),
+                None,
+            )
+            .await
+            .unwrap();
+
+        ctx.dao
+            .upsert_inventory(Inventory {
+                customer_id: ID_0,
+                vessel_id: ID_1,
+                inventory_type: INVENTORY_TYPE_0.to_string(),
+                inventory_id: INVENTORY_ID_0.to_string(),
+                serial_number: None,
+                aws_instance_id: None,
+                attributes: HashMap::new(),
+                status: InventoryStatus::Ordered,
+                status_change: None,
+                created_at: Utc
+                    .with_ymd_and_hms(2015, 7, 2, 1, 20, 0)
+                    .unwrap()
+                    .with_timezone(&FixedOffset::east_opt(7200).unwrap()),
+                deleted_at: None,
+                deleted_by: None,
+                transferred_at: None,
+                version: 0,
+            })
+            .await
+            .unwrap();
+
+        let inventory = ctx
+            .dao
+            .get_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
+            .await?
+            .unwrap();
+        assert_eq!(InventoryStatus::Faulty, inventory.status);
+        assert!(inventory.status_change.is_some());
+        assert_eq!(2, inventory.version);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn transition_inventory_forbidden(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let result = ctx
+            .dao
+            .transition_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                status_change(InventoryStatus::Installed),
+                None,
+            )
+            .await;
+
+        match result {
+            Err(ApiError::InvalidStatusTransition(InventoryStatus::InService, InventoryStatus::Installed)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn transition_inventory_unexisting(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let result = ctx
+            .dao
+            .transition_inventory(
+                ID_0,
+                ID_3,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                status_change(InventoryStatus::Faulty),
+                None,
+            )
+            .await;
+
+        match result {
+            Err(ApiError::InventoryNotFound(_, _)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn transition_inventory_version_conflict(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let result = ctx
+            .dao
+            .transition_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                status_change(InventoryStatus::Faulty),
+                Some(5),
+            )
+            .await;
+
+        match result {
+            Err(ApiError::VersionConflict(0)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn update_expression() {
+        let mut update = UpdateExpression::default();
+        update.assign("serialNumber", Some(Some(S("abc".into()))));
+        update.assign("awsInstanceId", Some(None));
+        update.assign("createdAt", None);
+
+        assert_eq!(
+            "SET #serialNumber = :serialNumber REMOVE #awsInstanceId",
+            update.expression()
+        );
+        assert_eq!(2, update.names.len());
+        assert_eq!(1, update.values.len());
+    }
+
+    #[test]
+    fn update_expression_increment() {
+        let mut update = UpdateExpression::default();
+        update.increment("version");
+
+        assert_eq!(
+            "SET #version = if_not_exists(#version, :zero) + :one",
+            update.expression()
+        );
+    }
+
+    fn deletion() -> Deletion {
+        Deletion {
+            deleted_at: Utc
+                .with_ymd_and_hms(2020, 2, 3, 10, 0, 0)
+                .unwrap()
+                .with_timezone(&FixedOffset::east_opt(3600).unwrap()),
+            deleted_by: Some("technician".into()),
+        }
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn delete_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let result = ctx
+            .dao
+            .delete_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                deletion(),
+                Some(0),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(INVENTORY_ID_0, result.inventory_id);
+        assert_eq!(Some("q1w2e3".to_string()), result.serial_number);
+        assert_eq!(Some("technician".to_string()), result.deleted_by);
+        assert_eq!(1, result.version);
+
+        let license = ctx
+            .client
+            .get_item()
+            .table_name(ctx.table_name.as_str())
+            .key("customerAndVesselId", S(hash_key_of(&ID_0, &ID_1)))
+            .key(
+                "inventoryKey",
+                S(sort_key_of(&INVENTORY_TYPE_0.to_string(), &INVENTORY_ID_0.to_string())),
+            )
+            .send()
+            .await?
+            .item
+            .unwrap();
+        // 2020-02-03T09:00:00Z + 30 days
+        assert_eq!(Some(&N("1583312400".into())), license.get("expiresAt"));
+        assert_eq!(Some(&S("technician".into())), license.get("deletedBy"));
+
+        let inventory = ctx
+            .dao
+            .get_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
+            .await?
+            .unwrap();
+        assert_eq!(Some(deletion().deleted_at), inventory.deleted_at);
+        assert_eq!(1, inventory.version);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn delete_inventory_unexisting(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let unexisting = ctx
+            .dao
+            .delete_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_1.into(),
+                INVENTORY_ID_1.into(),
+                deletion(),
+                None,
+            )
+            .await;
+        assert!(unexisting.unwrap().is_none());
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn delete_inventory_already_deleted(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let deleted = ctx
+            .dao
+            .delete_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                deletion(),
+                None,
+            )
+            .await;
+        assert!(deleted.unwrap().is_some());
+
+        let repeated = ctx
+            .dao
+            .delete_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                deletion(),
+                None,
+            )
+            .await;
+        assert!(repeated.unwrap().is_none());
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn delete_inventory_version_conflict(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let outdated = ctx
+            .dao
+            .delete_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                deletion(),
+                Some(2),
+            )
+            .await;
+
+        match outdated {
+            Err(ApiError::VersionConflict(0)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        let inventory = ctx
+            .dao
+            .get_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
+            .await?;
+        assert!(inventory.unwrap().deleted_at.is_none());
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn delete_inventory_hidden(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let result = ctx
+            .dao
+            .delete_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_1.into(),
+                deletion(),
+                None,
+            )
+            .await;
+        assert!(result.is_ok());
+
+        let listed = ctx
+            .dao
+            .list_inventory(ID_0, ID_1, InventoryFilter::default(), None, None, SortOrder::Asc)
+            .await
+            .unwrap();
+        assert_eq!(1, listed.items.len());
+        assert_eq!(INVENTORY_ID_0, listed.items[0].inventory_id);
+
+        let all = ctx
+            .dao
+            .list_inventory(
+                ID_0,
+                ID_1,
+                InventoryFilter {
+                    include_deleted: true,
+                    ..InventoryFilter::default()
+                },
+                None,
+                None,
+                SortOrder::Asc,
+            )
+            .await
+            .unwrap();
+        assert_eq!(2, all.items.len());
+
+        let located = ctx.dao.get_by_aws_instance_id("im-12345".into()).await?;
+        assert!(located.is_none());
+
+        let modified = ctx
+            .dao
+            .update_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_1.into(),
+                InventoryChanges {
+                    serial_number: Some(Some("abc".into())),
+                    aws_instance_id: None,
+                    attributes: None,
+                },
+                None,
+            )
+            .await;
+
+        match modified {
+            Err(ApiError::InventoryNotFound(_, _)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn restore_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        ctx.dao
+            .delete_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_1.into(),
+                deletion(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let inventory = ctx
+            .dao
+            .restore_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_1.into(), Some(1))
+            .await
+            .unwrap();
+        assert!(inventory.deleted_at.is_none());
+        assert_eq!(2, inventory.version);
+
+        let license = ctx
+            .client
+            .get_item()
+            .table_name(ctx.table_name.as_str())
+            .key("customerAndVesselId", S(hash_key_of(&ID_0, &ID_1)))
+            .key(
+                "inventoryKey",
+                S(sort_key_of(&INVENTORY_TYPE_0.to_string(), &INVENTORY_ID_1.to_string())),
+            )
+            .send()
+            .await?
+            .item
+            .unwrap();
+        assert!(!license.contains_key("expiresAt"));
+        assert!(!license.contains_key("deletedBy"));
+
+        let located = ctx.dao.get_by_aws_instance_id("im-12345".into()).await?;
+        assert!(located.is_some());
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn restore_inventory_unexisting(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let unexisting = ctx
+            .dao
+            .restore_inventory(ID_0, ID_1, INVENTORY_TYPE_1.into(), INVENTORY_ID_1.into(), None)
+            .await;
+
+        match unexisting {
+            Err(ApiError::InventoryNotFound(_, _)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn restore_inventory_aws_instance_id_taken(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        ctx.dao
+            .update_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                InventoryChanges {
+                    serial_number: None,
+                    aws_instance_id: Some(Some("mi-0123".into())),
+                    attributes: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        ctx.dao
+            .delete_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                deletion(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        // released ID can be taken by another device in the meantime
+        ctx.dao
+            .update_inventory(
+                ID_0,
+                ID_2,
+                INVENTORY_TYPE_1.into(),
+                INVENTORY_ID_0.into(),
+                InventoryChanges {
+                    serial_number: None,
+                    aws_instance_id: Some(Some("mi-0123".into())),
+                    attributes: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let restored = ctx
+            .dao
+            .restore_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into(), None)
+            .await;
+
+        match restored {
+            Err(ApiError::AwsInstanceIdAlreadyAssigned(_)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn transfer_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let inventory = ctx
+            .dao
+            .transfer_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                ID_3,
+                Some(0),
+            )
+            .await
+            .unwrap();
+        assert_eq!(ID_3, inventory.vessel_id);
+        assert_eq!(1, inventory.version);
+        assert!(inventory.transferred_at.is_some());
+        assert_eq!(
+            DateTime::parse_from_rfc3339("2011-01-30T14:58:00+01:00").unwrap(),
+            inventory.created_at
+        );
+
+        let source = ctx
+            .dao
+            .get_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
+            .await?;
+        assert!(source.is_none());
+
+        let target = ctx
+            .dao
+            .get_inventory(ID_0, ID_3, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
+            .await?
+            .unwrap();
+        assert_eq!(Some("q1w2e3".to_string()), target.serial_number);
+        assert_eq!(inventory.transferred_at, target.transferred_at);
+
+        // both vessels keep track of the move
+        for vessel_id in [ID_1, ID_3] {
+            let history = ctx
+                .dao
+                .list_history(
+                    ID_0,
+                    vessel_id,
+                    INVENTORY_TYPE_0.into(),
+                    INVENTORY_ID_0.into(),
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+            assert_eq!(1, history.items.len());
+            assert_eq!(ChangeType::Transferred, history.items[0].change_type);
+            assert_eq!(
+                Some(ID_1),
+                history.items[0].before.as_ref().map(|before| before.vessel_id)
+            );
+            assert_eq!(ID_3, history.items[0].after.vessel_id);
+        }
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn transfer_inventory_unexisting(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let unexisting = ctx
+            .dao
+            .transfer_inventory(ID_0, ID_1, INVENTORY_TYPE_1.into(), INVENTORY_ID_1.into(), ID_3, None)
+            .await;
+
+        match unexisting {
+            Err(ApiError::InventoryNotFound(_, _)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn transfer_inventory_version_conflict(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let conflict = ctx
+            .dao
+            .transfer_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                ID_3,
+                Some(3),
+            )
+            .await;
+
+        match conflict {
+            Err(ApiError::VersionConflict(0)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn transfer_inventory_target_taken(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let same = ctx
+            .dao
+            .transfer_inventory(ID_0, ID_2, INVENTORY_TYPE_1.into(), INVENTORY_ID_0.into(), ID_2, None)
+            .await;
+
+        match same {
+            Err(ApiError::ValidationFailed { .. }) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        ctx.dao
+            .transfer_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into(), ID_3, None)
+            .await
+            .unwrap();
+        ctx.dao
+            .create_inventory(Inventory {
+                customer_id: ID_0,
+                vessel_id: ID_1,
+                inventory_type: INVENTORY_TYPE_0.to_string(),
+                inventory_id: INVENTORY_ID_0.to_string(),
+                serial_number: None,
+                aws_instance_id: None,
+                attributes: HashMap::new(),
+                status: InventoryStatus::Ordered,
+                status_change: None,
+                created_at: Utc::now().fixed_offset(),
+                deleted_at: None,
+                deleted_by: None,
+                transferred_at: None,
+                version: 0,
+            })
+            .await
+            .unwrap();
+
+        let conflict = ctx
+            .dao
+            .transfer_inventory(ID_0, ID_3, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into(), ID_1, None)
+            .await;
+
+        match conflict {
+            Err(ApiError::InventoryAlreadyExists(_, _)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        // failed transfer leaves the record where it was
+        let source = ctx
+            .dao
+            .get_inventory(ID_0, ID_3, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
+            .await?;
+        assert!(source.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn history_keys() {
+        assert_eq!(
+            "history:00000000-0000-0000-0000-000000000000:00000000-0000-0000-0000-000000000001:pc:012",
+            history_key_of(&ID_0, &ID_1, &INVENTORY_TYPE_0.into(), &INVENTORY_ID_0.into())
+        );
+        assert_eq!(
+            "2020-02-03T09:00:00.000000Z#00000000000000000003",
+            history_entry_key_of(&deletion().deleted_at, 3)
+        );
+    }
+
+    #[test]
+    fn inventory_keys() {
+        assert!(is_inventory_key(&hash_key_of(&ID_0, &ID_1)));
+        assert!(!is_inventory_key("awsInstanceId:i-0123"));
+        assert!(!is_inventory_key(&history_key_of(
+            &ID_0,
+            &ID_1,
+            &INVENTORY_TYPE_0.into(),
+            &INVENTORY_ID_0.into()
+        )));
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn history_created(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let created_at = Utc
+            .with_ymd_and_hms(2015, 7, 2, 1, 20, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+
+        ctx.dao
+            .create_inventory(Inventory {
+                customer_id: ID_0,
+                vessel_id: ID_3,
+                inventory_type: INVENTORY_TYPE_1.to_string(),
+                inventory_id: INVENTORY_ID_1.to_string(),
+                serial_number: Some("xyz".into()),
+                aws_instance_id: None,
+                attributes: HashMap::new(),
+                status: InventoryStatus::Ordered,
+                status_change: None,
+                created_at,
+                deleted_at: None,
+                deleted_by: None,
+                transferred_at: None,
+                version: 0,
+            })
+            .await
+            .unwrap();
+
+        let history = ctx
+            .dao
+            .list_history(ID_0, ID_3, INVENTORY_TYPE_1.into(), INVENTORY_ID_1.into(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(1, history.items.len());
+        assert!(history.last_evaluated_key.is_none());
+
+        let entry = &history.items[0];
+        assert_eq!(ChangeType::Created, entry.change_type);
+        assert!(entry.actor.is_none());
+        assert!(entry.before.is_none());
+        assert_eq!(Some("xyz".to_string()), entry.after.serial_number);
+        assert_eq!(1, entry.version);
+
+        // history is kept aside, it's not a part of vessel inventory
+        let listed = ctx
+            .dao
+            .list_inventory(ID_0, ID_3, InventoryFilter::default(), None, None, SortOrder::Asc)
+            .await
+            .unwrap();
+        assert_eq!(1, listed.items.len());
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn history_timeline(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        ACTOR
+            .scope(Some("inspector".into()), async {
+                ctx.dao
+                    .update_inventory(
+                        ID_0,
+                        ID_1,
+                        INVENTORY_TYPE_0.into(),
+                        INVENTORY_ID_0.into(),
+                        InventoryChanges {
+                            serial_number: Some(Some("abc".into())),
+                            aws_instance_id: None,
+                            attributes: None,
+                        },
+                        None,
+                    )
+                    .await
+                    .unwrap();
+                ctx.dao
+                    .transition_inventory(
+                        ID_0,
+                        ID_1,
+                        INVENTORY_TYPE_0.into(),
+                        INVENTORY_ID_0.into(),
+                        status_change(InventoryStatus::Faulty),
+                        None,
+                    )
+                    .await
+                    .unwrap();
+                ctx.dao
+                    .delete_inventory(
+                        ID_0,
+                        ID_1,
+                        INVENTORY_TYPE_0.into(),
+                        INVENTORY_ID_0.into(),
+                        deletion(),
+                        None,
+                    )
+                    .await
+                    .unwrap();
+            })
+            .await;
+
+        let history = ctx
+            .dao
+            .list_history(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(3, history.items.len());
+        assert!(history
+            .items
+            .iter()
+            .all(|entry| entry.actor == Some("inspector".to_string())));
+
+        assert_eq!(ChangeType::Updated, history.items[0].change_type);
+        assert_eq!(
+            Some("q1w2e3".to_string()),
+            history.items[0].before.as_ref().unwrap().serial_number
+        );
+        assert_eq!(Some("abc".to_string()), history.items[0].after.serial_number);
+        assert_eq!(1, history.items[0].version);
+
+        assert_eq!(ChangeType::StatusChanged, history.items[1].change_type);
+        assert_eq!(InventoryStatus::Faulty, history.items[1].after.status);
+        assert_eq!(2, history.items[1].version);
+
+        assert_eq!(ChangeType::Deleted, history.items[2].change_type);
+        assert!(history.items[2].after.deleted_at.is_some());
+        assert_eq!(3, history.items[2].version);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn list_history_page(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        for serial_number in ["abc", "def"] {
+            ctx.dao
+                .update_inventory(
+                    ID_0,
+                    ID_1,
+                    INVENTORY_TYPE_0.into(),
+                    INVENTORY_ID_0.into(),
+                    InventoryChanges {
+                        serial_number: Some(Some(serial_number.into())),
+                        aws_instance_id: None,
+                        attributes: None,
+                    },
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        let first = ctx
+            .dao
+            .list_history(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                None,
+                Some(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, first.items.len());
+        assert_eq!(Some("abc".to_string()), first.items[0].after.serial_number);
+
+        let next = ctx
+            .dao
+            .list_history(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                first.last_evaluated_key,
+                Some(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, next.items.len());
+        assert_eq!(Some("def".to_string()), next.items[0].after.serial_number);
+
+        let foreign = ctx
+            .dao
+            .list_history(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                Some(PageToken::new(
+                    hash_key_of(&ID_0, &ID_1),
+                    sort_key_of(&INVENTORY_TYPE_0.into(), &INVENTORY_ID_0.into()),
+                )),
+                None,
+            )
+            .await;
+
+        match foreign {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn list_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let unexisting = ctx
+            .dao
+            .list_inventory(ID_0, ID_1, InventoryFilter::default(), None, None, SortOrder::Asc)
+            .await;
+        assert!(unexisting.is_ok());
+
+        let results = unexisting.unwrap();
+        assert_eq!(2, results.items.len());
+        assert_eq!(INVENTORY_TYPE_0, results.items[0].inventory_type);
+        assert_eq!(INVENTORY_ID_0, results.items[0].inventory_id);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn list_inventory_page(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let unexisting = ctx
+            .dao
+            .list_inventory(
+                ID_0,
+                ID_1,
+                InventoryFilter::default(),
+                Some(PageToken::new(
+                    hash_key_of(&ID_0, &ID_1),
+                    sort_key_of(&INVENTORY_TYPE_0.into(), &INVENTORY_ID_0.into()),
+                )),
+                None,
+                SortOrder::Asc,
+            )
+            .await;
+        assert!(unexisting.is_ok());
+
+        let results = unexisting.unwrap();
+        assert_eq!(1, results.items.len());
+        assert_eq!(INVENTORY_TYPE_0, results.items[0].inventory_type);
+        assert_eq!(INVENTORY_ID_1, results.items[0].inventory_id);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn list_inventory_limit(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let results = ctx
+            .dao
+            .list_inventory(ID_0, ID_1, InventoryFilter::default(), None, Some(1), SortOrder::Asc)
+            .await
+            .unwrap();
+        assert_eq!(1, results.items.len());
+        assert_eq!(INVENTORY_ID_0, results.items[0].inventory_id);
+        assert!(results.last_evaluated_key.is_some());
+
+        let results = ctx
+            .dao
+            .list_inventory(
+                ID_0,
+                ID_1,
+                InventoryFilter::default(),
+                results.last_evaluated_key,
+                Some(1),
+                SortOrder::Asc,
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, results.items.len());
+        assert_eq!(INVENTORY_ID_1, results.items[0].inventory_id);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn list_inventory_descending(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let results = ctx
+            .dao
+            .list_inventory(ID_0, ID_1, InventoryFilter::default(), None, Some(1), SortOrder::Desc)
+            .await
+            .unwrap();
+        assert_eq!(1, results.items.len());
+        assert_eq!(INVENTORY_ID_1, results.items[0].inventory_id);
+
+        let results = ctx
+            .dao
+            .list_inventory(
+                ID_0,
+                ID_1,
+                InventoryFilter::default(),
+                results.last_evaluated_key,
+                Some(1),
+                SortOrder::Desc,
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, results.items.len());
+        assert_eq!(INVENTORY_ID_0, results.items[0].inventory_id);
+
+        Ok(())
+    }
 
-            let (res1, res2, res3) = join!(
-                context.create_record(
-                    &ID_0,
-                    &ID_1,
-                    INVENTORY_TYPE_0,
-                    INVENTORY_ID_0,
-                    Some("q1w2e3"),
-                    None,
-                    "2011-01-30T14:58:00+01:00"
-                ),
-                context.create_record(
-                    &ID_0,
-                    &ID_1,
-                    INVENTORY_TYPE_0,
-                    INVENTORY_ID_1,
-                    None,
-                    Some("im-12345"),
-                    "2015-07-02T03:20:00+02:00"
-                ),
-                context.create_record(
-                    &ID_0,
-                    &ID_2,
-                    INVENTORY_TYPE_1,
-                    INVENTORY_ID_0,
-                    Some("r@nd0m"),
-                    None,
-                    "2017-11-11T16:00:00+02:00"
-                ),
+    #[test]
+    fn page_size_bounds() {
+        assert_eq!(100, page_size(None));
+        assert_eq!(10, page_size(Some(10)));
+        assert_eq!(1, page_size(Some(0)));
+        assert_eq!(100, page_size(Some(1000)));
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn list_inventory_page_other_vessel(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let foreign = ctx
+            .dao
+            .list_inventory(
+                ID_0,
+                ID_1,
+                InventoryFilter::default(),
+                Some(PageToken::new(
+                    hash_key_of(&ID_0, &ID_2),
+                    sort_key_of(&INVENTORY_TYPE_0.into(), &INVENTORY_ID_0.into()),
+                )),
+                None,
+                SortOrder::Asc,
             )
             .await;
 
-            res1.unwrap();
-            res2.unwrap();
-            res3.unwrap();
-
-            context
+        match foreign {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
         }
 
-        async fn teardown(self) {
-            self.client
-                .delete_table()
-                .table_name(self.table_name)
-                .send()
-                .await
-                .unwrap();
-        }
+        Ok(())
     }
 
     #[test_context(DynamoDbTestContext)]
     #[tokio_test]
-    async fn create_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
-        let created_at = Utc
-            .with_ymd_and_hms(2015, 7, 2, 1, 20, 0)
-            .unwrap()
-            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
-
-        let save = ctx
+    async fn list_inventory_by_type(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let results = ctx
             .dao
-            .create_inventory(Inventory {
-                customer_id: ID_0,
-                vessel_id: ID_2,
-                inventory_type: INVENTORY_TYPE_1.to_string(),
-                inventory_id: INVENTORY_ID_1.to_string(),
-                serial_number: None,
-                aws_instance_id: None,
-                created_at,
-            })
-            .await;
-        assert!(save.is_ok());
+            .list_inventory(
+                ID_0,
+                ID_1,
+                InventoryFilter {
+                    inventory_type: Some(INVENTORY_TYPE_0.into()),
+                    ..InventoryFilter::default()
+                },
+                None,
+                None,
+                SortOrder::Asc,
+            )
+            .await
+            .unwrap();
+        assert_eq!(2, results.items.len());
+        assert_eq!(INVENTORY_ID_0, results.items[0].inventory_id);
+        assert_eq!(INVENTORY_ID_1, results.items[1].inventory_id);
 
-        let inventory = ctx
-            .client
-            .get_item()
-            .table_name(ctx.table_name.as_str())
-            .key("customerAndVesselId", S(hash_key_of(&ID_0, &ID_2)))
-            .key(
-                "inventoryKey",
-                S(sort_key_of(&INVENTORY_TYPE_1.into(), &INVENTORY_ID_1.into())),
+        let results = ctx
+            .dao
+            .list_inventory(
+                ID_0,
+                ID_1,
+                InventoryFilter {
+                    inventory_type: Some(INVENTORY_TYPE_1.into()),
+                    ..InventoryFilter::default()
+                },
+                None,
+                None,
+                SortOrder::Asc,
             )
-            .send()
-            .await?;
-        assert!(inventory.item.is_some());
-        assert_eq!(
-            "2015-07-02T03:20:00+02:00",
-            inventory.item.unwrap()["createdAt"].as_s().unwrap()
-        );
+            .await
+            .unwrap();
+        assert!(results.items.is_empty());
 
         Ok(())
     }
 
     #[test_context(DynamoDbTestContext)]
     #[tokio_test]
-    async fn get_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
-        let created_at = Utc
-            .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
-            .unwrap()
-            .with_timezone(&FixedOffset::east_opt(3600).unwrap());
+    async fn list_inventory_by_status(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        ctx.dao
+            .transition_inventory(
+                ID_0,
+                ID_1,
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                status_change(InventoryStatus::Faulty),
+                None,
+            )
+            .await
+            .unwrap();
 
-        let inventory = ctx
+        let faulty = ctx
             .dao
-            .get_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
-            .await?
+            .list_inventory(
+                ID_0,
+                ID_1,
+                InventoryFilter {
+                    status: Some(InventoryStatus::Faulty),
+                    ..InventoryFilter::default()
+                },
+                None,
+                None,
+                SortOrder::Asc,
+            )
+            .await
             .unwrap();
-        assert!(inventory.aws_instance_id.is_none());
-        assert_eq!(created_at, inventory.created_at);
+        assert_eq!(1, faulty.items.len());
+        assert_eq!(INVENTORY_ID_0, faulty.items[0].inventory_id);
+
+        // records without status are in service
+        let in_service = ctx
+            .dao
+            .list_inventory(
+                ID_0,
+                ID_1,
+                InventoryFilter {
+                    status: Some(InventoryStatus::InService),
+                    ..InventoryFilter::default()
+                },
+                None,
+                None,
+                SortOrder::Asc,
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, in_service.items.len());
+        assert_eq!(INVENTORY_ID_1, in_service.items[0].inventory_id);
 
         Ok(())
     }
 
     #[test_context(DynamoDbTestContext)]
     #[tokio_test]
-    async fn get_inventory_unexisting(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
-        let unexisting = ctx
+    async fn list_customer_inventory_by_status(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        ctx.dao
+            .transition_inventory(
+                ID_0,
+                ID_2,
+                INVENTORY_TYPE_1.into(),
+                INVENTORY_ID_0.into(),
+                status_change(InventoryStatus::Decommissioned),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let results = ctx
             .dao
-            .get_inventory(ID_0, ID_1, INVENTORY_TYPE_1.into(), INVENTORY_ID_1.into())
-            .await?;
-        assert!(unexisting.is_none());
+            .list_customer_inventory(
+                ID_0,
+                InventoryFilter {
+                    inventory_type: Some(INVENTORY_TYPE_1.into()),
+                    status: Some(InventoryStatus::Decommissioned),
+                    ..InventoryFilter::default()
+                },
+                None,
+                None,
+                SortOrder::Asc,
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, results.items.len());
+        assert_eq!(ID_2, results.items[0].vessel_id);
 
         Ok(())
     }
 
     #[test_context(DynamoDbTestContext)]
     #[tokio_test]
-    async fn delete_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
-        let result = ctx
+    async fn list_customer_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let results = ctx
             .dao
-            .delete_inventory(ID_0, ID_1, INVENTORY_TYPE_0.into(), INVENTORY_ID_0.into())
-            .await;
-        assert!(result.is_ok());
+            .list_customer_inventory(ID_0, InventoryFilter::default(), None, None, SortOrder::Asc)
+            .await
+            .unwrap();
+        assert_eq!(3, results.items.len());
+        assert_eq!(ID_1, results.items[0].vessel_id);
+        assert_eq!(INVENTORY_ID_0, results.items[0].inventory_id);
+        assert_eq!(ID_1, results.items[1].vessel_id);
+        assert_eq!(INVENTORY_ID_1, results.items[1].inventory_id);
+        assert_eq!(ID_2, results.items[2].vessel_id);
+        assert_eq!(INVENTORY_TYPE_1, results.items[2].inventory_type);
 
-        let license = ctx
-            .client
-            .get_item()
-            .table_name(ctx.table_name.as_str())
-            .key("customerAndVesselId", S(hash_key_of(&ID_0, &ID_1)))
-            .key(
-                "inventoryKey",
-                S(sort_key_of(&INVENTORY_TYPE_0.to_string(), &INVENTORY_ID_0.to_string())),
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn list_customer_inventory_page(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let results = ctx
+            .dao
+            .list_customer_inventory(
+                ID_0,
+                InventoryFilter::default(),
+                Some(PageToken::new(
+                    ID_0.to_string(),
+                    customer_sort_key_of(&ID_1, &INVENTORY_TYPE_0.into(), &INVENTORY_ID_1.into()),
+                )),
+                None,
+                SortOrder::Asc,
             )
-            .send()
-            .await?;
-        assert!(license.item.is_none());
+            .await
+            .unwrap();
+        assert_eq!(1, results.items.len());
+        assert_eq!(ID_2, results.items[0].vessel_id);
+        assert_eq!(INVENTORY_ID_0, results.items[0].inventory_id);
 
         Ok(())
     }
 
     #[test_context(DynamoDbTestContext)]
     #[tokio_test]
-    async fn delete_inventory_unexisting(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
-        let unexisting = ctx
+    async fn list_customer_inventory_page_other_customer(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let foreign = ctx
             .dao
-            .delete_inventory(ID_0, ID_1, INVENTORY_TYPE_1.into(), INVENTORY_ID_1.into())
+            .list_customer_inventory(
+                ID_0,
+                InventoryFilter::default(),
+                Some(PageToken::new(
+                    ID_3.to_string(),
+                    customer_sort_key_of(&ID_1, &INVENTORY_TYPE_0.into(), &INVENTORY_ID_1.into()),
+                )),
+                None,
+                SortOrder::Asc,
+            )
             .await;
-        assert!(unexisting.is_ok());
+
+        match foreign {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
 
         Ok(())
     }
 
     #[test_context(DynamoDbTestContext)]
     #[tokio_test]
-    async fn list_inventory(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
-        let unexisting = ctx.dao.list_inventory(ID_0, ID_1, None).await;
-        assert!(unexisting.is_ok());
+    async fn list_customer_inventory_by_type(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let results = ctx
+            .dao
+            .list_customer_inventory(
+                ID_0,
+                InventoryFilter {
+                    inventory_type: Some(INVENTORY_TYPE_1.into()),
+                    ..InventoryFilter::default()
+                },
+                None,
+                None,
+                SortOrder::Asc,
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, results.items.len());
+        assert_eq!(ID_2, results.items[0].vessel_id);
 
-        let results = unexisting.unwrap();
-        assert_eq!(2, results.items.len());
-        assert_eq!(INVENTORY_TYPE_0, results.items[0].inventory_type);
-        assert_eq!(INVENTORY_ID_0, results.items[0].inventory_id);
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn list_customer_inventory_unexisting(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        let results = ctx
+            .dao
+            .list_customer_inventory(ID_3, InventoryFilter::default(), None, None, SortOrder::Asc)
+            .await
+            .unwrap();
+        assert!(results.items.is_empty());
+        assert!(results.last_evaluated_key.is_none());
 
         Ok(())
     }
 
     #[test_context(DynamoDbTestContext)]
     #[tokio_test]
-    async fn list_inventory_page(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
-        let unexisting = ctx
+    async fn list_customer_inventory_legacy_record(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        // record written before CustomerIndex range key was introduced
+        ctx.client
+            .put_item()
+            .table_name(ctx.table_name.as_str())
+            .item("customerAndVesselId", S(hash_key_of(&ID_3, &ID_1)))
+            .item(
+                "inventoryKey",
+                S(sort_key_of(&INVENTORY_TYPE_0.to_string(), &INVENTORY_ID_0.to_string())),
+            )
+            .item("customerId", S(ID_3.to_string()))
+            .item("vesselId", S(ID_1.to_string()))
+            .item("inventoryType", S(INVENTORY_TYPE_0.into()))
+            .item("inventoryId", S(INVENTORY_ID_0.into()))
+            .item("createdAt", S("2011-01-30T14:58:00+01:00".into()))
+            .send()
+            .await
+            .unwrap();
+
+        let results = ctx
             .dao
-            .list_inventory(
-                ID_0,
+            .list_customer_inventory(ID_3, InventoryFilter::default(), None, None, SortOrder::Asc)
+            .await
+            .unwrap();
+        assert!(results.items.is_empty());
+
+        ctx.dao
+            .transition_inventory(
+                ID_3,
                 ID_1,
-                Some(sort_key_of(&INVENTORY_TYPE_0.into(), &INVENTORY_ID_0.into())),
+                INVENTORY_TYPE_0.into(),
+                INVENTORY_ID_0.into(),
+                status_change(InventoryStatus::Faulty),
+                None,
             )
-            .await;
-        assert!(unexisting.is_ok());
+            .await
+            .unwrap();
 
-        let results = unexisting.unwrap();
+        let results = ctx
+            .dao
+            .list_customer_inventory(ID_3, InventoryFilter::default(), None, None, SortOrder::Asc)
+            .await
+            .unwrap();
         assert_eq!(1, results.items.len());
-        assert_eq!(INVENTORY_TYPE_0, results.items[0].inventory_type);
-        assert_eq!(INVENTORY_ID_1, results.items[0].inventory_id);
+        assert_eq!(INVENTORY_ID_0, results.items[0].inventory_id);
+
+        Ok(())
+    }
+
+    #[test_context(DynamoDbTestContext)]
+    #[tokio_test]
+    async fn backfill_customer_index(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
+        // record written before CustomerIndex range key was introduced
+        ctx.client
+            .put_item()
+            .table_name(ctx.table_name.as_str())
+            .item("customerAndVesselId", S(hash_key_of(&ID_3, &ID_1)))
+            .item(
+                "inventoryKey",
+                S(sort_key_of(&INVENTORY_TYPE_0.to_string(), &INVENTORY_ID_0.to_string())),
+            )
+            .item("customerId", S(ID_3.to_string()))
+            .item("vesselId", S(ID_1.to_string()))
+            .item("inventoryType", S(INVENTORY_TYPE_0.into()))
+            .item("inventoryId", S(INVENTORY_ID_0.into()))
+            .item("createdAt", S("2011-01-30T14:58:00+01:00".into()))
+            .send()
+            .await
+            .unwrap();
+
+        assert!(ctx.dao.backfill_customer_index().await? > 0);
+        // already migrated records are left untouched
+        assert_eq!(0, ctx.dao.backfill_customer_index().await?);
+
+        let results = ctx
+            .dao
+            .list_customer_inventory(ID_3, InventoryFilter::default(), None, None, SortOrder::Asc)
+            .await
+            .unwrap();
+        assert_eq!(1, results.items.len());
+        assert_eq!(INVENTORY_ID_0, results.items[0].inventory_id);
 
         Ok(())
     }
@@ -446,7 +4131,10 @@ mod tests {
     #[test_context(DynamoDbTestContext)]
     #[tokio_test]
     async fn list_inventory_unexisting(ctx: &DynamoDbTestContext) -> Result<(), RuntimeError> {
-        let unexisting = ctx.dao.list_inventory(ID_1, ID_2, None).await;
+        let unexisting = ctx
+            .dao
+            .list_inventory(ID_1, ID_2, InventoryFilter::default(), None, None, SortOrder::Asc)
+            .await;
         assert!(unexisting.is_ok());
 
         let results = unexisting.unwrap();
@@ -456,6 +4144,36 @@ mod tests {
         Ok(())
     }
 
+    fn throughput() -> ProvisionedThroughput {
+        ProvisionedThroughput::builder()
+            .read_capacity_units(1000)
+            .write_capacity_units(1000)
+            .build()
+    }
+
+    fn index(index_name: &str, hash_key: &str, range_key: Option<&str>) -> GlobalSecondaryIndex {
+        let mut builder = GlobalSecondaryIndex::builder().index_name(index_name).key_schema(
+            KeySchemaElement::builder()
+                .attribute_name(hash_key)
+                .key_type(KeyType::Hash)
+                .build(),
+        );
+
+        if let Some(range_key) = range_key {
+            builder = builder.key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(range_key)
+                    .key_type(KeyType::Range)
+                    .build(),
+            );
+        }
+
+        builder
+            .projection(Projection::builder().projection_type(ProjectionType::All).build())
+            .provisioned_throughput(throughput())
+            .build()
+    }
+
     impl DynamoDbTestContext {
         async fn create_record(
             &self,
@@ -476,6 +4194,14 @@ mod tests {
                     "inventoryKey",
                     S(sort_key_of(&inventory_type.to_string(), &inventory_id.to_string())),
                 )
+                .item(
+                    "vesselAndInventoryKey",
+                    S(customer_sort_key_of(
+                        vessel_id,
+                        &inventory_type.to_string(),
+                        &inventory_id.to_string(),
+                    )),
+                )
                 .item("customerId", S(customer_id.to_string()))
                 .item("vesselId", S(vessel_id.to_string()))
                 .item("inventoryType", S(inventory_type.into()))
@@ -4,16 +4,57 @@
  * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
  */
 
+use crate::ErrorEnvelope;
 use env_logger::Builder;
-use lambda_runtime::{run, service_fn, Error, LambdaEvent};
+use lambda_runtime::{run, service_fn, Context, Error, LambdaEvent};
+use log::error;
 use serde::{Deserialize, Serialize};
-use std::fmt::{Debug, Display};
+use std::fmt::Debug;
 use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task_local;
 use tracing_core::dispatcher::set_global_default;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::Registry;
 use xray::XRaySubscriber;
 
+task_local! {
+    // caller identity of the currently handled request, recorded in the audit trail
+    pub(crate) static ACTOR: Option<String>;
+    // moment when Lambda runtime kills the currently handled invocation
+    pub(crate) static DEADLINE: SystemTime;
+}
+
+#[doc = "Caller identity - Cognito identity, or `actor` entry of the custom client context."]
+pub fn actor_of(context: &Context) -> Option<String> {
+    context
+        .identity
+        .as_ref()
+        .map(|identity| identity.identity_id.clone())
+        .or_else(|| {
+            context
+                .client_context
+                .as_ref()
+                .and_then(|client_context| client_context.custom.get("actor").cloned())
+        })
+}
+
+pub(crate) fn current_actor() -> Option<String> {
+    ACTOR.try_with(Clone::clone).ok().flatten()
+}
+
+#[doc = "Invocation deadline - `deadline` of the context is a Unix timestamp in milliseconds."]
+pub fn deadline_of(context: &Context) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(context.deadline)
+}
+
+// time left for the currently handled invocation, none outside of Lambda handler
+pub(crate) fn remaining_time() -> Option<Duration> {
+    DEADLINE
+        .try_with(|deadline| deadline.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO))
+        .ok()
+}
+
 pub async fn run_lambda<PayloadType, HandlerType, FutureType, ReturnType, ErrorType>(
     func: HandlerType,
 ) -> Result<(), Error>
@@ -22,13 +63,28 @@ where
     HandlerType: Fn(LambdaEvent<PayloadType>) -> FutureType,
     FutureType: Future<Output = Result<ReturnType, ErrorType>>,
     ReturnType: Serialize,
-    ErrorType: Debug + Display,
+    ErrorType: Debug,
+    ErrorEnvelope: From<ErrorType>,
 {
     Builder::from_default_env().format_timestamp(None).init();
 
     set_global_default(Registry::default().with(XRaySubscriber::default()).into())?;
 
-    run(service_fn(func)).await
+    run(service_fn(move |event: LambdaEvent<PayloadType>| {
+        let request_id = event.context.request_id.clone();
+        let actor = actor_of(&event.context);
+        let deadline = deadline_of(&event.context);
+        let response = ACTOR.scope(actor, DEADLINE.scope(deadline, func(event)));
+
+        async move {
+            response.await.map_err(|failure| {
+                // full failure details stay in the logs, client only gets the envelope
+                error!("Request {request_id} failed: {failure:?}");
+                ErrorEnvelope::from(failure).with_request_id(request_id)
+            })
+        }
+    }))
+    .await
 }
 
 #[macro_export]
@@ -7,13 +7,39 @@
 #![feature(future_join)]
 
 mod api_error;
+mod error_envelope;
+mod event_sink;
+mod events;
+mod history;
 mod inventory_dao;
 mod lambda;
+mod lifecycle;
 mod model;
+mod page_token;
+mod repository;
 mod runtime_error;
+mod type_registry;
+mod validation;
 
 pub use crate::api_error::ApiError;
+pub use crate::error_envelope::{ErrorCode, ErrorEnvelope};
+pub use crate::event_sink::{load_event_sink_from_env, EventBridgeSink, EventSink, MemorySink, SnsSink};
+pub use crate::events::{
+    InventoryEvent, InventoryEventType, StreamChange, StreamEventName, StreamRecord, EVENT_SCHEMA_VERSION,
+};
+pub use crate::history::{ChangeType, HistoryEntry};
 pub use crate::inventory_dao::InventoryDao;
-pub use crate::lambda::run_lambda;
-pub use crate::model::{DynamoResultsPage, Inventory};
-pub use crate::runtime_error::RuntimeError;
+pub use crate::lambda::{actor_of, deadline_of, run_lambda};
+pub use crate::lifecycle::{InventoryStatus, StatusChange};
+pub use crate::model::{
+    Deletion, DynamoResultsPage, Inventory, InventoryAttribute, InventoryChanges, InventoryFilter, SortOrder,
+};
+pub use crate::page_token::{PageToken, PageTokenCodec};
+pub use crate::repository::{FailingRepository, InventoryRepository, MemoryRepository};
+pub use crate::runtime_error::{ErrorKind, RuntimeError};
+pub use crate::type_registry::{
+    AttributeDefinition, AttributeType, InventoryField, InventoryTypeDefinition, TypeRegistry,
+};
+pub use crate::validation::{
+    validate_attributes, validate_identifier, validate_inventory_key, validate_optional_value, validate_value,
+};
@@ -0,0 +1,122 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+#[doc = "Lifecycle status of the device."]
+pub enum InventoryStatus {
+    Ordered,
+    Installed,
+    // records created before statuses were introduced describe devices that are already in use
+    #[default]
+    InService,
+    Faulty,
+    UnderRepair,
+    Decommissioned,
+}
+
+impl InventoryStatus {
+    #[doc = "Statuses that can directly follow the current one."]
+    pub fn next_statuses(&self) -> &'static [InventoryStatus] {
+        match self {
+            InventoryStatus::Ordered => &[InventoryStatus::Installed, InventoryStatus::Decommissioned],
+            InventoryStatus::Installed => &[
+                InventoryStatus::InService,
+                InventoryStatus::Faulty,
+                InventoryStatus::Decommissioned,
+            ],
+            InventoryStatus::InService => &[InventoryStatus::Faulty, InventoryStatus::Decommissioned],
+            InventoryStatus::Faulty => &[InventoryStatus::UnderRepair, InventoryStatus::Decommissioned],
+            InventoryStatus::UnderRepair => &[
+                InventoryStatus::InService,
+                InventoryStatus::Faulty,
+                InventoryStatus::Decommissioned,
+            ],
+            InventoryStatus::Decommissioned => &[],
+        }
+    }
+
+    pub fn can_transition_to(&self, status: InventoryStatus) -> bool {
+        self.next_statuses().contains(&status)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InventoryStatus::Ordered => "ordered",
+            InventoryStatus::Installed => "installed",
+            InventoryStatus::InService => "inService",
+            InventoryStatus::Faulty => "faulty",
+            InventoryStatus::UnderRepair => "underRepair",
+            InventoryStatus::Decommissioned => "decommissioned",
+        }
+    }
+}
+
+impl Display for InventoryStatus {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        formatter.write_str(self.name())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[doc = "Details of the status change."]
+pub struct StatusChange {
+    #[doc = "Status set by the change."]
+    pub status: InventoryStatus,
+    #[doc = "Date of the change."]
+    pub changed_at: DateTime<FixedOffset>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[doc = "Identity of the caller who made the change."]
+    pub changed_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[doc = "Reason of the change."]
+    pub reason: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lifecycle::InventoryStatus;
+
+    #[test]
+    fn forward_transitions() {
+        assert!(InventoryStatus::Ordered.can_transition_to(InventoryStatus::Installed));
+        assert!(InventoryStatus::Installed.can_transition_to(InventoryStatus::InService));
+        assert!(InventoryStatus::InService.can_transition_to(InventoryStatus::Faulty));
+        assert!(InventoryStatus::Faulty.can_transition_to(InventoryStatus::UnderRepair));
+        assert!(InventoryStatus::UnderRepair.can_transition_to(InventoryStatus::InService));
+        assert!(InventoryStatus::UnderRepair.can_transition_to(InventoryStatus::Decommissioned));
+    }
+
+    #[test]
+    fn forbidden_transitions() {
+        assert!(!InventoryStatus::Ordered.can_transition_to(InventoryStatus::InService));
+        assert!(!InventoryStatus::InService.can_transition_to(InventoryStatus::Installed));
+        assert!(!InventoryStatus::Faulty.can_transition_to(InventoryStatus::InService));
+        assert!(!InventoryStatus::InService.can_transition_to(InventoryStatus::InService));
+    }
+
+    #[test]
+    fn decommissioned_is_final() {
+        assert!(InventoryStatus::Decommissioned.next_statuses().is_empty());
+    }
+
+    #[test]
+    fn serialized_names() {
+        assert_eq!(
+            "\"underRepair\"",
+            serde_json::to_string(&InventoryStatus::UnderRepair).unwrap()
+        );
+        assert_eq!(
+            InventoryStatus::UnderRepair.name(),
+            InventoryStatus::UnderRepair.to_string()
+        );
+    }
+}
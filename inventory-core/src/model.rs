@@ -4,11 +4,23 @@
  * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
  */
 
+use crate::lifecycle::{InventoryStatus, StatusChange};
 use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+#[doc = "Typed value of free-form inventory attribute."]
+pub enum InventoryAttribute {
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Date(DateTime<FixedOffset>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 #[doc = "License entity."]
 pub struct Inventory {
@@ -20,12 +32,74 @@ pub struct Inventory {
     pub inventory_type: String,
     #[doc = "Inventory ID (within given type)."]
     pub inventory_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[doc = "Serial number."]
     pub serial_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[doc = "AWS Systems Manager identifier."]
     pub aws_instance_id: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[doc = "Free-form attributes (manufacturer, firmware version etc.)."]
+    pub attributes: HashMap<String, InventoryAttribute>,
+    #[serde(default)]
+    #[doc = "Lifecycle status."]
+    pub status: InventoryStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[doc = "Last status change."]
+    pub status_change: Option<StatusChange>,
     #[doc = "Date when inventory was added."]
     pub created_at: DateTime<FixedOffset>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[doc = "Date when inventory was deleted - deleted inventory is kept only for the retention period."]
+    pub deleted_at: Option<DateTime<FixedOffset>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[doc = "Identity of the caller who deleted the inventory."]
+    pub deleted_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[doc = "Date when inventory was transferred to its current vessel."]
+    pub transferred_at: Option<DateTime<FixedOffset>>,
+    #[serde(default)]
+    #[doc = "Record version, increased with every modification."]
+    pub version: u64,
+}
+
+#[derive(Default)]
+#[doc = "Partial inventory modification - `None` keeps current value, `Some(None)` clears optional field."]
+pub struct InventoryChanges {
+    #[doc = "Serial number."]
+    pub serial_number: Option<Option<String>>,
+    #[doc = "AWS Systems Manager identifier."]
+    pub aws_instance_id: Option<Option<String>>,
+    #[doc = "Free-form attributes - replace all of the current ones."]
+    pub attributes: Option<HashMap<String, InventoryAttribute>>,
+}
+
+#[derive(Default)]
+#[doc = "Listing criteria - `None` matches everything."]
+pub struct InventoryFilter {
+    #[doc = "Inventory type."]
+    pub inventory_type: Option<String>,
+    #[doc = "Lifecycle status."]
+    pub status: Option<InventoryStatus>,
+    #[doc = "Whether soft-deleted inventory should be listed as well."]
+    pub include_deleted: bool,
+}
+
+#[doc = "Soft deletion details."]
+pub struct Deletion {
+    #[doc = "Date of deletion."]
+    pub deleted_at: DateTime<FixedOffset>,
+    #[doc = "Identity of the caller who deleted the inventory."]
+    pub deleted_by: Option<String>,
+}
+
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+#[doc = "Listing direction."]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
 }
 
 pub struct DynamoResultsPage<T, K> {
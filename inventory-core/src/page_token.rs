@@ -0,0 +1,192 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+use crate::api_error::ApiError;
+use crate::runtime_error::RuntimeError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::env::var;
+
+type Signature = Hmac<Sha256>;
+
+// neither partition, nor key contain the separator - it's forbidden in identifiers
+static SEPARATOR: char = '|';
+static SIGNATURE_LENGTH: usize = 32;
+// HMAC-SHA256 key shorter than the hash output weakens the signature
+static MIN_SECRET_LENGTH: usize = 32;
+
+#[derive(Debug, PartialEq)]
+#[doc = "Position within listing, bound to the partition it was issued for."]
+pub struct PageToken {
+    pub(crate) partition: String,
+    pub(crate) key: String,
+}
+
+impl PageToken {
+    pub(crate) fn new(partition: String, key: String) -> Self {
+        Self { partition, key }
+    }
+}
+
+#[doc = "Converts page tokens into opaque, tamper-proof strings exposed to clients. Tokens are signed together with the \
+listing scope (query criteria) they were issued for, so they can't be replayed with different criteria."]
+pub struct PageTokenCodec {
+    secret: Vec<u8>,
+}
+
+/**
+Required environment variables:
+<dl>
+    <dt><code>PAGE_TOKEN_SECRET</code></dt>
+    <dd>Key used for signing page tokens, at least 32 bytes long.</dd>
+</dl>
+ */
+impl PageTokenCodec {
+    pub fn load_from_env() -> Result<Self, RuntimeError> {
+        var("PAGE_TOKEN_SECRET")
+            .map_err(RuntimeError::ClientConfigLoadingError)
+            .and_then(|secret| Self::with_secret(secret.into_bytes()))
+    }
+
+    fn with_secret(secret: Vec<u8>) -> Result<Self, RuntimeError> {
+        if secret.len() < MIN_SECRET_LENGTH {
+            Err(RuntimeError::ConfigurationValueError("PAGE_TOKEN_SECRET".into()))
+        } else {
+            Ok(Self::new(secret))
+        }
+    }
+
+    pub fn new(secret: Vec<u8>) -> Self {
+        Self { secret }
+    }
+
+    pub fn encode(&self, scope: &str, token: &PageToken) -> String {
+        let mut data = format!("{}{SEPARATOR}{}", token.partition, token.key).into_bytes();
+        let signature = self.signature(scope, &data).finalize().into_bytes();
+        data.extend_from_slice(&signature);
+
+        URL_SAFE_NO_PAD.encode(data)
+    }
+
+    pub fn decode(&self, scope: &str, token: &str) -> Result<PageToken, ApiError> {
+        let data = URL_SAFE_NO_PAD.decode(token).map_err(|_| ApiError::InvalidPageToken)?;
+
+        if data.len() < SIGNATURE_LENGTH {
+            return Err(ApiError::InvalidPageToken);
+        }
+
+        let (payload, signature) = data.split_at(data.len() - SIGNATURE_LENGTH);
+        self.signature(scope, payload)
+            .verify_slice(signature)
+            .map_err(|_| ApiError::InvalidPageToken)?;
+
+        String::from_utf8(payload.to_vec())
+            .ok()
+            .and_then(|payload| {
+                payload
+                    .split_once(SEPARATOR)
+                    .map(|(partition, key)| PageToken::new(partition.into(), key.into()))
+            })
+            .ok_or(ApiError::InvalidPageToken)
+    }
+
+    fn signature(&self, scope: &str, payload: &[u8]) -> Signature {
+        // HMAC accepts keys of any length
+        let mut signature = Signature::new_from_slice(&self.secret).unwrap();
+        // scope is not part of the token itself - it's only signed, so token verifies only within the same scope
+        signature.update(format!("{scope}{SEPARATOR}").as_bytes());
+        signature.update(payload);
+        signature
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::page_token::{PageToken, PageTokenCodec};
+    use crate::{ApiError, RuntimeError};
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+
+    static PARTITION: &str = "00000000-0000-0000-0000-000000000000:00000000-0000-0000-0000-000000000001";
+    static KEY: &str = "pc:012";
+    static SCOPE: &str = "vessel";
+
+    #[test]
+    fn encode_decode() {
+        let codec = PageTokenCodec::new(b"test".to_vec());
+        let token = codec.encode(SCOPE, &PageToken::new(PARTITION.into(), KEY.into()));
+
+        assert!(!token.contains(KEY));
+        assert_eq!(
+            PageToken::new(PARTITION.into(), KEY.into()),
+            codec.decode(SCOPE, &token).unwrap()
+        );
+    }
+
+    #[test]
+    fn decode_other_secret() {
+        let token = PageTokenCodec::new(b"test".to_vec()).encode(SCOPE, &PageToken::new(PARTITION.into(), KEY.into()));
+
+        match PageTokenCodec::new(b"other".to_vec()).decode(SCOPE, &token) {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[test]
+    fn decode_other_scope() {
+        let codec = PageTokenCodec::new(b"test".to_vec());
+        let token = codec.encode(SCOPE, &PageToken::new(PARTITION.into(), KEY.into()));
+
+        match codec.decode("customer", &token) {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[test]
+    fn decode_tampered() {
+        let codec = PageTokenCodec::new(b"test".to_vec());
+        let mut data = URL_SAFE_NO_PAD
+            .decode(codec.encode(SCOPE, &PageToken::new(PARTITION.into(), KEY.into())))
+            .unwrap();
+        data[0] ^= 1;
+
+        match codec.decode(SCOPE, &URL_SAFE_NO_PAD.encode(data)) {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[test]
+    fn decode_malformed() {
+        let codec = PageTokenCodec::new(b"test".to_vec());
+
+        match codec.decode(SCOPE, "pc:012") {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
+        match codec.decode(SCOPE, "cGM6MDEy") {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[test]
+    fn short_secret() {
+        match PageTokenCodec::with_secret(vec![]) {
+            Err(RuntimeError::ConfigurationValueError(setting)) => assert_eq!("PAGE_TOKEN_SECRET", setting),
+            _ => panic!("Invalid error type."),
+        }
+        match PageTokenCodec::with_secret(b"test".to_vec()) {
+            Err(RuntimeError::ConfigurationValueError(setting)) => assert_eq!("PAGE_TOKEN_SECRET", setting),
+            _ => panic!("Invalid error type."),
+        }
+        assert!(PageTokenCodec::with_secret(vec![b'x'; 32]).is_ok());
+    }
+}
@@ -0,0 +1,1717 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+use crate::api_error::ApiError;
+use crate::history::{ChangeType, HistoryEntry};
+use crate::inventory_dao::{
+    customer_sort_key_of, hash_key_of, history_entry_key_of, history_key_of, page_size, sort_key_of, InventoryDao,
+};
+use crate::lambda::current_actor;
+use crate::lifecycle::StatusChange;
+use crate::model::{Deletion, DynamoResultsPage, Inventory, InventoryChanges, InventoryFilter, SortOrder};
+use crate::page_token::PageToken;
+use crate::runtime_error::RuntimeError;
+use async_trait::async_trait;
+use aws_sdk_dynamodb::operation::query::QueryError;
+use aws_smithy_http::body::SdkBody;
+use aws_smithy_http::operation::Response;
+use aws_smithy_http::result::SdkError;
+use aws_smithy_types::error::ErrorMetadata;
+use chrono::Utc;
+use http::Response as HttpResponse;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use uuid::Uuid;
+
+#[async_trait]
+#[doc = "Storage of inventory records."]
+pub trait InventoryRepository: Send + Sync {
+    async fn create_inventory(&self, inventory: Inventory) -> Result<(), ApiError>;
+
+    async fn batch_create(&self, inventories: Vec<Inventory>) -> Result<Vec<Result<(), ApiError>>, ApiError>;
+
+    async fn upsert_inventory(&self, inventory: Inventory) -> Result<(), ApiError>;
+
+    async fn get_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+    ) -> Result<Option<Inventory>, RuntimeError>;
+
+    async fn batch_get(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        keys: Vec<(String, String)>,
+    ) -> Result<Vec<Inventory>, RuntimeError>;
+
+    async fn find_by_serial_number(
+        &self,
+        customer_id: Uuid,
+        serial_number: String,
+    ) -> Result<Vec<Inventory>, RuntimeError>;
+
+    async fn get_by_aws_instance_id(&self, aws_instance_id: String) -> Result<Option<Inventory>, RuntimeError>;
+
+    async fn list_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        filter: InventoryFilter,
+        page_token: Option<PageToken>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> Result<DynamoResultsPage<Inventory, PageToken>, ApiError>;
+
+    async fn list_customer_inventory(
+        &self,
+        customer_id: Uuid,
+        filter: InventoryFilter,
+        page_token: Option<PageToken>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> Result<DynamoResultsPage<Inventory, PageToken>, ApiError>;
+
+    async fn list_history(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        page_token: Option<PageToken>,
+        limit: Option<u32>,
+    ) -> Result<DynamoResultsPage<HistoryEntry, PageToken>, ApiError>;
+
+    async fn update_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        changes: InventoryChanges,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError>;
+
+    async fn delete_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        deletion: Deletion,
+        expected_version: Option<u64>,
+    ) -> Result<Option<Inventory>, ApiError>;
+
+    async fn restore_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError>;
+
+    async fn transition_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        change: StatusChange,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError>;
+
+    async fn transfer_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        target_vessel_id: Uuid,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError>;
+}
+
+#[async_trait]
+impl InventoryRepository for InventoryDao {
+    async fn create_inventory(&self, inventory: Inventory) -> Result<(), ApiError> {
+        InventoryDao::create_inventory(self, inventory).await
+    }
+
+    async fn batch_create(&self, inventories: Vec<Inventory>) -> Result<Vec<Result<(), ApiError>>, ApiError> {
+        InventoryDao::batch_create(self, inventories).await
+    }
+
+    async fn upsert_inventory(&self, inventory: Inventory) -> Result<(), ApiError> {
+        InventoryDao::upsert_inventory(self, inventory).await
+    }
+
+    async fn get_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+    ) -> Result<Option<Inventory>, RuntimeError> {
+        InventoryDao::get_inventory(self, customer_id, vessel_id, inventory_type, inventory_id).await
+    }
+
+    async fn batch_get(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        keys: Vec<(String, String)>,
+    ) -> Result<Vec<Inventory>, RuntimeError> {
+        InventoryDao::batch_get(self, customer_id, vessel_id, keys).await
+    }
+
+    async fn find_by_serial_number(
+        &self,
+        customer_id: Uuid,
+        serial_number: String,
+    ) -> Result<Vec<Inventory>, RuntimeError> {
+        InventoryDao::find_by_serial_number(self, customer_id, serial_number).await
+    }
+
+    async fn get_by_aws_instance_id(&self, aws_instance_id: String) -> Result<Option<Inventory>, RuntimeError> {
+        InventoryDao::get_by_aws_instance_id(self, aws_instance_id).await
+    }
+
+    async fn list_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        filter: InventoryFilter,
+        page_token: Option<PageToken>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> Result<DynamoResultsPage<Inventory, PageToken>, ApiError> {
+        InventoryDao::list_inventory(self, customer_id, vessel_id, filter, page_token, limit, order).await
+    }
+
+    async fn list_customer_inventory(
+        &self,
+        customer_id: Uuid,
+        filter: InventoryFilter,
+        page_token: Option<PageToken>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> Result<DynamoResultsPage<Inventory, PageToken>, ApiError> {
+        InventoryDao::list_customer_inventory(self, customer_id, filter, page_token, limit, order).await
+    }
+
+    async fn list_history(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        page_token: Option<PageToken>,
+        limit: Option<u32>,
+    ) -> Result<DynamoResultsPage<HistoryEntry, PageToken>, ApiError> {
+        InventoryDao::list_history(
+            self,
+            customer_id,
+            vessel_id,
+            inventory_type,
+            inventory_id,
+            page_token,
+            limit,
+        )
+        .await
+    }
+
+    async fn update_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        changes: InventoryChanges,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        InventoryDao::update_inventory(
+            self,
+            customer_id,
+            vessel_id,
+            inventory_type,
+            inventory_id,
+            changes,
+            expected_version,
+        )
+        .await
+    }
+
+    async fn delete_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        deletion: Deletion,
+        expected_version: Option<u64>,
+    ) -> Result<Option<Inventory>, ApiError> {
+        InventoryDao::delete_inventory(
+            self,
+            customer_id,
+            vessel_id,
+            inventory_type,
+            inventory_id,
+            deletion,
+            expected_version,
+        )
+        .await
+    }
+
+    async fn restore_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        InventoryDao::restore_inventory(
+            self,
+            customer_id,
+            vessel_id,
+            inventory_type,
+            inventory_id,
+            expected_version,
+        )
+        .await
+    }
+
+    async fn transition_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        change: StatusChange,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        InventoryDao::transition_inventory(
+            self,
+            customer_id,
+            vessel_id,
+            inventory_type,
+            inventory_id,
+            change,
+            expected_version,
+        )
+        .await
+    }
+
+    async fn transfer_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        target_vessel_id: Uuid,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        InventoryDao::transfer_inventory(
+            self,
+            customer_id,
+            vessel_id,
+            inventory_type,
+            inventory_id,
+            target_vessel_id,
+            expected_version,
+        )
+        .await
+    }
+}
+
+type RecordKey = (String, String);
+
+fn record_key_of(inventory: &Inventory) -> RecordKey {
+    (
+        hash_key_of(&inventory.customer_id, &inventory.vessel_id),
+        sort_key_of(&inventory.inventory_type, &inventory.inventory_id),
+    )
+}
+
+fn matches_filter(filter: &InventoryFilter, inventory: &Inventory) -> bool {
+    (filter.status.is_none() || filter.status == Some(inventory.status))
+        && (filter.include_deleted || inventory.deleted_at.is_none())
+}
+
+// mimics DynamoDB query - limit applies to evaluated records and filter only afterwards, so pages may be shorter
+fn page_of<T>(
+    mut records: Vec<(String, T)>,
+    start_key: Option<String>,
+    limit: Option<u32>,
+    order: SortOrder,
+    filter: impl Fn(&T) -> bool,
+) -> (Vec<T>, Option<String>) {
+    records.sort_by(|(left, _), (right, _)| left.cmp(right));
+    if order == SortOrder::Desc {
+        records.reverse();
+    }
+
+    let size = page_size(limit) as usize;
+    let evaluated: Vec<(String, T)> = records
+        .into_iter()
+        .filter(|(key, _)| match &start_key {
+            Some(start_key) if order == SortOrder::Asc => key > start_key,
+            Some(start_key) => key < start_key,
+            None => true,
+        })
+        .take(size)
+        .collect();
+
+    // full page does not tell whether anything is left, just like DynamoDB
+    let last_evaluated_key = if evaluated.len() == size {
+        evaluated.last().map(|(key, _)| key.clone())
+    } else {
+        None
+    };
+
+    (
+        evaluated
+            .into_iter()
+            .map(|(_, record)| record)
+            .filter(|record| filter(record))
+            .collect(),
+        last_evaluated_key,
+    )
+}
+
+#[derive(Default)]
+#[doc = "Keeps inventory in memory with DynamoDB paging and ordering semantics - meant for tests and local runs."]
+pub struct MemoryRepository {
+    records: Mutex<HashMap<RecordKey, Inventory>>,
+    history: Mutex<HashMap<String, Vec<HistoryEntry>>>,
+}
+
+impl From<Vec<Inventory>> for MemoryRepository {
+    fn from(inventory: Vec<Inventory>) -> Self {
+        Self {
+            records: Mutex::new(
+                inventory
+                    .into_iter()
+                    .map(|inventory| (record_key_of(&inventory), inventory))
+                    .collect(),
+            ),
+            history: Mutex::default(),
+        }
+    }
+}
+
+impl MemoryRepository {
+    pub fn inventory(&self) -> Vec<Inventory> {
+        self.records().values().cloned().collect()
+    }
+
+    // every operation leaves records consistent, so state of a panicked thread is still usable
+    fn records(&self) -> MutexGuard<'_, HashMap<RecordKey, Inventory>> {
+        self.records.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn history(&self) -> MutexGuard<'_, HashMap<String, Vec<HistoryEntry>>> {
+        self.history.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // audit trail follows every change, just like the DynamoDB transactions do
+    fn record_history(&self, change_type: ChangeType, before: Option<Inventory>, after: &Inventory) {
+        let entry = HistoryEntry {
+            changed_at: Utc::now().fixed_offset(),
+            change_type,
+            actor: current_actor(),
+            version: after.version,
+            before,
+            after: after.clone(),
+        };
+        let mut history = self.history();
+
+        // transfer entry closes the trail on the source vessel and opens it on the target one
+        if let Some(before) = entry
+            .before
+            .as_ref()
+            .filter(|before| before.vessel_id != after.vessel_id)
+        {
+            history
+                .entry(history_key_of(
+                    &before.customer_id,
+                    &before.vessel_id,
+                    &before.inventory_type,
+                    &before.inventory_id,
+                ))
+                .or_default()
+                .push(entry.clone());
+        }
+
+        history
+            .entry(history_key_of(
+                &after.customer_id,
+                &after.vessel_id,
+                &after.inventory_type,
+                &after.inventory_id,
+            ))
+            .or_default()
+            .push(entry);
+    }
+
+    fn existing(
+        records: &HashMap<RecordKey, Inventory>,
+        key: &RecordKey,
+        inventory_type: String,
+        inventory_id: String,
+    ) -> Result<Inventory, ApiError> {
+        records
+            .get(key)
+            .filter(|current| current.deleted_at.is_none())
+            .cloned()
+            .ok_or(ApiError::InventoryNotFound(inventory_type, inventory_id))
+    }
+}
+
+// soft-deleted records release their AWS instance ID reservation
+fn check_aws_instance_id(
+    records: &HashMap<RecordKey, Inventory>,
+    key: &RecordKey,
+    aws_instance_id: Option<&String>,
+) -> Result<(), ApiError> {
+    match aws_instance_id {
+        Some(aws_instance_id)
+            if records.iter().any(|(other, inventory)| {
+                other != key
+                    && inventory.deleted_at.is_none()
+                    && inventory.aws_instance_id.as_ref() == Some(aws_instance_id)
+            }) =>
+        {
+            Err(ApiError::AwsInstanceIdAlreadyAssigned(aws_instance_id.clone()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[async_trait]
+impl InventoryRepository for MemoryRepository {
+    async fn create_inventory(&self, inventory: Inventory) -> Result<(), ApiError> {
+        let key = record_key_of(&inventory);
+        let mut records = self.records();
+
+        if records.contains_key(&key) {
+            return Err(ApiError::InventoryAlreadyExists(
+                inventory.inventory_type,
+                inventory.inventory_id,
+            ));
+        }
+        check_aws_instance_id(&records, &key, inventory.aws_instance_id.as_ref())?;
+
+        let created = Inventory {
+            version: 1,
+            ..inventory
+        };
+        records.insert(key, created.clone());
+        self.record_history(ChangeType::Created, None, &created);
+        Ok(())
+    }
+
+    async fn batch_create(&self, inventories: Vec<Inventory>) -> Result<Vec<Result<(), ApiError>>, ApiError> {
+        let mut results = vec![];
+        // duplicates within the batch are rejected the same way as already existing records
+        for inventory in inventories {
+            results.push(self.create_inventory(inventory).await);
+        }
+        Ok(results)
+    }
+
+    async fn upsert_inventory(&self, inventory: Inventory) -> Result<(), ApiError> {
+        let key = record_key_of(&inventory);
+        let mut records = self.records();
+
+        check_aws_instance_id(&records, &key, inventory.aws_instance_id.as_ref())?;
+
+        // status can only be changed by transition
+        let current = records.get(&key).cloned();
+        let after = match &current {
+            Some(current) => Inventory {
+                status: current.status,
+                status_change: current.status_change.clone(),
+                version: current.version + 1,
+                ..inventory
+            },
+            None => Inventory {
+                version: 1,
+                ..inventory
+            },
+        };
+        records.insert(key, after.clone());
+        let change_type = if current.is_some() {
+            ChangeType::Updated
+        } else {
+            ChangeType::Created
+        };
+        self.record_history(change_type, current, &after);
+        Ok(())
+    }
+
+    async fn get_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+    ) -> Result<Option<Inventory>, RuntimeError> {
+        Ok(self
+            .records()
+            .get(&(
+                hash_key_of(&customer_id, &vessel_id),
+                sort_key_of(&inventory_type, &inventory_id),
+            ))
+            .cloned())
+    }
+
+    async fn batch_get(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        keys: Vec<(String, String)>,
+    ) -> Result<Vec<Inventory>, RuntimeError> {
+        let hash_key = hash_key_of(&customer_id, &vessel_id);
+        let records = self.records();
+        let mut sort_keys = vec![];
+        for (inventory_type, inventory_id) in &keys {
+            let sort_key = sort_key_of(inventory_type, inventory_id);
+            if !sort_keys.contains(&sort_key) {
+                sort_keys.push(sort_key);
+            }
+        }
+
+        Ok(sort_keys
+            .into_iter()
+            .filter_map(|sort_key| records.get(&(hash_key.clone(), sort_key)).cloned())
+            .collect())
+    }
+
+    async fn find_by_serial_number(
+        &self,
+        customer_id: Uuid,
+        serial_number: String,
+    ) -> Result<Vec<Inventory>, RuntimeError> {
+        let mut found: Vec<(RecordKey, Inventory)> = self
+            .records()
+            .iter()
+            .filter(|(_, inventory)| {
+                inventory.customer_id == customer_id
+                    && inventory.deleted_at.is_none()
+                    && inventory.serial_number.as_ref() == Some(&serial_number)
+            })
+            .map(|(key, inventory)| (key.clone(), inventory.clone()))
+            .collect();
+        found.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+        Ok(found.into_iter().map(|(_, inventory)| inventory).collect())
+    }
+
+    async fn get_by_aws_instance_id(&self, aws_instance_id: String) -> Result<Option<Inventory>, RuntimeError> {
+        // soft-deleted records keep the ID, but only one live record can hold it
+        Ok(self
+            .records()
+            .values()
+            .find(|inventory| {
+                inventory.deleted_at.is_none() && inventory.aws_instance_id.as_ref() == Some(&aws_instance_id)
+            })
+            .cloned())
+    }
+
+    async fn list_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        filter: InventoryFilter,
+        page_token: Option<PageToken>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> Result<DynamoResultsPage<Inventory, PageToken>, ApiError> {
+        let hash_key = hash_key_of(&customer_id, &vessel_id);
+        let start_key = page_token
+            .map(|page_token| {
+                if page_token.partition == hash_key {
+                    Ok(page_token.key)
+                } else {
+                    Err(ApiError::InvalidPageToken)
+                }
+            })
+            .transpose()?;
+
+        // type is a key prefix, so it narrows evaluated records instead of filtering them
+        let prefix = filter
+            .inventory_type
+            .as_ref()
+            .map(|inventory_type| format!("{inventory_type}:"))
+            .unwrap_or_default();
+        let records = self
+            .records()
+            .iter()
+            .filter(|((partition, key), _)| *partition == hash_key && key.starts_with(prefix.as_str()))
+            .map(|((_, key), inventory)| (key.clone(), inventory.clone()))
+            .collect();
+
+        let (items, last_evaluated_key) = page_of(records, start_key, limit, order, |inventory| {
+            matches_filter(&filter, inventory)
+        });
+
+        Ok(DynamoResultsPage {
+            items,
+            last_evaluated_key: last_evaluated_key.map(|key| PageToken::new(hash_key, key)),
+        })
+    }
+
+    async fn list_customer_inventory(
+        &self,
+        customer_id: Uuid,
+        filter: InventoryFilter,
+        page_token: Option<PageToken>,
+        limit: Option<u32>,
+        order: SortOrder,
+    ) -> Result<DynamoResultsPage<Inventory, PageToken>, ApiError> {
+        let partition = customer_id.to_string();
+        let start_key = page_token
+            .map(|page_token| {
+                if page_token.partition == partition && page_token.key.contains('#') {
+                    Ok(page_token.key)
+                } else {
+                    Err(ApiError::InvalidPageToken)
+                }
+            })
+            .transpose()?;
+
+        let records = self
+            .records()
+            .values()
+            .filter(|inventory| inventory.customer_id == customer_id)
+            .map(|inventory| {
+                (
+                    customer_sort_key_of(&inventory.vessel_id, &inventory.inventory_type, &inventory.inventory_id),
+                    inventory.clone(),
+                )
+            })
+            .collect();
+
+        let (items, last_evaluated_key) = page_of(records, start_key, limit, order, |inventory| {
+            // across vessels type is not a key prefix anymore
+            (filter.inventory_type.is_none() || filter.inventory_type.as_ref() == Some(&inventory.inventory_type))
+                && matches_filter(&filter, inventory)
+        });
+
+        Ok(DynamoResultsPage {
+            items,
+            last_evaluated_key: last_evaluated_key.map(|key| PageToken::new(partition, key)),
+        })
+    }
+
+    async fn list_history(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        page_token: Option<PageToken>,
+        limit: Option<u32>,
+    ) -> Result<DynamoResultsPage<HistoryEntry, PageToken>, ApiError> {
+        let hash_key = history_key_of(&customer_id, &vessel_id, &inventory_type, &inventory_id);
+        let start_key = page_token
+            .map(|page_token| {
+                if page_token.partition == hash_key {
+                    Ok(page_token.key)
+                } else {
+                    Err(ApiError::InvalidPageToken)
+                }
+            })
+            .transpose()?;
+
+        let entries = self
+            .history()
+            .get(&hash_key)
+            .into_iter()
+            .flatten()
+            .map(|entry| (history_entry_key_of(&entry.changed_at, entry.version), entry.clone()))
+            .collect();
+
+        let (items, last_evaluated_key) = page_of(entries, start_key, limit, SortOrder::Asc, |_| true);
+
+        Ok(DynamoResultsPage {
+            items,
+            last_evaluated_key: last_evaluated_key.map(|key| PageToken::new(hash_key, key)),
+        })
+    }
+
+    async fn update_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        changes: InventoryChanges,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        let mut records = self.records();
+        let key = (
+            hash_key_of(&customer_id, &vessel_id),
+            sort_key_of(&inventory_type, &inventory_id),
+        );
+        let current = Self::existing(&records, &key, inventory_type, inventory_id)?;
+
+        if expected_version.is_some_and(|version| version != current.version) {
+            return Err(ApiError::VersionConflict(current.version));
+        }
+
+        // nothing to change, but caller still expects current state
+        if changes.serial_number.is_none() && changes.aws_instance_id.is_none() && changes.attributes.is_none() {
+            return Ok(current);
+        }
+
+        let aws_instance_id = changes.aws_instance_id.unwrap_or(current.aws_instance_id.clone());
+        check_aws_instance_id(&records, &key, aws_instance_id.as_ref())?;
+
+        let after = Inventory {
+            serial_number: changes.serial_number.unwrap_or(current.serial_number.clone()),
+            aws_instance_id,
+            attributes: changes.attributes.unwrap_or(current.attributes.clone()),
+            version: current.version + 1,
+            ..current.clone()
+        };
+        records.insert(key, after.clone());
+        self.record_history(ChangeType::Updated, Some(current), &after);
+        Ok(after)
+    }
+
+    async fn delete_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        deletion: Deletion,
+        expected_version: Option<u64>,
+    ) -> Result<Option<Inventory>, ApiError> {
+        let mut records = self.records();
+        let current = match records.get_mut(&(
+            hash_key_of(&customer_id, &vessel_id),
+            sort_key_of(&inventory_type, &inventory_id),
+        )) {
+            None => return Ok(None),
+            Some(current) if current.deleted_at.is_some() => return Ok(None),
+            Some(current) => current,
+        };
+
+        if expected_version.is_some_and(|version| version != current.version) {
+            return Err(ApiError::VersionConflict(current.version));
+        }
+
+        let before = current.clone();
+        current.deleted_at = Some(deletion.deleted_at);
+        current.deleted_by = deletion.deleted_by;
+        current.version += 1;
+        self.record_history(ChangeType::Deleted, Some(before), current);
+        Ok(Some(current.clone()))
+    }
+
+    async fn restore_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        let mut records = self.records();
+        let key = (
+            hash_key_of(&customer_id, &vessel_id),
+            sort_key_of(&inventory_type, &inventory_id),
+        );
+        let current = records
+            .get(&key)
+            .cloned()
+            .ok_or(ApiError::InventoryNotFound(inventory_type, inventory_id))?;
+
+        if expected_version.is_some_and(|version| version != current.version) {
+            return Err(ApiError::VersionConflict(current.version));
+        }
+
+        // nothing to restore
+        if current.deleted_at.is_none() {
+            return Ok(current);
+        }
+
+        check_aws_instance_id(&records, &key, current.aws_instance_id.as_ref())?;
+
+        let after = Inventory {
+            deleted_at: None,
+            deleted_by: None,
+            version: current.version + 1,
+            ..current.clone()
+        };
+        records.insert(key, after.clone());
+        self.record_history(ChangeType::Restored, Some(current), &after);
+        Ok(after)
+    }
+
+    async fn transition_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        change: StatusChange,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        let mut records = self.records();
+        let key = (
+            hash_key_of(&customer_id, &vessel_id),
+            sort_key_of(&inventory_type, &inventory_id),
+        );
+        let current = Self::existing(&records, &key, inventory_type, inventory_id)?;
+
+        if expected_version.is_some_and(|version| version != current.version) {
+            return Err(ApiError::VersionConflict(current.version));
+        }
+
+        if !current.status.can_transition_to(change.status) {
+            return Err(ApiError::InvalidStatusTransition(current.status, change.status));
+        }
+
+        let after = Inventory {
+            status: change.status,
+            status_change: Some(change),
+            version: current.version + 1,
+            ..current.clone()
+        };
+        records.insert(key, after.clone());
+        self.record_history(ChangeType::StatusChanged, Some(current), &after);
+        Ok(after)
+    }
+
+    async fn transfer_inventory(
+        &self,
+        customer_id: Uuid,
+        vessel_id: Uuid,
+        inventory_type: String,
+        inventory_id: String,
+        target_vessel_id: Uuid,
+        expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        if target_vessel_id == vessel_id {
+            return Err(ApiError::ValidationFailed {
+                field: "targetVesselId".into(),
+                reason: "must differ from current vessel".into(),
+            });
+        }
+
+        let mut records = self.records();
+        let key = (
+            hash_key_of(&customer_id, &vessel_id),
+            sort_key_of(&inventory_type, &inventory_id),
+        );
+        let current = Self::existing(&records, &key, inventory_type.clone(), inventory_id.clone())?;
+
+        if expected_version.is_some_and(|version| version != current.version) {
+            return Err(ApiError::VersionConflict(current.version));
+        }
+
+        let after = Inventory {
+            vessel_id: target_vessel_id,
+            transferred_at: Some(Utc::now().fixed_offset()),
+            version: current.version + 1,
+            ..current.clone()
+        };
+        let target_key = record_key_of(&after);
+        if records.contains_key(&target_key) {
+            return Err(ApiError::InventoryAlreadyExists(inventory_type, inventory_id));
+        }
+
+        records.remove(&key);
+        records.insert(target_key, after.clone());
+        self.record_history(ChangeType::Transferred, Some(current), &after);
+        Ok(after)
+    }
+}
+
+#[doc = "Fails every request with DynamoDB error of given code - meant for tests of error handling."]
+pub struct FailingRepository {
+    code: String,
+}
+
+impl FailingRepository {
+    pub fn new(code: &str) -> Self {
+        Self { code: code.into() }
+    }
+
+    fn error(&self) -> RuntimeError {
+        SdkError::service_error(
+            QueryError::generic(ErrorMetadata::builder().code(self.code.as_str()).build()),
+            Response::new(HttpResponse::builder().status(400).body(SdkBody::empty()).unwrap()),
+        )
+        .into()
+    }
+}
+
+#[async_trait]
+impl InventoryRepository for FailingRepository {
+    async fn create_inventory(&self, _inventory: Inventory) -> Result<(), ApiError> {
+        Err(self.error().into())
+    }
+
+    async fn batch_create(&self, _inventories: Vec<Inventory>) -> Result<Vec<Result<(), ApiError>>, ApiError> {
+        Err(self.error().into())
+    }
+
+    async fn upsert_inventory(&self, _inventory: Inventory) -> Result<(), ApiError> {
+        Err(self.error().into())
+    }
+
+    async fn get_inventory(
+        &self,
+        _customer_id: Uuid,
+        _vessel_id: Uuid,
+        _inventory_type: String,
+        _inventory_id: String,
+    ) -> Result<Option<Inventory>, RuntimeError> {
+        Err(self.error())
+    }
+
+    async fn batch_get(
+        &self,
+        _customer_id: Uuid,
+        _vessel_id: Uuid,
+        _keys: Vec<(String, String)>,
+    ) -> Result<Vec<Inventory>, RuntimeError> {
+        Err(self.error())
+    }
+
+    async fn find_by_serial_number(
+        &self,
+        _customer_id: Uuid,
+        _serial_number: String,
+    ) -> Result<Vec<Inventory>, RuntimeError> {
+        Err(self.error())
+    }
+
+    async fn get_by_aws_instance_id(&self, _aws_instance_id: String) -> Result<Option<Inventory>, RuntimeError> {
+        Err(self.error())
+    }
+
+    async fn list_inventory(
+        &self,
+        _customer_id: Uuid,
+        _vessel_id: Uuid,
+        _filter: InventoryFilter,
+        _page_token: Option<PageToken>,
+        _limit: Option<u32>,
+        _order: SortOrder,
+    ) -> Result<DynamoResultsPage<Inventory, PageToken>, ApiError> {
+        Err(self.error().into())
+    }
+
+    async fn list_customer_inventory(
+        &self,
+        _customer_id: Uuid,
+        _filter: InventoryFilter,
+        _page_token: Option<PageToken>,
+        _limit: Option<u32>,
+        _order: SortOrder,
+    ) -> Result<DynamoResultsPage<Inventory, PageToken>, ApiError> {
+        Err(self.error().into())
+    }
+
+    async fn list_history(
+        &self,
+        _customer_id: Uuid,
+        _vessel_id: Uuid,
+        _inventory_type: String,
+        _inventory_id: String,
+        _page_token: Option<PageToken>,
+        _limit: Option<u32>,
+    ) -> Result<DynamoResultsPage<HistoryEntry, PageToken>, ApiError> {
+        Err(self.error().into())
+    }
+
+    async fn update_inventory(
+        &self,
+        _customer_id: Uuid,
+        _vessel_id: Uuid,
+        _inventory_type: String,
+        _inventory_id: String,
+        _changes: InventoryChanges,
+        _expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        Err(self.error().into())
+    }
+
+    async fn delete_inventory(
+        &self,
+        _customer_id: Uuid,
+        _vessel_id: Uuid,
+        _inventory_type: String,
+        _inventory_id: String,
+        _deletion: Deletion,
+        _expected_version: Option<u64>,
+    ) -> Result<Option<Inventory>, ApiError> {
+        Err(self.error().into())
+    }
+
+    async fn restore_inventory(
+        &self,
+        _customer_id: Uuid,
+        _vessel_id: Uuid,
+        _inventory_type: String,
+        _inventory_id: String,
+        _expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        Err(self.error().into())
+    }
+
+    async fn transition_inventory(
+        &self,
+        _customer_id: Uuid,
+        _vessel_id: Uuid,
+        _inventory_type: String,
+        _inventory_id: String,
+        _change: StatusChange,
+        _expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        Err(self.error().into())
+    }
+
+    async fn transfer_inventory(
+        &self,
+        _customer_id: Uuid,
+        _vessel_id: Uuid,
+        _inventory_type: String,
+        _inventory_id: String,
+        _target_vessel_id: Uuid,
+        _expected_version: Option<u64>,
+    ) -> Result<Inventory, ApiError> {
+        Err(self.error().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repository::{FailingRepository, InventoryRepository, MemoryRepository};
+    use crate::{
+        ApiError, ChangeType, Deletion, ErrorKind, Inventory, InventoryAttribute, InventoryChanges, InventoryFilter,
+        InventoryStatus, PageToken, SortOrder, StatusChange,
+    };
+    use chrono::{DateTime, Utc};
+    use std::collections::HashMap;
+    use tokio::test as tokio_test;
+    use uuid::{uuid, Uuid};
+
+    // customers
+    static ID_0: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
+    // vessels
+    static ID_1: Uuid = uuid!("00000000-0000-0000-0000-000000000001");
+    static ID_2: Uuid = uuid!("00000000-0000-0000-0000-000000000002");
+
+    fn inventory(
+        vessel_id: Uuid,
+        inventory_type: &str,
+        inventory_id: &str,
+        aws_instance_id: Option<&str>,
+    ) -> Inventory {
+        Inventory {
+            customer_id: ID_0,
+            vessel_id,
+            inventory_type: inventory_type.into(),
+            inventory_id: inventory_id.into(),
+            serial_number: None,
+            aws_instance_id: aws_instance_id.map(Into::into),
+            attributes: HashMap::new(),
+            status: InventoryStatus::Ordered,
+            status_change: None,
+            created_at: DateTime::parse_from_rfc3339("2011-01-30T14:58:00+01:00").unwrap(),
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 0,
+        }
+    }
+
+    fn repository() -> MemoryRepository {
+        MemoryRepository::from(vec![
+            inventory(ID_1, "pc", "012", None),
+            inventory(ID_1, "pc", "345", Some("im-12345")),
+            inventory(ID_1, "radar", "012", None),
+            inventory(ID_2, "radar", "012", None),
+        ])
+    }
+
+    fn deletion() -> Deletion {
+        Deletion {
+            deleted_at: Utc::now().fixed_offset(),
+            deleted_by: Some("technician".into()),
+        }
+    }
+
+    fn ids(items: &[Inventory]) -> Vec<String> {
+        items
+            .iter()
+            .map(|inventory| format!("{}:{}", inventory.inventory_type, inventory.inventory_id))
+            .collect()
+    }
+
+    #[tokio_test]
+    async fn create_inventory() {
+        let repository = repository();
+
+        repository
+            .create_inventory(inventory(ID_2, "pc", "012", Some("im-67890")))
+            .await
+            .unwrap();
+
+        let created = repository
+            .get_inventory(ID_0, ID_2, "pc".into(), "012".into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(Some("im-67890".to_string()), created.aws_instance_id);
+        assert_eq!(1, created.version);
+    }
+
+    #[tokio_test]
+    async fn create_inventory_duplicate() {
+        match repository().create_inventory(inventory(ID_1, "pc", "012", None)).await {
+            Err(ApiError::InventoryAlreadyExists(_, _)) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn create_inventory_aws_instance_id_taken() {
+        let repository = repository();
+
+        match repository
+            .create_inventory(inventory(ID_2, "pc", "012", Some("im-12345")))
+            .await
+        {
+            Err(ApiError::AwsInstanceIdAlreadyAssigned(_)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        // deleted inventory releases its reservation
+        repository
+            .delete_inventory(ID_0, ID_1, "pc".into(), "345".into(), deletion(), None)
+            .await
+            .unwrap();
+        repository
+            .create_inventory(inventory(ID_2, "pc", "012", Some("im-12345")))
+            .await
+            .unwrap();
+    }
+
+    #[tokio_test]
+    async fn upsert_inventory_keeps_status() {
+        let repository = repository();
+
+        repository
+            .upsert_inventory(Inventory {
+                serial_number: Some("xyz".into()),
+                status: InventoryStatus::Faulty,
+                ..inventory(ID_1, "pc", "012", None)
+            })
+            .await
+            .unwrap();
+
+        let updated = repository
+            .get_inventory(ID_0, ID_1, "pc".into(), "012".into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(Some("xyz".to_string()), updated.serial_number);
+        assert_eq!(InventoryStatus::Ordered, updated.status);
+        assert_eq!(1, updated.version);
+    }
+
+    #[tokio_test]
+    async fn batch_create() {
+        let repository = repository();
+
+        let results = repository
+            .batch_create(vec![
+                inventory(ID_2, "pc", "012", None),
+                inventory(ID_2, "pc", "012", None),
+                inventory(ID_1, "pc", "012", None),
+                inventory(ID_2, "pc", "345", Some("im-12345")),
+            ])
+            .await
+            .unwrap();
+
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(ApiError::InventoryAlreadyExists(_, _))));
+        assert!(matches!(results[2], Err(ApiError::InventoryAlreadyExists(_, _))));
+        assert!(matches!(results[3], Err(ApiError::AwsInstanceIdAlreadyAssigned(_))));
+    }
+
+    #[tokio_test]
+    async fn find_by_serial_number() {
+        let repository = MemoryRepository::from(vec![
+            Inventory {
+                serial_number: Some("abc".into()),
+                ..inventory(ID_1, "pc", "012", None)
+            },
+            Inventory {
+                serial_number: Some("abc".into()),
+                ..inventory(ID_2, "radar", "012", None)
+            },
+            Inventory {
+                serial_number: Some("abc".into()),
+                deleted_at: Some(Utc::now().fixed_offset()),
+                ..inventory(ID_1, "radar", "345", None)
+            },
+            Inventory {
+                customer_id: ID_1,
+                serial_number: Some("abc".into()),
+                ..inventory(ID_1, "pc", "345", None)
+            },
+        ]);
+
+        let found = repository.find_by_serial_number(ID_0, "abc".into()).await.unwrap();
+        assert_eq!(vec!["pc:012", "radar:012"], ids(&found));
+        assert_eq!(ID_2, found[1].vessel_id);
+
+        assert!(repository
+            .find_by_serial_number(ID_0, "xyz".into())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio_test]
+    async fn get_by_aws_instance_id() {
+        let repository = repository();
+
+        let found = repository
+            .get_by_aws_instance_id("im-12345".into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!("345", found.inventory_id);
+
+        repository
+            .delete_inventory(ID_0, ID_1, "pc".into(), "345".into(), deletion(), None)
+            .await
+            .unwrap();
+        assert!(repository
+            .get_by_aws_instance_id("im-12345".into())
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio_test]
+    async fn failing_repository() {
+        match FailingRepository::new("ResourceNotFoundException")
+            .get_by_aws_instance_id("im-12345".into())
+            .await
+        {
+            Err(error) => assert_eq!(ErrorKind::NotFound, error.kind()),
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn batch_get() {
+        let found = repository()
+            .batch_get(
+                ID_0,
+                ID_1,
+                vec![
+                    ("radar".into(), "012".into()),
+                    ("pc".into(), "999".into()),
+                    ("pc".into(), "012".into()),
+                    ("radar".into(), "012".into()),
+                ],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(vec!["radar:012", "pc:012"], ids(&found));
+    }
+
+    #[tokio_test]
+    async fn delete_inventory() {
+        let repository = repository();
+
+        let deleted = repository
+            .delete_inventory(ID_0, ID_1, "pc".into(), "012".into(), deletion(), Some(0))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(deleted.deleted_at.is_some());
+        assert_eq!(Some("technician".to_string()), deleted.deleted_by);
+        assert_eq!(1, deleted.version);
+
+        // already deleted
+        let deleted = repository
+            .delete_inventory(ID_0, ID_1, "pc".into(), "012".into(), deletion(), None)
+            .await
+            .unwrap();
+        assert!(deleted.is_none());
+    }
+
+    #[tokio_test]
+    async fn delete_inventory_version_conflict() {
+        match repository()
+            .delete_inventory(ID_0, ID_1, "pc".into(), "012".into(), deletion(), Some(3))
+            .await
+        {
+            Err(ApiError::VersionConflict(0)) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn update_inventory() {
+        let repository = repository();
+        let attributes = HashMap::from([("rackPosition".to_string(), InventoryAttribute::Number(2.0))]);
+
+        let updated = repository
+            .update_inventory(
+                ID_0,
+                ID_1,
+                "pc".into(),
+                "012".into(),
+                InventoryChanges {
+                    serial_number: Some(Some("xyz".into())),
+                    aws_instance_id: None,
+                    attributes: Some(attributes.clone()),
+                },
+                Some(0),
+            )
+            .await
+            .unwrap();
+        assert_eq!(Some("xyz".to_string()), updated.serial_number);
+        assert_eq!(attributes, updated.attributes);
+        assert_eq!(1, updated.version);
+
+        // no changes keep the version
+        let unchanged = repository
+            .update_inventory(ID_0, ID_1, "pc".into(), "012".into(), InventoryChanges::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(1, unchanged.version);
+    }
+
+    #[tokio_test]
+    async fn update_inventory_aws_instance_id_taken() {
+        match repository()
+            .update_inventory(
+                ID_0,
+                ID_1,
+                "pc".into(),
+                "012".into(),
+                InventoryChanges {
+                    aws_instance_id: Some(Some("im-12345".into())),
+                    ..InventoryChanges::default()
+                },
+                None,
+            )
+            .await
+        {
+            Err(ApiError::AwsInstanceIdAlreadyAssigned(_)) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn restore_inventory() {
+        let repository = repository();
+        repository
+            .delete_inventory(ID_0, ID_1, "pc".into(), "012".into(), deletion(), None)
+            .await
+            .unwrap();
+
+        let restored = repository
+            .restore_inventory(ID_0, ID_1, "pc".into(), "012".into(), Some(1))
+            .await
+            .unwrap();
+        assert!(restored.deleted_at.is_none());
+        assert!(restored.deleted_by.is_none());
+        assert_eq!(2, restored.version);
+
+        match repository
+            .restore_inventory(ID_0, ID_1, "pc".into(), "999".into(), None)
+            .await
+        {
+            Err(ApiError::InventoryNotFound(_, inventory_id)) => assert_eq!("999", inventory_id),
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn transition_inventory() {
+        let repository = repository();
+        let change = |status| StatusChange {
+            status,
+            changed_at: Utc::now().fixed_offset(),
+            changed_by: Some("technician".into()),
+            reason: None,
+        };
+
+        let transitioned = repository
+            .transition_inventory(
+                ID_0,
+                ID_1,
+                "pc".into(),
+                "012".into(),
+                change(InventoryStatus::Installed),
+                Some(0),
+            )
+            .await
+            .unwrap();
+        assert_eq!(InventoryStatus::Installed, transitioned.status);
+        assert_eq!(1, transitioned.version);
+
+        match repository
+            .transition_inventory(
+                ID_0,
+                ID_1,
+                "pc".into(),
+                "012".into(),
+                change(InventoryStatus::Ordered),
+                None,
+            )
+            .await
+        {
+            Err(ApiError::InvalidStatusTransition(InventoryStatus::Installed, InventoryStatus::Ordered)) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn list_history() {
+        let repository = repository();
+        repository
+            .upsert_inventory(inventory(ID_1, "pc", "012", None))
+            .await
+            .unwrap();
+        repository
+            .delete_inventory(ID_0, ID_1, "pc".into(), "012".into(), deletion(), None)
+            .await
+            .unwrap();
+        repository
+            .transfer_inventory(ID_0, ID_1, "pc".into(), "345".into(), ID_2, None)
+            .await
+            .unwrap();
+
+        let page = repository
+            .list_history(ID_0, ID_1, "pc".into(), "012".into(), None, Some(1))
+            .await
+            .unwrap();
+        assert_eq!(1, page.items.len());
+        assert_eq!(ChangeType::Updated, page.items[0].change_type);
+        assert!(page.last_evaluated_key.is_some());
+
+        let page = repository
+            .list_history(ID_0, ID_1, "pc".into(), "012".into(), page.last_evaluated_key, None)
+            .await
+            .unwrap();
+        assert_eq!(1, page.items.len());
+        assert_eq!(ChangeType::Deleted, page.items[0].change_type);
+        assert!(page.last_evaluated_key.is_none());
+
+        // transfer shows up in both timelines
+        for vessel_id in [ID_1, ID_2] {
+            let page = repository
+                .list_history(ID_0, vessel_id, "pc".into(), "345".into(), None, None)
+                .await
+                .unwrap();
+            assert_eq!(1, page.items.len());
+            assert_eq!(ChangeType::Transferred, page.items[0].change_type);
+        }
+    }
+
+    #[tokio_test]
+    async fn list_history_other_timeline_token() {
+        match repository()
+            .list_history(
+                ID_0,
+                ID_1,
+                "pc".into(),
+                "012".into(),
+                Some(PageToken::new(format!("history:{ID_0}:{ID_1}:pc:345"), "abc".into())),
+                None,
+            )
+            .await
+        {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn transfer_inventory() {
+        let repository = repository();
+
+        let transferred = repository
+            .transfer_inventory(ID_0, ID_1, "pc".into(), "012".into(), ID_2, Some(0))
+            .await
+            .unwrap();
+        assert_eq!(ID_2, transferred.vessel_id);
+        assert!(transferred.transferred_at.is_some());
+        assert_eq!(1, transferred.version);
+
+        assert!(repository
+            .get_inventory(ID_0, ID_1, "pc".into(), "012".into())
+            .await
+            .unwrap()
+            .is_none());
+        assert!(repository
+            .get_inventory(ID_0, ID_2, "pc".into(), "012".into())
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio_test]
+    async fn transfer_inventory_target_taken() {
+        match repository()
+            .transfer_inventory(ID_0, ID_1, "radar".into(), "012".into(), ID_2, None)
+            .await
+        {
+            Err(ApiError::InventoryAlreadyExists(_, inventory_id)) => assert_eq!("012", inventory_id),
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn list_inventory_pages() {
+        let repository = repository();
+
+        let page = repository
+            .list_inventory(ID_0, ID_1, InventoryFilter::default(), None, Some(2), SortOrder::Asc)
+            .await
+            .unwrap();
+        assert_eq!(vec!["pc:012", "pc:345"], ids(&page.items));
+        assert!(page.last_evaluated_key.is_some());
+
+        let page = repository
+            .list_inventory(
+                ID_0,
+                ID_1,
+                InventoryFilter::default(),
+                page.last_evaluated_key,
+                Some(2),
+                SortOrder::Asc,
+            )
+            .await
+            .unwrap();
+        assert_eq!(vec!["radar:012"], ids(&page.items));
+        assert!(page.last_evaluated_key.is_none());
+    }
+
+    #[tokio_test]
+    async fn list_inventory_descending() {
+        let page = repository()
+            .list_inventory(ID_0, ID_1, InventoryFilter::default(), None, None, SortOrder::Desc)
+            .await
+            .unwrap();
+
+        assert_eq!(vec!["radar:012", "pc:345", "pc:012"], ids(&page.items));
+    }
+
+    #[tokio_test]
+    async fn list_inventory_filtered() {
+        let repository = repository();
+        repository
+            .delete_inventory(ID_0, ID_1, "pc".into(), "012".into(), deletion(), None)
+            .await
+            .unwrap();
+
+        // deleted record still counts towards the limit, like with DynamoDB filter expressions
+        let page = repository
+            .list_inventory(
+                ID_0,
+                ID_1,
+                InventoryFilter {
+                    inventory_type: Some("pc".into()),
+                    ..InventoryFilter::default()
+                },
+                None,
+                Some(1),
+                SortOrder::Asc,
+            )
+            .await
+            .unwrap();
+        assert!(page.items.is_empty());
+        assert!(page.last_evaluated_key.is_some());
+
+        let page = repository
+            .list_inventory(
+                ID_0,
+                ID_1,
+                InventoryFilter {
+                    include_deleted: true,
+                    ..InventoryFilter::default()
+                },
+                None,
+                None,
+                SortOrder::Asc,
+            )
+            .await
+            .unwrap();
+        assert_eq!(3, page.items.len());
+    }
+
+    #[tokio_test]
+    async fn list_inventory_other_partition_token() {
+        match repository()
+            .list_inventory(
+                ID_0,
+                ID_1,
+                InventoryFilter::default(),
+                Some(PageToken::new(format!("{ID_0}:{ID_2}"), "pc:012".into())),
+                None,
+                SortOrder::Asc,
+            )
+            .await
+        {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn list_customer_inventory() {
+        let repository = repository();
+
+        let page = repository
+            .list_customer_inventory(ID_0, InventoryFilter::default(), None, Some(3), SortOrder::Asc)
+            .await
+            .unwrap();
+        assert_eq!(vec!["pc:012", "pc:345", "radar:012"], ids(&page.items));
+        assert_eq!(
+            Some(PageToken::new(ID_0.to_string(), format!("{ID_1}#radar:012"))),
+            page.last_evaluated_key
+        );
+
+        let page = repository
+            .list_customer_inventory(
+                ID_0,
+                InventoryFilter {
+                    inventory_type: Some("radar".into()),
+                    ..InventoryFilter::default()
+                },
+                page.last_evaluated_key,
+                None,
+                SortOrder::Asc,
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, page.items.len());
+        assert_eq!(ID_2, page.items[0].vessel_id);
+        assert!(page.last_evaluated_key.is_none());
+    }
+}
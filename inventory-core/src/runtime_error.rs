@@ -4,30 +4,333 @@
  * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
  */
 
-use aws_sdk_dynamodb::operation::delete_item::DeleteItemError;
+use aws_sdk_dynamodb::error::ProvideErrorMetadata;
+use aws_sdk_dynamodb::operation::batch_get_item::BatchGetItemError;
 use aws_sdk_dynamodb::operation::get_item::GetItemError;
-use aws_sdk_dynamodb::operation::put_item::PutItemError;
 use aws_sdk_dynamodb::operation::query::QueryError;
+use aws_sdk_dynamodb::operation::scan::ScanError;
+use aws_sdk_dynamodb::operation::transact_write_items::TransactWriteItemsError;
+use aws_sdk_dynamodb::operation::update_item::UpdateItemError;
 use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_eventbridge::operation::put_events::PutEventsError;
+use aws_sdk_sns::operation::publish::PublishError;
 use aws_smithy_http::result::SdkError;
 use serde_dynamo::Error as SerializationError;
+use serde_json::Error as TypeRegistryParseError;
+use serde_json::Error as EventSerializationError;
 use std::env::VarError;
-use std::fmt::{Debug, Display, Formatter, Result};
+use std::io::Error as IoError;
+use std::num::ParseIntError;
 use thiserror::Error;
 
+#[doc = "Failure classification, determines retry policy that applies to the failed operation."]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    Throttled,
+    Timeout,
+    Conflict,
+    ConditionFailed,
+    NotFound,
+    Transient,
+    Fatal,
+}
+
+impl ErrorKind {
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Throttled | Self::Timeout | Self::Conflict | Self::Transient)
+    }
+}
+
+// messages are exposed to API clients - details of the underlying failure are only available through `Debug`
 #[derive(Error, Debug)]
 pub enum RuntimeError {
-    ClientConfigLoadingError(VarError),
-    DeleteItemError(#[from] SdkError<DeleteItemError>),
-    GetItemError(#[from] SdkError<GetItemError>),
-    PutItemError(#[from] SdkError<PutItemError>),
-    QueryError(#[from] SdkError<QueryError>),
+    #[error("Service configuration could not be loaded.")]
+    ClientConfigLoadingError(#[source] VarError),
+    #[error("Invalid value of {0} setting.")]
+    ConfigurationParseError(String, #[source] ParseIntError),
+    #[error("Invalid value of {0} setting.")]
+    ConfigurationValueError(String),
+    #[error("Inventory storage request failed.")]
+    BatchGetItemError(#[source] Box<SdkError<BatchGetItemError>>),
+    #[error("Inventory storage request failed.")]
+    GetItemError(#[source] Box<SdkError<GetItemError>>),
+    #[error("Inventory storage request failed.")]
+    QueryError(#[source] Box<SdkError<QueryError>>),
+    #[error("Inventory storage request failed.")]
+    ScanError(#[source] Box<SdkError<ScanError>>),
+    #[error("Inventory storage request failed.")]
+    TransactWriteItemsError(#[source] Box<SdkError<TransactWriteItemsError>>),
+    #[error("Inventory storage request failed.")]
+    UpdateItemError(#[source] Box<SdkError<UpdateItemError>>),
+    #[error("Inventory storage did not process all items.")]
+    UnprocessedItem(String),
+    #[error("Inventory storage request did not complete before invocation deadline.")]
+    DeadlineExceeded,
+    #[error("Stored inventory data is malformed.")]
     DataError(AttributeValue, String),
+    #[error("Inventory data could not be serialized.")]
     SerializationError(#[from] SerializationError),
+    #[error("Inventory type registry could not be read.")]
+    TypeRegistryReadError(#[source] IoError),
+    #[error("Inventory type registry is malformed.")]
+    TypeRegistryParseError(#[from] TypeRegistryParseError),
+    #[error("Unknown event sink {0}.")]
+    UnknownEventSink(String),
+    #[error("Inventory event could not be serialized.")]
+    EventSerializationError(#[source] EventSerializationError),
+    #[error("Inventory event could not be published.")]
+    PutEventsError(#[source] Box<SdkError<PutEventsError>>),
+    #[error("Inventory event could not be published.")]
+    PublishError(#[source] Box<SdkError<PublishError>>),
+    #[error("Inventory event could not be published.")]
+    EventRejected(String),
+}
+
+// SDK errors are boxed to keep `Result<_, RuntimeError>` small
+macro_rules! from_sdk_error {
+    ($($variant:ident($error:ty)),+) => {
+        $(
+            impl From<SdkError<$error>> for RuntimeError {
+                fn from(error: SdkError<$error>) -> Self {
+                    Self::$variant(Box::new(error))
+                }
+            }
+        )+
+    };
+}
+
+from_sdk_error!(
+    BatchGetItemError(BatchGetItemError),
+    GetItemError(GetItemError),
+    QueryError(QueryError),
+    ScanError(ScanError),
+    TransactWriteItemsError(TransactWriteItemsError),
+    UpdateItemError(UpdateItemError),
+    PutEventsError(PutEventsError),
+    PublishError(PublishError)
+);
+
+impl RuntimeError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::BatchGetItemError(error) => sdk_error_kind(error),
+            Self::GetItemError(error) => sdk_error_kind(error),
+            Self::QueryError(error) => sdk_error_kind(error),
+            Self::ScanError(error) => sdk_error_kind(error),
+            Self::TransactWriteItemsError(error) => transaction_error_kind(error),
+            Self::UpdateItemError(error) => sdk_error_kind(error),
+            Self::PutEventsError(error) => sdk_error_kind(error),
+            Self::PublishError(error) => sdk_error_kind(error),
+            // DynamoDB keeps returning unprocessed items when table capacity is exceeded
+            Self::UnprocessedItem(_) => ErrorKind::Throttled,
+            Self::DeadlineExceeded => ErrorKind::Timeout,
+            Self::EventRejected(_) => ErrorKind::Transient,
+            _ => ErrorKind::Fatal,
+        }
+    }
 }
 
-impl Display for RuntimeError {
-    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result {
-        write!(formatter, "{self:?}")
+fn sdk_error_kind<E: ProvideErrorMetadata, R>(error: &SdkError<E, R>) -> ErrorKind {
+    match error {
+        SdkError::ServiceError(context) => service_error_kind(context.err().code()),
+        SdkError::TimeoutError(_) => ErrorKind::Timeout,
+        SdkError::DispatchFailure(_) | SdkError::ResponseError(_) => ErrorKind::Transient,
+        _ => ErrorKind::Fatal,
+    }
+}
+
+fn service_error_kind(code: Option<&str>) -> ErrorKind {
+    match code {
+        Some(
+            "ProvisionedThroughputExceededException"
+            | "RequestLimitExceeded"
+            | "ThrottlingException"
+            | "Throttling"
+            | "ThrottledException",
+        ) => ErrorKind::Throttled,
+        Some("TransactionConflictException" | "TransactionInProgressException") => ErrorKind::Conflict,
+        // condition is evaluated against the stored record - repeating the same request fails the same way
+        Some("ConditionalCheckFailedException") => ErrorKind::ConditionFailed,
+        Some("ResourceNotFoundException" | "NotFound" | "NotFoundException") => ErrorKind::NotFound,
+        Some(
+            "InternalServerError"
+            | "InternalFailure"
+            | "InternalErrorException"
+            | "InternalException"
+            | "ServiceUnavailable"
+            | "ServiceUnavailableException",
+        ) => ErrorKind::Transient,
+        _ => ErrorKind::Fatal,
+    }
+}
+
+// cancelled transaction reports the actual cause per item
+fn transaction_error_kind(error: &SdkError<TransactWriteItemsError>) -> ErrorKind {
+    match error {
+        SdkError::ServiceError(context) => match context.err() {
+            TransactWriteItemsError::TransactionCanceledException(exception) => {
+                let codes: Vec<&str> = exception
+                    .cancellation_reasons()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|reason| reason.code())
+                    .collect();
+
+                if codes
+                    .iter()
+                    .any(|code| matches!(*code, "ThrottlingError" | "ProvisionedThroughputExceeded"))
+                {
+                    ErrorKind::Throttled
+                } else if codes.contains(&"ConditionalCheckFailed") {
+                    ErrorKind::ConditionFailed
+                } else if codes.contains(&"TransactionConflict") {
+                    ErrorKind::Conflict
+                } else {
+                    ErrorKind::Fatal
+                }
+            }
+            _ => sdk_error_kind(error),
+        },
+        _ => sdk_error_kind(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, RuntimeError};
+    use aws_sdk_dynamodb::operation::query::QueryError;
+    use aws_sdk_dynamodb::operation::transact_write_items::TransactWriteItemsError;
+    use aws_sdk_dynamodb::types::error::TransactionCanceledException;
+    use aws_sdk_dynamodb::types::CancellationReason;
+    use aws_smithy_http::body::SdkBody;
+    use aws_smithy_http::operation::Response;
+    use aws_smithy_http::result::SdkError;
+    use aws_smithy_types::error::ErrorMetadata;
+    use http::Response as HttpResponse;
+    use std::env::VarError;
+
+    fn raw_response() -> Response {
+        Response::new(HttpResponse::builder().status(400).body(SdkBody::empty()).unwrap())
+    }
+
+    fn query_error(code: &str) -> RuntimeError {
+        SdkError::service_error(
+            QueryError::generic(ErrorMetadata::builder().code(code).build()),
+            raw_response(),
+        )
+        .into()
+    }
+
+    fn cancelled_transaction(codes: &[&str]) -> RuntimeError {
+        SdkError::service_error(
+            TransactWriteItemsError::TransactionCanceledException(
+                TransactionCanceledException::builder()
+                    .set_cancellation_reasons(Some(
+                        codes
+                            .iter()
+                            .map(|code| CancellationReason::builder().code(*code).build())
+                            .collect(),
+                    ))
+                    .build(),
+            ),
+            raw_response(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn client_safe_message() {
+        let error = "abc".parse::<u32>().unwrap_err();
+
+        assert_eq!(
+            "Invalid value of INVENTORY_RETENTION_DAYS setting.",
+            RuntimeError::ConfigurationParseError("INVENTORY_RETENTION_DAYS".into(), error).to_string()
+        );
+        assert_eq!(
+            "Service configuration could not be loaded.",
+            RuntimeError::ClientConfigLoadingError(VarError::NotPresent).to_string()
+        );
+    }
+
+    #[test]
+    fn kind_throttled() {
+        assert_eq!(
+            ErrorKind::Throttled,
+            query_error("ProvisionedThroughputExceededException").kind()
+        );
+        assert_eq!(ErrorKind::Throttled, query_error("RequestLimitExceeded").kind());
+        assert_eq!(
+            ErrorKind::Throttled,
+            RuntimeError::UnprocessedItem("pc:test0".into()).kind()
+        );
+        assert_eq!(
+            ErrorKind::Throttled,
+            cancelled_transaction(&["None", "ThrottlingError"]).kind()
+        );
+    }
+
+    #[test]
+    fn kind_conflict() {
+        assert_eq!(ErrorKind::Conflict, query_error("TransactionConflictException").kind());
+        assert_eq!(
+            ErrorKind::Conflict,
+            cancelled_transaction(&["None", "TransactionConflict"]).kind()
+        );
+    }
+
+    #[test]
+    fn kind_condition_failed() {
+        assert_eq!(
+            ErrorKind::ConditionFailed,
+            query_error("ConditionalCheckFailedException").kind()
+        );
+        assert_eq!(
+            ErrorKind::ConditionFailed,
+            cancelled_transaction(&["None", "ConditionalCheckFailed"]).kind()
+        );
+        assert_eq!(
+            ErrorKind::ConditionFailed,
+            cancelled_transaction(&["TransactionConflict", "ConditionalCheckFailed"]).kind()
+        );
+    }
+
+    #[test]
+    fn kind_not_found() {
+        assert_eq!(ErrorKind::NotFound, query_error("ResourceNotFoundException").kind());
+    }
+
+    #[test]
+    fn kind_transient() {
+        assert_eq!(ErrorKind::Transient, query_error("InternalServerError").kind());
+    }
+
+    #[test]
+    fn kind_timeout() {
+        assert_eq!(
+            ErrorKind::Timeout,
+            RuntimeError::from(SdkError::<QueryError>::timeout_error("timed out")).kind()
+        );
+        assert_eq!(ErrorKind::Timeout, RuntimeError::DeadlineExceeded.kind());
+    }
+
+    #[test]
+    fn kind_fatal() {
+        assert_eq!(ErrorKind::Fatal, query_error("ValidationException").kind());
+        assert_eq!(ErrorKind::Fatal, cancelled_transaction(&["ValidationError"]).kind());
+        assert_eq!(
+            ErrorKind::Fatal,
+            RuntimeError::ClientConfigLoadingError(VarError::NotPresent).kind()
+        );
+    }
+
+    #[test]
+    fn kind_retryable() {
+        assert!(ErrorKind::Throttled.is_retryable());
+        assert!(ErrorKind::Timeout.is_retryable());
+        assert!(ErrorKind::Conflict.is_retryable());
+        assert!(ErrorKind::Transient.is_retryable());
+        assert!(!ErrorKind::ConditionFailed.is_retryable());
+        assert!(!ErrorKind::NotFound.is_retryable());
+        assert!(!ErrorKind::Fatal.is_retryable());
     }
 }
@@ -0,0 +1,402 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+use crate::api_error::ApiError;
+use crate::model::{Inventory, InventoryAttribute, InventoryChanges};
+use crate::runtime_error::RuntimeError;
+use serde::Deserialize;
+use serde_json::from_str;
+use std::collections::HashMap;
+use std::env::var;
+use std::fs::read_to_string;
+
+static BUNDLED_TYPES: &str = include_str!("../resources/inventory-types.json");
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[doc = "Optional inventory attribute that can be required by the type."]
+pub enum InventoryField {
+    SerialNumber,
+    AwsInstanceId,
+}
+
+impl InventoryField {
+    fn name(&self) -> &'static str {
+        match self {
+            InventoryField::SerialNumber => "serialNumber",
+            InventoryField::AwsInstanceId => "awsInstanceId",
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+#[doc = "Type of free-form attribute value."]
+pub enum AttributeType {
+    String,
+    Number,
+    Bool,
+    Date,
+}
+
+impl AttributeType {
+    fn name(&self) -> &'static str {
+        match self {
+            AttributeType::String => "string",
+            AttributeType::Number => "number",
+            AttributeType::Bool => "bool",
+            AttributeType::Date => "date",
+        }
+    }
+
+    fn accepts(&self, value: &InventoryAttribute) -> bool {
+        matches!(
+            (self, value),
+            (AttributeType::String, InventoryAttribute::String(_))
+                | (AttributeType::Number, InventoryAttribute::Number(_))
+                | (AttributeType::Bool, InventoryAttribute::Bool(_))
+                | (AttributeType::Date, InventoryAttribute::Date(_))
+        )
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[doc = "Free-form attribute declaration."]
+pub struct AttributeDefinition {
+    #[serde(rename = "type")]
+    #[doc = "Value type."]
+    pub attribute_type: AttributeType,
+    #[serde(default)]
+    #[doc = "Whether attribute must be set for inventory of this type."]
+    pub required: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[doc = "Inventory type declaration."]
+pub struct InventoryTypeDefinition {
+    #[doc = "Human-readable name."]
+    pub display_name: String,
+    #[serde(default)]
+    #[doc = "Attributes that must be set for inventory of this type."]
+    pub required_fields: Vec<InventoryField>,
+    #[serde(default)]
+    #[doc = "Schema of free-form attributes - without it any attributes are allowed."]
+    pub attributes: Option<HashMap<String, AttributeDefinition>>,
+}
+
+#[doc = "Set of allowed inventory types."]
+pub struct TypeRegistry {
+    types: HashMap<String, InventoryTypeDefinition>,
+}
+
+/**
+Optional environment variables:
+<dl>
+    <dt><code>INVENTORY_TYPES_FILE</code></dt>
+    <dd>Path to JSON file with types definitions, bundled definitions are used if not set.</dd>
+</dl>
+ */
+impl TypeRegistry {
+    pub fn load_from_env() -> Result<Self, RuntimeError> {
+        match var("INVENTORY_TYPES_FILE") {
+            Ok(path) => Self::from_json(&read_to_string(path).map_err(RuntimeError::TypeRegistryReadError)?),
+            Err(_) => Self::bundled(),
+        }
+    }
+
+    pub fn bundled() -> Result<Self, RuntimeError> {
+        Self::from_json(BUNDLED_TYPES)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, RuntimeError> {
+        Ok(Self { types: from_str(json)? })
+    }
+
+    pub fn definition(&self, inventory_type: &str) -> Result<&InventoryTypeDefinition, ApiError> {
+        self.types
+            .get(inventory_type)
+            .ok_or_else(|| ApiError::ValidationFailed {
+                field: "inventoryType".into(),
+                reason: format!("{inventory_type} is not a known inventory type"),
+            })
+    }
+
+    pub fn validate_inventory(&self, inventory: &Inventory) -> Result<(), ApiError> {
+        let definition = self.definition(&inventory.inventory_type)?;
+
+        if let Some(schema) = &definition.attributes {
+            validate_schema(schema, &inventory.inventory_type, &inventory.attributes)?;
+        }
+
+        definition.required_fields.iter().try_for_each(|field| {
+            let value = match field {
+                InventoryField::SerialNumber => &inventory.serial_number,
+                InventoryField::AwsInstanceId => &inventory.aws_instance_id,
+            };
+
+            match value {
+                Some(_) => Ok(()),
+                None => Err(missing(field.name(), &inventory.inventory_type)),
+            }
+        })
+    }
+
+    pub fn validate_changes(&self, inventory_type: &str, changes: &InventoryChanges) -> Result<(), ApiError> {
+        let definition = self.definition(inventory_type)?;
+
+        if let (Some(schema), Some(attributes)) = (&definition.attributes, &changes.attributes) {
+            validate_schema(schema, inventory_type, attributes)?;
+        }
+
+        definition.required_fields.iter().try_for_each(|field| {
+            let change = match field {
+                InventoryField::SerialNumber => &changes.serial_number,
+                InventoryField::AwsInstanceId => &changes.aws_instance_id,
+            };
+
+            // untouched fields keep their current values
+            match change {
+                Some(None) => Err(missing(field.name(), inventory_type)),
+                _ => Ok(()),
+            }
+        })
+    }
+}
+
+fn validate_schema(
+    schema: &HashMap<String, AttributeDefinition>,
+    inventory_type: &str,
+    attributes: &HashMap<String, InventoryAttribute>,
+) -> Result<(), ApiError> {
+    attributes.iter().try_for_each(|(name, value)| match schema.get(name) {
+        None => Err(ApiError::ValidationFailed {
+            field: format!("attributes.{name}"),
+            reason: format!("is not defined for {inventory_type} inventory"),
+        }),
+        Some(definition) if !definition.attribute_type.accepts(value) => Err(ApiError::ValidationFailed {
+            field: format!("attributes.{name}"),
+            reason: format!("must be of {} type", definition.attribute_type.name()),
+        }),
+        Some(_) => Ok(()),
+    })?;
+
+    schema
+        .iter()
+        .filter(|(name, definition)| definition.required && !attributes.contains_key(*name))
+        .try_for_each(|(name, _)| Err(missing(&format!("attributes.{name}"), inventory_type)))
+}
+
+fn missing(field: &str, inventory_type: &str) -> ApiError {
+    ApiError::ValidationFailed {
+        field: field.into(),
+        reason: format!("is required for {inventory_type} inventory"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::type_registry::{InventoryField, TypeRegistry};
+    use crate::{ApiError, Inventory, InventoryAttribute, InventoryChanges, InventoryStatus};
+    use chrono::{FixedOffset, TimeZone, Utc};
+    use std::collections::HashMap;
+    use uuid::{uuid, Uuid};
+
+    static ID_0: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
+    static ID_1: Uuid = uuid!("00000000-0000-0000-0000-000000000001");
+
+    fn inventory(inventory_type: &str, serial_number: Option<&str>, aws_instance_id: Option<&str>) -> Inventory {
+        Inventory {
+            customer_id: ID_0,
+            vessel_id: ID_1,
+            inventory_type: inventory_type.into(),
+            inventory_id: "012".into(),
+            serial_number: serial_number.map(String::from),
+            aws_instance_id: aws_instance_id.map(String::from),
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at: Utc
+                .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+                .unwrap()
+                .with_timezone(&FixedOffset::east_opt(3600).unwrap()),
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 0,
+        }
+    }
+
+    fn assert_failure(result: Result<(), ApiError>, expected_field: &str) {
+        match result {
+            Err(ApiError::ValidationFailed { field, .. }) => assert_eq!(expected_field, field),
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[test]
+    fn bundled_types() {
+        let registry = TypeRegistry::bundled().unwrap();
+
+        let pc = registry.definition("pc").unwrap();
+        assert_eq!("PC", pc.display_name);
+        assert_eq!(vec![InventoryField::AwsInstanceId], pc.required_fields);
+
+        let radar = registry.definition("radar").unwrap();
+        assert_eq!(vec![InventoryField::SerialNumber], radar.required_fields);
+    }
+
+    #[test]
+    fn unknown_type() {
+        let registry = TypeRegistry::bundled().unwrap();
+
+        assert!(registry.definition("PC").is_err());
+        assert_failure(
+            registry.validate_inventory(&inventory("STATION", None, None)),
+            "inventoryType",
+        );
+    }
+
+    #[test]
+    fn required_fields() {
+        let registry = TypeRegistry::bundled().unwrap();
+
+        assert!(registry
+            .validate_inventory(&inventory("radar", Some("q1w2e3"), None))
+            .is_ok());
+        assert_failure(
+            registry.validate_inventory(&inventory("radar", None, Some("mi-0123"))),
+            "serialNumber",
+        );
+        assert_failure(
+            registry.validate_inventory(&inventory("pc", Some("q1w2e3"), None)),
+            "awsInstanceId",
+        );
+    }
+
+    #[test]
+    fn optional_fields() {
+        let registry = TypeRegistry::from_json(r#"{"station":{"displayName":"Station"}}"#).unwrap();
+
+        assert!(registry.validate_inventory(&inventory("station", None, None)).is_ok());
+    }
+
+    #[test]
+    fn attributes_schema() {
+        let registry = TypeRegistry::from_json(
+            r#"{"pc":{"displayName":"PC","attributes":{"manufacturer":{"type":"string","required":true},"rackPosition":{"type":"number"}}}}"#,
+        )
+        .unwrap();
+
+        let mut pc = inventory("pc", None, None);
+        assert_failure(registry.validate_inventory(&pc), "attributes.manufacturer");
+
+        pc.attributes
+            .insert("manufacturer".into(), InventoryAttribute::String("Dell".into()));
+        assert!(registry.validate_inventory(&pc).is_ok());
+
+        pc.attributes
+            .insert("rackPosition".into(), InventoryAttribute::String("3".into()));
+        assert_failure(registry.validate_inventory(&pc), "attributes.rackPosition");
+
+        pc.attributes
+            .insert("rackPosition".into(), InventoryAttribute::Number(3.0));
+        assert!(registry.validate_inventory(&pc).is_ok());
+
+        pc.attributes.insert("color".into(), InventoryAttribute::Bool(true));
+        assert_failure(registry.validate_inventory(&pc), "attributes.color");
+    }
+
+    #[test]
+    fn attributes_free_form() {
+        let registry = TypeRegistry::bundled().unwrap();
+
+        let mut station = inventory("station", None, None);
+        station
+            .attributes
+            .insert("anything".into(), InventoryAttribute::Bool(false));
+        assert!(registry.validate_inventory(&station).is_ok());
+    }
+
+    #[test]
+    fn changes() {
+        let registry = TypeRegistry::bundled().unwrap();
+
+        assert!(registry
+            .validate_changes(
+                "radar",
+                &InventoryChanges {
+                    serial_number: None,
+                    aws_instance_id: Some(None),
+                    attributes: None,
+                }
+            )
+            .is_ok());
+        assert!(registry
+            .validate_changes(
+                "radar",
+                &InventoryChanges {
+                    serial_number: Some(Some("q1w2e3".into())),
+                    aws_instance_id: None,
+                    attributes: None,
+                }
+            )
+            .is_ok());
+        assert_failure(
+            registry.validate_changes(
+                "radar",
+                &InventoryChanges {
+                    serial_number: Some(None),
+                    aws_instance_id: None,
+                    attributes: None,
+                },
+            ),
+            "serialNumber",
+        );
+    }
+
+    #[test]
+    fn changes_attributes() {
+        let registry = TypeRegistry::bundled().unwrap();
+
+        assert!(registry
+            .validate_changes(
+                "radar",
+                &InventoryChanges {
+                    attributes: Some(HashMap::from([(
+                        "firmwareVersion".into(),
+                        InventoryAttribute::String("2.1".into()),
+                    )])),
+                    ..InventoryChanges::default()
+                }
+            )
+            .is_ok());
+        assert_failure(
+            registry.validate_changes(
+                "radar",
+                &InventoryChanges {
+                    attributes: Some(HashMap::from([(
+                        "installedAt".into(),
+                        InventoryAttribute::String("yesterday".into()),
+                    )])),
+                    ..InventoryChanges::default()
+                },
+            ),
+            "attributes.installedAt",
+        );
+        assert_failure(
+            registry.validate_changes(
+                "pc",
+                &InventoryChanges {
+                    attributes: Some(HashMap::from([("color".into(), InventoryAttribute::Bool(true))])),
+                    ..InventoryChanges::default()
+                },
+            ),
+            "attributes.color",
+        );
+    }
+}
@@ -0,0 +1,154 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+use crate::api_error::ApiError;
+use crate::model::InventoryAttribute;
+use std::collections::HashMap;
+
+static MAX_LENGTH: usize = 128;
+
+// characters used to compose record keys and page tokens
+static SEPARATORS: [char; 3] = [':', '#', '|'];
+
+fn failure(field: &str, reason: &str) -> ApiError {
+    ApiError::ValidationFailed {
+        field: field.into(),
+        reason: reason.into(),
+    }
+}
+
+#[doc = "Validates free-form value - it can not be empty, too long or contain control characters."]
+pub fn validate_value(field: &str, value: &str) -> Result<(), ApiError> {
+    if value.is_empty() {
+        Err(failure(field, "must not be empty"))
+    } else if value.chars().count() > MAX_LENGTH {
+        Err(failure(
+            field,
+            &format!("must not be longer than {MAX_LENGTH} characters"),
+        ))
+    } else if value.chars().any(char::is_control) {
+        Err(failure(field, "must not contain control characters"))
+    } else {
+        Ok(())
+    }
+}
+
+#[doc = "Validates optional free-form value, absent value is always valid."]
+pub fn validate_optional_value(field: &str, value: Option<&String>) -> Result<(), ApiError> {
+    value.map_or(Ok(()), |value| validate_value(field, value))
+}
+
+#[doc = "Validates value used as a part of record key, which additionally can not contain key separators."]
+pub fn validate_identifier(field: &str, value: &str) -> Result<(), ApiError> {
+    validate_value(field, value)?;
+
+    if value.contains(SEPARATORS) {
+        Err(failure(field, "must not contain \":\", \"#\" or \"|\""))
+    } else {
+        Ok(())
+    }
+}
+
+#[doc = "Validates both parts of inventory key."]
+pub fn validate_inventory_key(inventory_type: &str, inventory_id: &str) -> Result<(), ApiError> {
+    validate_identifier("inventoryType", inventory_type)?;
+    validate_identifier("inventoryId", inventory_id)
+}
+
+#[doc = "Validates names and textual values of free-form attributes."]
+pub fn validate_attributes(attributes: &HashMap<String, InventoryAttribute>) -> Result<(), ApiError> {
+    attributes.iter().try_for_each(|(name, value)| {
+        validate_value("attributes", name)?;
+
+        match value {
+            InventoryAttribute::String(value) => validate_value(&format!("attributes.{name}"), value),
+            _ => Ok(()),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::validation::{
+        validate_attributes, validate_identifier, validate_inventory_key, validate_optional_value, validate_value,
+    };
+    use crate::{ApiError, InventoryAttribute};
+    use std::collections::HashMap;
+
+    fn assert_failure(result: Result<(), ApiError>, expected_field: &str) {
+        match result {
+            Err(ApiError::ValidationFailed { field, .. }) => assert_eq!(expected_field, field),
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[test]
+    fn valid_value() {
+        assert!(validate_value("serialNumber", "q1w2:e3#r4").is_ok());
+    }
+
+    #[test]
+    fn empty_value() {
+        assert_failure(validate_value("serialNumber", ""), "serialNumber");
+    }
+
+    #[test]
+    fn oversize_value() {
+        assert!(validate_value("serialNumber", &"x".repeat(128)).is_ok());
+        assert_failure(validate_value("serialNumber", &"x".repeat(129)), "serialNumber");
+    }
+
+    #[test]
+    fn control_characters() {
+        assert_failure(validate_value("serialNumber", "q1w2\ne3"), "serialNumber");
+        assert_failure(validate_value("serialNumber", "q1w2\u{0}e3"), "serialNumber");
+    }
+
+    #[test]
+    fn optional_value() {
+        assert!(validate_optional_value("awsInstanceId", None).is_ok());
+        assert!(validate_optional_value("awsInstanceId", Some(&"mi-0123".to_string())).is_ok());
+        assert_failure(
+            validate_optional_value("awsInstanceId", Some(&"".to_string())),
+            "awsInstanceId",
+        );
+    }
+
+    #[test]
+    fn identifier_separators() {
+        assert!(validate_identifier("inventoryType", "pc").is_ok());
+        assert_failure(validate_identifier("inventoryType", "pc:x"), "inventoryType");
+        assert_failure(validate_identifier("inventoryType", "pc#x"), "inventoryType");
+        assert_failure(validate_identifier("inventoryType", "pc|x"), "inventoryType");
+    }
+
+    #[test]
+    fn inventory_key() {
+        assert!(validate_inventory_key("pc", "012").is_ok());
+        assert_failure(validate_inventory_key("", "012"), "inventoryType");
+        assert_failure(validate_inventory_key("pc", ""), "inventoryId");
+    }
+
+    #[test]
+    fn attributes() {
+        assert!(validate_attributes(&HashMap::from([
+            ("manufacturer".into(), InventoryAttribute::String("Furuno".into())),
+            ("rackPosition".into(), InventoryAttribute::Number(3.0)),
+        ]))
+        .is_ok());
+        assert_failure(
+            validate_attributes(&HashMap::from([("".into(), InventoryAttribute::Bool(true))])),
+            "attributes",
+        );
+        assert_failure(
+            validate_attributes(&HashMap::from([(
+                "manufacturer".into(),
+                InventoryAttribute::String("".into()),
+            )])),
+            "attributes.manufacturer",
+        );
+    }
+}
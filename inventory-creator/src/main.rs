@@ -7,9 +7,13 @@
 #![feature(future_join)]
 
 use chrono::Utc;
-use inventory_core::{run_lambda, ApiError, Inventory, InventoryDao};
+use inventory_core::{
+    run_lambda, validate_attributes, validate_inventory_key, validate_optional_value, ApiError, Inventory,
+    InventoryAttribute, InventoryDao, InventoryRepository, InventoryStatus, TypeRegistry,
+};
 use lambda_runtime::{Error, LambdaEvent};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tokio::main as tokio_main;
 use uuid::Uuid;
 
@@ -22,6 +26,11 @@ struct Request {
     inventory_id: String,
     serial_number: Option<String>,
     aws_instance_id: Option<String>,
+    #[serde(default)]
+    attributes: HashMap<String, InventoryAttribute>,
+    status: Option<InventoryStatus>,
+    #[serde(default)]
+    upsert: bool,
 }
 
 #[derive(Serialize)]
@@ -31,34 +40,64 @@ struct Response {
     inventory_id: String,
 }
 
+async fn handle(
+    repository: &dyn InventoryRepository,
+    registry: &TypeRegistry,
+    request: Request,
+) -> Result<Response, ApiError> {
+    validate_inventory_key(&request.inventory_type, &request.inventory_id)?;
+    validate_optional_value("serialNumber", request.serial_number.as_ref())?;
+    validate_optional_value("awsInstanceId", request.aws_instance_id.as_ref())?;
+    validate_attributes(&request.attributes)?;
+
+    let inventory = Inventory {
+        customer_id: request.customer_id,
+        vessel_id: request.vessel_id,
+        inventory_type: request.inventory_type.clone(),
+        inventory_id: request.inventory_id.clone(),
+        serial_number: request.serial_number,
+        aws_instance_id: request.aws_instance_id,
+        attributes: request.attributes,
+        // existing devices can be registered in any stage of their lifecycle
+        status: request.status.unwrap_or(InventoryStatus::Ordered),
+        status_change: None,
+        created_at: Utc::now().fixed_offset(),
+        deleted_at: None,
+        deleted_by: None,
+        transferred_at: None,
+        version: 0,
+    };
+    registry.validate_inventory(&inventory)?;
+
+    if request.upsert {
+        repository.upsert_inventory(inventory).await?;
+    } else {
+        repository.create_inventory(inventory).await?;
+    }
+
+    Ok(Response {
+        inventory_type: request.inventory_type,
+        inventory_id: request.inventory_id,
+    })
+}
+
 #[tokio_main]
 async fn main() -> Result<(), Error> {
     let dao = &InventoryDao::load_from_env().await?;
+    let registry = &TypeRegistry::load_from_env()?;
 
-    run_lambda!(move |event: LambdaEvent<Request>| async move {
-        dao.create_inventory(Inventory {
-            customer_id: event.payload.customer_id,
-            vessel_id: event.payload.vessel_id,
-            inventory_type: event.payload.inventory_type.clone(),
-            inventory_id: event.payload.inventory_id.clone(),
-            serial_number: event.payload.serial_number,
-            aws_instance_id: event.payload.aws_instance_id,
-            created_at: Utc::now().fixed_offset(),
-        })
-        .await?;
-
-        Ok::<Response, ApiError>(Response {
-            inventory_type: event.payload.inventory_type,
-            inventory_id: event.payload.inventory_id,
-        })
-    })
+    run_lambda!(move |event: LambdaEvent<Request>| handle(dao, registry, event.payload))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::Request;
+    use crate::{handle, Request};
     use chrono::{DateTime, FixedOffset};
+    use inventory_core::{
+        ApiError, InventoryAttribute, InventoryRepository, InventoryStatus, MemoryRepository, TypeRegistry,
+    };
     use serde_json::from_str;
+    use tokio::test as tokio_test;
     use uuid::{uuid, Uuid};
 
     const CUSTOMER_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
@@ -67,6 +106,17 @@ mod tests {
     const INVENTORY_ID: &str = "123";
     const SERIAL_NUMBER: &str = "abc";
 
+    fn registry() -> TypeRegistry {
+        TypeRegistry::from_json(r#"{"station":{"displayName":"Station"}}"#).unwrap()
+    }
+
+    fn request(inventory_type: &str, inventory_id: &str, upsert: bool) -> Request {
+        from_str(&format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{inventory_type}\",\"inventoryId\":\"{inventory_id}\",\"serialNumber\":\"{SERIAL_NUMBER}\",\"upsert\":{upsert}}}"
+        ))
+        .unwrap()
+    }
+
     #[test]
     fn deserialize_request() {
         let input = format!(
@@ -77,6 +127,9 @@ mod tests {
         assert_eq!(CUSTOMER_ID, request.customer_id);
         assert_eq!(INVENTORY_TYPE, request.inventory_type);
         assert!(request.serial_number.is_none());
+        assert!(request.attributes.is_empty());
+        assert!(request.status.is_none());
+        assert!(!request.upsert);
     }
 
     #[test]
@@ -87,4 +140,110 @@ mod tests {
         assert_eq!(CUSTOMER_ID, request.customer_id);
         assert_eq!(Some(SERIAL_NUMBER.to_string()), request.serial_number);
     }
+
+    #[test]
+    fn deserialize_request_status() {
+        let input = format!("{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"status\":\"installed\"}}");
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(Some(InventoryStatus::Installed), request.status);
+    }
+
+    #[test]
+    fn deserialize_request_upsert() {
+        let input = format!("{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"upsert\":true}}");
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(INVENTORY_ID, request.inventory_id);
+        assert!(request.upsert);
+    }
+
+    #[test]
+    fn deserialize_request_attributes() {
+        let input = format!("{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"attributes\":{{\"manufacturer\":{{\"string\":\"Furuno\"}},\"rackPosition\":{{\"number\":3}},\"primary\":{{\"bool\":true}},\"installedAt\":{{\"date\":\"2014-03-01T09:00:00+01:00\"}}}}}}");
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(4, request.attributes.len());
+        assert_eq!(
+            InventoryAttribute::String("Furuno".into()),
+            request.attributes["manufacturer"]
+        );
+        assert_eq!(InventoryAttribute::Number(3.0), request.attributes["rackPosition"]);
+        assert_eq!(InventoryAttribute::Bool(true), request.attributes["primary"]);
+        assert_eq!(
+            InventoryAttribute::Date(DateTime::<FixedOffset>::parse_from_rfc3339("2014-03-01T09:00:00+01:00").unwrap()),
+            request.attributes["installedAt"]
+        );
+    }
+
+    #[tokio_test]
+    async fn handle_create() {
+        let repository = MemoryRepository::default();
+
+        let response = handle(&repository, &registry(), request(INVENTORY_TYPE, INVENTORY_ID, false))
+            .await
+            .unwrap();
+        assert_eq!(INVENTORY_TYPE, response.inventory_type);
+        assert_eq!(INVENTORY_ID, response.inventory_id);
+
+        let created = repository
+            .get_inventory(CUSTOMER_ID, VESSEL_ID, INVENTORY_TYPE.into(), INVENTORY_ID.into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(Some(SERIAL_NUMBER.to_string()), created.serial_number);
+        assert_eq!(InventoryStatus::Ordered, created.status);
+        assert_eq!(1, created.version);
+    }
+
+    #[tokio_test]
+    async fn handle_create_duplicate() {
+        let repository = MemoryRepository::default();
+        handle(&repository, &registry(), request(INVENTORY_TYPE, INVENTORY_ID, false))
+            .await
+            .unwrap();
+
+        match handle(&repository, &registry(), request(INVENTORY_TYPE, INVENTORY_ID, false)).await {
+            Err(ApiError::InventoryAlreadyExists(_, _)) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_upsert() {
+        let repository = MemoryRepository::default();
+        handle(&repository, &registry(), request(INVENTORY_TYPE, INVENTORY_ID, false))
+            .await
+            .unwrap();
+
+        handle(&repository, &registry(), request(INVENTORY_TYPE, INVENTORY_ID, true))
+            .await
+            .unwrap();
+
+        let inventory = repository.inventory();
+        assert_eq!(1, inventory.len());
+        assert_eq!(2, inventory[0].version);
+    }
+
+    #[tokio_test]
+    async fn handle_invalid_key() {
+        let repository = MemoryRepository::default();
+
+        match handle(&repository, &registry(), request(INVENTORY_TYPE, "", false)).await {
+            Err(ApiError::ValidationFailed { field, .. }) => assert_eq!("inventoryId", field),
+            _ => panic!("Invalid error type."),
+        }
+        assert!(repository.inventory().is_empty());
+    }
+
+    #[tokio_test]
+    async fn handle_unknown_type() {
+        let repository = MemoryRepository::default();
+
+        match handle(&repository, &registry(), request("radar", INVENTORY_ID, false)).await {
+            Err(ApiError::ValidationFailed { field, .. }) => assert_eq!("inventoryType", field),
+            _ => panic!("Invalid error type."),
+        }
+        assert!(repository.inventory().is_empty());
+    }
 }
@@ -6,9 +6,12 @@
 
 #![feature(future_join)]
 
-use inventory_core::{run_lambda, InventoryDao};
+use chrono::{DateTime, FixedOffset, Utc};
+use inventory_core::{
+    actor_of, run_lambda, validate_inventory_key, ApiError, Deletion, Inventory, InventoryDao, InventoryRepository,
+};
 use lambda_runtime::{Error, LambdaEvent};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::main as tokio_main;
 use uuid::Uuid;
 
@@ -19,27 +22,85 @@ struct Request {
     vessel_id: Uuid,
     inventory_type: String,
     inventory_id: String,
+    expected_version: Option<u64>,
+    #[serde(default)]
+    fail_if_missing: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    inventory_type: String,
+    inventory_id: String,
+    serial_number: Option<String>,
+    aws_instance_id: Option<String>,
+    created_at: DateTime<FixedOffset>,
+    deleted_at: Option<DateTime<FixedOffset>>,
+    deleted_by: Option<String>,
+    version: u64,
+}
+
+impl From<Inventory> for Response {
+    fn from(model: Inventory) -> Self {
+        Self {
+            inventory_type: model.inventory_type,
+            inventory_id: model.inventory_id,
+            serial_number: model.serial_number,
+            aws_instance_id: model.aws_instance_id,
+            created_at: model.created_at,
+            deleted_at: model.deleted_at,
+            deleted_by: model.deleted_by,
+            version: model.version,
+        }
+    }
+}
+
+// deletion is attributed to the authenticated caller, never to the value claimed by the request
+async fn handle(
+    repository: &dyn InventoryRepository,
+    request: Request,
+    actor: Option<String>,
+) -> Result<Option<Response>, ApiError> {
+    validate_inventory_key(&request.inventory_type, &request.inventory_id)?;
+
+    match repository
+        .delete_inventory(
+            request.customer_id,
+            request.vessel_id,
+            request.inventory_type.clone(),
+            request.inventory_id.clone(),
+            Deletion {
+                deleted_at: Utc::now().fixed_offset(),
+                deleted_by: actor,
+            },
+            request.expected_version,
+        )
+        .await?
+    {
+        // by default deletion is idempotent, nothing deleted results in empty response
+        None if request.fail_if_missing => Err(ApiError::InventoryNotFound(
+            request.inventory_type,
+            request.inventory_id,
+        )),
+        inventory => Ok(inventory.map(Response::from)),
+    }
 }
 
 #[tokio_main]
 async fn main() -> Result<(), Error> {
     let dao = &InventoryDao::load_from_env().await?;
 
-    run_lambda!(move |event: LambdaEvent<Request>| async move {
-        dao.delete_inventory(
-            event.payload.customer_id,
-            event.payload.vessel_id,
-            event.payload.inventory_type,
-            event.payload.inventory_id,
-        )
-        .await
-    })
+    run_lambda!(move |event: LambdaEvent<Request>| handle(dao, event.payload, actor_of(&event.context)))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::Request;
-    use serde_json::from_str;
+    use crate::{handle, Request, Response};
+    use chrono::{FixedOffset, TimeZone, Utc};
+    use inventory_core::{ApiError, Inventory, InventoryRepository, InventoryStatus, MemoryRepository};
+    use serde_json::{from_str, to_string};
+    use std::collections::HashMap;
+    use tokio::test as tokio_test;
     use uuid::{uuid, Uuid};
 
     const CUSTOMER_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
@@ -47,6 +108,35 @@ mod tests {
     const INVENTORY_TYPE: &str = "STATION";
     const INVENTORY_ID: &str = "test0";
 
+    fn repository() -> MemoryRepository {
+        MemoryRepository::from(vec![Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id: VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: Some("abc".into()),
+            aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at: Utc
+                .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+                .unwrap()
+                .with_timezone(&FixedOffset::east_opt(3600).unwrap()),
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 2,
+        }])
+    }
+
+    fn request(inventory_id: &str, extra: &str) -> Request {
+        from_str(&format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{inventory_id}\"{extra}}}"
+        ))
+        .unwrap()
+    }
+
     #[test]
     fn deserialize_request() {
         let input = format!(
@@ -58,5 +148,146 @@ mod tests {
         assert_eq!(VESSEL_ID, request.vessel_id);
         assert_eq!(INVENTORY_TYPE, request.inventory_type);
         assert_eq!(INVENTORY_ID, request.inventory_id);
+        assert!(request.expected_version.is_none());
+        assert!(!request.fail_if_missing);
+    }
+
+    #[test]
+    fn deserialize_request_version() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"expectedVersion\":5}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(Some(5), request.expected_version);
+    }
+
+    #[test]
+    fn deserialize_request_fail_if_missing() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"failIfMissing\":true}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert!(request.fail_if_missing);
+    }
+
+    #[test]
+    fn serialize_response() {
+        let created_at = Utc
+            .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(3600).unwrap());
+
+        let output = to_string(&Response {
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: None,
+            aws_instance_id: None,
+            created_at,
+            deleted_at: Some(created_at),
+            deleted_by: Some("technician".into()),
+            version: 2,
+        })
+        .unwrap();
+
+        assert!(output.contains(&format!("\"{INVENTORY_ID}\"")));
+        assert!(output.contains("\"deletedAt\":\"2011-01-30T14:58:00+01:00\""));
+        assert!(output.contains("\"deletedBy\":\"technician\""));
+    }
+
+    #[test]
+    fn response_from_model() {
+        let created_at = Utc
+            .with_ymd_and_hms(2015, 7, 2, 1, 20, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+
+        let response = Response::from(Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id: VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: Some("abc".into()),
+            aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at,
+            deleted_at: Some(created_at),
+            deleted_by: None,
+            transferred_at: None,
+            version: 3,
+        });
+
+        assert_eq!(INVENTORY_ID, response.inventory_id);
+        assert_eq!(Some(created_at), response.deleted_at);
+        assert_eq!(3, response.version);
+    }
+
+    #[tokio_test]
+    async fn handle_delete() {
+        let repository = repository();
+
+        let response = handle(&repository, request(INVENTORY_ID, ""), Some("technician".into()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(INVENTORY_ID, response.inventory_id);
+        assert!(response.deleted_at.is_some());
+        assert_eq!(Some("technician".to_string()), response.deleted_by);
+        assert_eq!(3, response.version);
+
+        let stored = repository
+            .get_inventory(CUSTOMER_ID, VESSEL_ID, INVENTORY_TYPE.into(), INVENTORY_ID.into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.deleted_at.is_some());
+    }
+
+    #[tokio_test]
+    async fn handle_delete_missing() {
+        let response = handle(&repository(), request("test1", ""), None).await.unwrap();
+
+        assert!(response.is_none());
+    }
+
+    #[tokio_test]
+    async fn handle_delete_missing_failing() {
+        match handle(&repository(), request("test1", ",\"failIfMissing\":true"), None).await {
+            Err(ApiError::InventoryNotFound(_, inventory_id)) => assert_eq!("test1", inventory_id),
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_delete_twice() {
+        let repository = repository();
+        handle(&repository, request(INVENTORY_ID, ""), None).await.unwrap();
+
+        let response = handle(&repository, request(INVENTORY_ID, ""), None).await.unwrap();
+        assert!(response.is_none());
+    }
+
+    #[tokio_test]
+    async fn handle_delete_version_conflict() {
+        match handle(&repository(), request(INVENTORY_ID, ",\"expectedVersion\":1"), None).await {
+            Err(ApiError::VersionConflict(2)) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_delete_ignores_claimed_actor() {
+        let response = handle(
+            &repository(),
+            request(INVENTORY_ID, ",\"deletedBy\":\"someone-else\""),
+            Some("technician".into()),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(Some("technician".to_string()), response.deleted_by);
     }
 }
@@ -7,39 +7,144 @@
 #![feature(future_join)]
 
 use chrono::{DateTime, FixedOffset};
-use inventory_core::{run_lambda, ApiError, Inventory, InventoryDao};
+use inventory_core::{
+    run_lambda, validate_inventory_key, ApiError, Inventory, InventoryAttribute, InventoryDao, InventoryRepository,
+    InventoryStatus, StatusChange,
+};
 use lambda_runtime::{Error, LambdaEvent};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tokio::main as tokio_main;
 use uuid::Uuid;
 
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct InventoryKey {
+    inventory_type: String,
+    inventory_id: String,
+}
+
+// batch mode is selected by presence of `keys` list instead of single key
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Target {
+    Batch {
+        keys: Vec<InventoryKey>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Single {
+        inventory_type: String,
+        inventory_id: String,
+    },
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct Request {
     customer_id: Uuid,
     vessel_id: Uuid,
-    inventory_type: String,
-    inventory_id: String,
+    #[serde(flatten)]
+    target: Target,
+    #[serde(default)]
+    include_deleted: bool,
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-struct Response {
+struct InventoryResponse {
     inventory_type: String,
     inventory_id: String,
     serial_number: Option<String>,
     aws_instance_id: Option<String>,
+    attributes: HashMap<String, InventoryAttribute>,
+    status: InventoryStatus,
+    status_change: Option<StatusChange>,
     created_at: DateTime<FixedOffset>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deleted_at: Option<DateTime<FixedOffset>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deleted_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transferred_at: Option<DateTime<FixedOffset>>,
+    version: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchResponse {
+    inventory: Vec<InventoryResponse>,
+    missing: Vec<InventoryKey>,
 }
 
-impl From<Inventory> for Response {
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Response {
+    Single(Box<InventoryResponse>),
+    Batch(BatchResponse),
+}
+
+impl From<Inventory> for InventoryResponse {
     fn from(model: Inventory) -> Self {
         Self {
             inventory_type: model.inventory_type,
             inventory_id: model.inventory_id,
             serial_number: model.serial_number,
             aws_instance_id: model.aws_instance_id,
+            attributes: model.attributes,
+            status: model.status,
+            status_change: model.status_change,
             created_at: model.created_at,
+            deleted_at: model.deleted_at,
+            deleted_by: model.deleted_by,
+            transferred_at: model.transferred_at,
+            version: model.version,
+        }
+    }
+}
+
+async fn handle(repository: &dyn InventoryRepository, request: Request) -> Result<Response, ApiError> {
+    let include_deleted = request.include_deleted;
+
+    match request.target {
+        Target::Single {
+            inventory_type,
+            inventory_id,
+        } => {
+            validate_inventory_key(&inventory_type, &inventory_id)?;
+
+            match repository
+                .get_inventory(
+                    request.customer_id,
+                    request.vessel_id,
+                    inventory_type.clone(),
+                    inventory_id.clone(),
+                )
+                .await?
+                .filter(|inventory| include_deleted || inventory.deleted_at.is_none())
+            {
+                None => Err(ApiError::InventoryNotFound(inventory_type, inventory_id)),
+                Some(license) => Ok(Response::Single(Box::new(InventoryResponse::from(license)))),
+            }
+        }
+        Target::Batch { keys } => {
+            for key in &keys {
+                validate_inventory_key(&key.inventory_type, &key.inventory_id)?;
+            }
+
+            let found: Vec<Inventory> = repository
+                .batch_get(
+                    request.customer_id,
+                    request.vessel_id,
+                    keys.iter()
+                        .map(|key| (key.inventory_type.clone(), key.inventory_id.clone()))
+                        .collect(),
+                )
+                .await?
+                .into_iter()
+                .filter(|inventory| include_deleted || inventory.deleted_at.is_none())
+                .collect();
+
+            Ok(Response::Batch(batch_response(keys, found)))
         }
     }
 }
@@ -48,31 +153,34 @@ impl From<Inventory> for Response {
 async fn main() -> Result<(), Error> {
     let dao = &InventoryDao::load_from_env().await?;
 
-    run_lambda!(move |event: LambdaEvent<Request>| async move {
-        match dao
-            .get_inventory(
-                event.payload.customer_id,
-                event.payload.vessel_id,
-                event.payload.inventory_type.clone(),
-                event.payload.inventory_id.clone(),
-            )
-            .await?
-        {
-            None => Err(ApiError::InventoryNotFound(
-                event.payload.inventory_type,
-                event.payload.inventory_id,
-            )),
-            Some(license) => Ok(Response::from(license)),
+    run_lambda!(move |event: LambdaEvent<Request>| handle(dao, event.payload))
+}
+
+fn batch_response(keys: Vec<InventoryKey>, found: Vec<Inventory>) -> BatchResponse {
+    let mut missing: Vec<InventoryKey> = vec![];
+    for key in keys {
+        let exists = found.iter().any(|inventory| {
+            inventory.inventory_type == key.inventory_type && inventory.inventory_id == key.inventory_id
+        });
+        if !exists && !missing.contains(&key) {
+            missing.push(key);
         }
-    })
+    }
+
+    BatchResponse {
+        inventory: found.into_iter().map(InventoryResponse::from).collect(),
+        missing,
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Request, Response};
+    use crate::{batch_response, handle, InventoryKey, InventoryResponse, Request, Response, Target};
     use chrono::{FixedOffset, TimeZone, Utc};
-    use inventory_core::Inventory;
+    use inventory_core::{ApiError, Inventory, InventoryAttribute, InventoryStatus, MemoryRepository, StatusChange};
     use serde_json::{from_str, to_string};
+    use std::collections::HashMap;
+    use tokio::test as tokio_test;
     use uuid::{uuid, Uuid};
 
     const CUSTOMER_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
@@ -80,6 +188,46 @@ mod tests {
     const INVENTORY_TYPE: &str = "pc";
     const INVENTORY_ID: &str = "0";
     const SERIAL_NUMBER: &str = "abc";
+    const DELETED_ID: &str = "2";
+
+    fn repository() -> MemoryRepository {
+        let created_at = Utc
+            .with_ymd_and_hms(2015, 7, 2, 1, 20, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+        let inventory = Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id: VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: Some(SERIAL_NUMBER.to_string()),
+            aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at,
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 1,
+        };
+
+        MemoryRepository::from(vec![
+            Inventory {
+                inventory_id: DELETED_ID.to_string(),
+                deleted_at: Some(created_at),
+                ..inventory.clone()
+            },
+            inventory,
+        ])
+    }
+
+    fn single_request(inventory_id: &str, include_deleted: bool) -> Request {
+        from_str(&format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{inventory_id}\",\"includeDeleted\":{include_deleted}}}"
+        ))
+        .unwrap()
+    }
 
     #[test]
     fn deserialize_request() {
@@ -90,8 +238,47 @@ mod tests {
 
         assert_eq!(CUSTOMER_ID, request.customer_id);
         assert_eq!(VESSEL_ID, request.vessel_id);
-        assert_eq!(INVENTORY_TYPE, request.inventory_type);
-        assert_eq!(INVENTORY_ID, request.inventory_id);
+        match request.target {
+            Target::Single {
+                inventory_type,
+                inventory_id,
+            } => {
+                assert_eq!(INVENTORY_TYPE, inventory_type);
+                assert_eq!(INVENTORY_ID, inventory_id);
+            }
+            _ => panic!("Invalid request mode."),
+        }
+        assert!(!request.include_deleted);
+    }
+
+    #[test]
+    fn deserialize_request_include_deleted() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"includeDeleted\":true}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert!(request.include_deleted);
+    }
+
+    #[test]
+    fn deserialize_request_batch() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"keys\":[{{\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\"}},{{\"inventoryType\":\"radar\",\"inventoryId\":\"1\"}}],\"includeDeleted\":true}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(CUSTOMER_ID, request.customer_id);
+        match request.target {
+            Target::Batch { keys } => {
+                assert_eq!(2, keys.len());
+                assert_eq!(INVENTORY_TYPE, keys[0].inventory_type);
+                assert_eq!(INVENTORY_ID, keys[0].inventory_id);
+                assert_eq!("radar", keys[1].inventory_type);
+            }
+            _ => panic!("Invalid request mode."),
+        }
+        assert!(request.include_deleted);
     }
 
     #[test]
@@ -101,18 +288,62 @@ mod tests {
             .unwrap()
             .with_timezone(&FixedOffset::east_opt(3600).unwrap());
 
-        let output = to_string(&Response {
+        let output = to_string(&InventoryResponse {
             inventory_type: INVENTORY_TYPE.to_string(),
             inventory_id: INVENTORY_ID.to_string(),
             serial_number: Some(SERIAL_NUMBER.to_string()),
             aws_instance_id: None,
+            attributes: HashMap::from([("manufacturer".into(), InventoryAttribute::String("Dell".into()))]),
+            status: InventoryStatus::Faulty,
+            status_change: Some(StatusChange {
+                status: InventoryStatus::Faulty,
+                changed_at: created_at,
+                changed_by: Some("technician".into()),
+                reason: Some("Display broken".into()),
+            }),
             created_at,
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 1,
         })
         .unwrap();
 
         assert!(output.contains(&format!("\"{INVENTORY_ID}\"")));
         assert!(output.contains(&format!("\"{SERIAL_NUMBER}\"")));
         assert!(output.contains(&format!("\"2011-01-30T14:58:00+01:00\"")));
+        assert!(output.contains("\"attributes\":{\"manufacturer\":{\"string\":\"Dell\"}}"));
+        assert!(output.contains("\"status\":\"faulty\""));
+        assert!(output.contains("\"changedBy\":\"technician\""));
+        assert!(!output.contains("\"deletedAt\""));
+        assert!(!output.contains("\"transferredAt\""));
+    }
+
+    #[test]
+    fn serialize_response_deleted() {
+        let created_at = Utc
+            .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(3600).unwrap());
+
+        let output = to_string(&InventoryResponse {
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: None,
+            aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at,
+            deleted_at: Some(created_at),
+            deleted_by: Some("technician".into()),
+            transferred_at: None,
+            version: 2,
+        })
+        .unwrap();
+
+        assert!(output.contains("\"deletedAt\":\"2011-01-30T14:58:00+01:00\""));
+        assert!(output.contains("\"deletedBy\":\"technician\""));
     }
 
     #[test]
@@ -122,18 +353,127 @@ mod tests {
             .unwrap()
             .with_timezone(&FixedOffset::east_opt(7200).unwrap());
 
-        let response = Response::from(Inventory {
+        let response = InventoryResponse::from(Inventory {
             customer_id: CUSTOMER_ID,
             vessel_id: VESSEL_ID,
             inventory_type: INVENTORY_TYPE.to_string(),
             inventory_id: INVENTORY_ID.to_string(),
             serial_number: Some(SERIAL_NUMBER.to_string()),
             aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
             created_at,
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 3,
         });
 
         assert_eq!(INVENTORY_TYPE, response.inventory_type);
         assert_eq!(Some(SERIAL_NUMBER.to_string()), response.serial_number);
         assert!(response.aws_instance_id.is_none());
+        assert_eq!(3, response.version);
+    }
+
+    #[test]
+    fn batch_response_missing() {
+        let created_at = Utc
+            .with_ymd_and_hms(2015, 7, 2, 1, 20, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+        let key = |inventory_id: &str| InventoryKey {
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: inventory_id.to_string(),
+        };
+
+        let response = batch_response(
+            vec![key(INVENTORY_ID), key("1"), key("1")],
+            vec![Inventory {
+                customer_id: CUSTOMER_ID,
+                vessel_id: VESSEL_ID,
+                inventory_type: INVENTORY_TYPE.to_string(),
+                inventory_id: INVENTORY_ID.to_string(),
+                serial_number: None,
+                aws_instance_id: None,
+                attributes: HashMap::new(),
+                status: InventoryStatus::InService,
+                status_change: None,
+                created_at,
+                deleted_at: None,
+                deleted_by: None,
+                transferred_at: None,
+                version: 1,
+            }],
+        );
+
+        assert_eq!(1, response.inventory.len());
+        assert_eq!(INVENTORY_ID, response.inventory[0].inventory_id);
+        assert_eq!(vec![key("1")], response.missing);
+
+        let output = to_string(&Response::Batch(response)).unwrap();
+        assert!(output.starts_with("{\"inventory\":[{"));
+        assert!(output.ends_with(&format!(
+            "\"missing\":[{{\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"1\"}}]}}"
+        )));
+    }
+
+    #[tokio_test]
+    async fn handle_single() {
+        match handle(&repository(), single_request(INVENTORY_ID, false)).await {
+            Ok(Response::Single(inventory)) => {
+                assert_eq!(INVENTORY_ID, inventory.inventory_id);
+                assert_eq!(Some(SERIAL_NUMBER.to_string()), inventory.serial_number);
+            }
+            _ => panic!("Invalid response type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_single_missing() {
+        match handle(&repository(), single_request("1", false)).await {
+            Err(ApiError::InventoryNotFound(_, inventory_id)) => assert_eq!("1", inventory_id),
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_single_deleted() {
+        match handle(&repository(), single_request(DELETED_ID, false)).await {
+            Err(ApiError::InventoryNotFound(_, _)) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        match handle(&repository(), single_request(DELETED_ID, true)).await {
+            Ok(Response::Single(inventory)) => assert!(inventory.deleted_at.is_some()),
+            _ => panic!("Invalid response type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_single_invalid_key() {
+        match handle(&repository(), single_request("", false)).await {
+            Err(ApiError::ValidationFailed { field, .. }) => assert_eq!("inventoryId", field),
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_batch() {
+        let request: Request = from_str(&format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"keys\":[{{\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"1\"}},{{\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{DELETED_ID}\"}},{{\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\"}}]}}"
+        ))
+        .unwrap();
+
+        match handle(&repository(), request).await {
+            Ok(Response::Batch(response)) => {
+                assert_eq!(1, response.inventory.len());
+                assert_eq!(INVENTORY_ID, response.inventory[0].inventory_id);
+                assert_eq!(2, response.missing.len());
+                assert_eq!("1", response.missing[0].inventory_id);
+                assert_eq!(DELETED_ID, response.missing[1].inventory_id);
+            }
+            _ => panic!("Invalid response type."),
+        }
     }
 }
@@ -0,0 +1,249 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+#![feature(future_join)]
+
+use inventory_core::{
+    run_lambda, validate_inventory_key, ApiError, DynamoResultsPage, HistoryEntry, InventoryDao, InventoryRepository,
+    PageTokenCodec,
+};
+use lambda_runtime::{Error, LambdaEvent};
+use serde::{Deserialize, Serialize};
+use tokio::main as tokio_main;
+use uuid::Uuid;
+
+// timeline is already identified by the token partition, scope only separates history tokens from listing ones
+static HISTORY_SCOPE: &str = "history";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    customer_id: Uuid,
+    vessel_id: Uuid,
+    inventory_type: String,
+    inventory_id: String,
+    page_token: Option<String>,
+    limit: Option<u32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    history: Vec<HistoryEntry>,
+    page_token: Option<String>,
+}
+
+impl From<DynamoResultsPage<HistoryEntry, String>> for Response {
+    fn from(value: DynamoResultsPage<HistoryEntry, String>) -> Self {
+        Self {
+            history: value.items,
+            page_token: value.last_evaluated_key,
+        }
+    }
+}
+
+async fn handle(
+    repository: &dyn InventoryRepository,
+    codec: &PageTokenCodec,
+    request: Request,
+) -> Result<Response, ApiError> {
+    validate_inventory_key(&request.inventory_type, &request.inventory_id)?;
+
+    let page_token = request
+        .page_token
+        .map(|page_token| codec.decode(HISTORY_SCOPE, &page_token))
+        .transpose()?;
+
+    let page = repository
+        .list_history(
+            request.customer_id,
+            request.vessel_id,
+            request.inventory_type,
+            request.inventory_id,
+            page_token,
+            request.limit,
+        )
+        .await?;
+
+    Ok(Response::from(DynamoResultsPage {
+        items: page.items,
+        last_evaluated_key: page
+            .last_evaluated_key
+            .map(|page_token| codec.encode(HISTORY_SCOPE, &page_token)),
+    }))
+}
+
+#[tokio_main]
+async fn main() -> Result<(), Error> {
+    let dao = &InventoryDao::load_from_env().await?;
+    let codec = &PageTokenCodec::load_from_env()?;
+
+    run_lambda!(move |event: LambdaEvent<Request>| handle(dao, codec, event.payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{handle, Request, Response};
+    use chrono::{FixedOffset, TimeZone, Utc};
+    use inventory_core::{
+        ApiError, ChangeType, DynamoResultsPage, HistoryEntry, Inventory, InventoryRepository, InventoryStatus,
+        MemoryRepository, PageTokenCodec, StatusChange,
+    };
+    use serde_json::{from_str, to_string};
+    use std::collections::HashMap;
+    use tokio::test as tokio_test;
+    use uuid::{uuid, Uuid};
+
+    const CUSTOMER_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
+    const VESSEL_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000001");
+    const INVENTORY_TYPE: &str = "radar";
+    const INVENTORY_ID: &str = "test0";
+    const PAGE_TOKEN: &str = "abc";
+
+    fn entry() -> HistoryEntry {
+        let changed_at = Utc
+            .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(3600).unwrap());
+
+        HistoryEntry {
+            changed_at,
+            change_type: ChangeType::Created,
+            actor: Some("inspector".into()),
+            version: 1,
+            before: None,
+            after: Inventory {
+                customer_id: CUSTOMER_ID,
+                vessel_id: VESSEL_ID,
+                inventory_type: INVENTORY_TYPE.to_string(),
+                inventory_id: INVENTORY_ID.to_string(),
+                serial_number: None,
+                aws_instance_id: None,
+                attributes: HashMap::new(),
+                status: InventoryStatus::Ordered,
+                status_change: None,
+                created_at: changed_at,
+                deleted_at: None,
+                deleted_by: None,
+                transferred_at: None,
+                version: 1,
+            },
+        }
+    }
+
+    async fn repository() -> MemoryRepository {
+        let repository = MemoryRepository::default();
+        repository.create_inventory(entry().after).await.unwrap();
+        repository
+            .transition_inventory(
+                CUSTOMER_ID,
+                VESSEL_ID,
+                INVENTORY_TYPE.into(),
+                INVENTORY_ID.into(),
+                StatusChange {
+                    status: InventoryStatus::Installed,
+                    changed_at: Utc::now().fixed_offset(),
+                    changed_by: None,
+                    reason: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        repository
+    }
+
+    fn codec() -> PageTokenCodec {
+        PageTokenCodec::new(b"secret".to_vec())
+    }
+
+    fn request(page_token: Option<String>) -> Request {
+        Request {
+            customer_id: CUSTOMER_ID,
+            vessel_id: VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.into(),
+            inventory_id: INVENTORY_ID.into(),
+            page_token,
+            limit: Some(1),
+        }
+    }
+
+    #[test]
+    fn deserialize_request() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"pageToken\":\"{PAGE_TOKEN}\",\"limit\":10}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(CUSTOMER_ID, request.customer_id);
+        assert_eq!(VESSEL_ID, request.vessel_id);
+        assert_eq!(INVENTORY_TYPE, request.inventory_type);
+        assert_eq!(INVENTORY_ID, request.inventory_id);
+        assert_eq!(Some(PAGE_TOKEN.to_string()), request.page_token);
+        assert_eq!(Some(10), request.limit);
+    }
+
+    #[test]
+    fn deserialize_request_no_page() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\"}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert!(request.page_token.is_none());
+        assert!(request.limit.is_none());
+    }
+
+    #[test]
+    fn serialize_response() {
+        let output = to_string(&Response {
+            history: vec![entry()],
+            page_token: Some(PAGE_TOKEN.to_string()),
+        })
+        .unwrap();
+
+        assert!(output.contains("\"changeType\":\"created\""));
+        assert!(output.contains("\"actor\":\"inspector\""));
+        assert!(output.contains(&format!("\"inventoryId\":\"{INVENTORY_ID}\"")));
+        assert!(output.contains(&format!("\"pageToken\":\"{PAGE_TOKEN}\"")));
+    }
+
+    #[test]
+    fn response_from_page() {
+        let response = Response::from(DynamoResultsPage {
+            items: vec![entry()],
+            last_evaluated_key: None,
+        });
+
+        assert_eq!(1, response.history.len());
+        assert_eq!(ChangeType::Created, response.history[0].change_type);
+        assert!(response.page_token.is_none());
+    }
+
+    #[tokio_test]
+    async fn handle_history() {
+        let repository = repository().await;
+        let codec = codec();
+
+        let response = handle(&repository, &codec, request(None)).await.unwrap();
+        assert_eq!(1, response.history.len());
+        assert_eq!(ChangeType::Created, response.history[0].change_type);
+        assert!(response.page_token.is_some());
+
+        let response = handle(&repository, &codec, request(response.page_token)).await.unwrap();
+        assert_eq!(1, response.history.len());
+        assert_eq!(ChangeType::StatusChanged, response.history[0].change_type);
+        assert_eq!(InventoryStatus::Installed, response.history[0].after.status);
+    }
+
+    #[tokio_test]
+    async fn handle_history_invalid_page_token() {
+        match handle(&repository().await, &codec(), request(Some(PAGE_TOKEN.into()))).await {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+}
@@ -7,28 +7,55 @@
 #![feature(future_join)]
 
 use chrono::{DateTime, FixedOffset};
-use inventory_core::{run_lambda, DynamoResultsPage, Inventory, InventoryDao};
+use inventory_core::{
+    run_lambda, validate_identifier, ApiError, DynamoResultsPage, Inventory, InventoryAttribute, InventoryDao,
+    InventoryFilter, InventoryRepository, InventoryStatus, PageTokenCodec, SortOrder,
+};
 use lambda_runtime::{Error, LambdaEvent};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tokio::main as tokio_main;
 use uuid::Uuid;
 
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+enum Scope {
+    #[default]
+    Vessel,
+    // results miss records written before CustomerIndex was introduced until the backfill runs (see db.md)
+    Customer,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct Request {
     customer_id: Uuid,
-    vessel_id: Uuid,
+    vessel_id: Option<Uuid>,
+    #[serde(default)]
+    scope: Scope,
+    inventory_type: Option<String>,
+    status: Option<InventoryStatus>,
+    #[serde(default)]
+    include_deleted: bool,
     page_token: Option<String>,
+    limit: Option<u32>,
+    order: Option<SortOrder>,
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct InventoryResponse {
+    vessel_id: Uuid,
     inventory_type: String,
     inventory_id: String,
     serial_number: Option<String>,
     aws_instance_id: Option<String>,
+    attributes: HashMap<String, InventoryAttribute>,
+    status: InventoryStatus,
     created_at: DateTime<FixedOffset>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deleted_at: Option<DateTime<FixedOffset>>,
+    version: u64,
 }
 
 #[derive(Serialize)]
@@ -41,11 +68,16 @@ struct Response {
 impl From<Inventory> for InventoryResponse {
     fn from(model: Inventory) -> Self {
         Self {
+            vessel_id: model.vessel_id,
             inventory_type: model.inventory_type,
             inventory_id: model.inventory_id,
             serial_number: model.serial_number,
             aws_instance_id: model.aws_instance_id,
+            attributes: model.attributes,
+            status: model.status,
             created_at: model.created_at,
+            deleted_at: model.deleted_at,
+            version: model.version,
         }
     }
 }
@@ -59,27 +91,73 @@ impl From<DynamoResultsPage<Inventory, String>> for Response {
     }
 }
 
+async fn handle(
+    repository: &dyn InventoryRepository,
+    codec: &PageTokenCodec,
+    request: Request,
+) -> Result<Response, ApiError> {
+    if let Some(inventory_type) = &request.inventory_type {
+        validate_identifier("inventoryType", inventory_type)?;
+    }
+
+    let order = request.order.unwrap_or_default();
+    // token continues only the listing it was issued for - vessel partition is already part of the token itself
+    let scope = format!(
+        "{:?}:{:?}:{:?}:{}:{order:?}",
+        request.scope, request.inventory_type, request.status, request.include_deleted
+    );
+
+    let page_token = request
+        .page_token
+        .map(|page_token| codec.decode(&scope, &page_token))
+        .transpose()?;
+
+    let filter = InventoryFilter {
+        inventory_type: request.inventory_type,
+        status: request.status,
+        include_deleted: request.include_deleted,
+    };
+
+    let page = match (request.scope, request.vessel_id) {
+        (Scope::Customer, _) => {
+            repository
+                .list_customer_inventory(request.customer_id, filter, page_token, request.limit, order)
+                .await
+        }
+        (Scope::Vessel, Some(vessel_id)) => {
+            repository
+                .list_inventory(request.customer_id, vessel_id, filter, page_token, request.limit, order)
+                .await
+        }
+        (Scope::Vessel, None) => Err(ApiError::VesselIdRequired),
+    }?;
+
+    Ok(Response::from(DynamoResultsPage {
+        items: page.items,
+        last_evaluated_key: page
+            .last_evaluated_key
+            .map(|page_token| codec.encode(&scope, &page_token)),
+    }))
+}
+
 #[tokio_main]
 async fn main() -> Result<(), Error> {
     let dao = &InventoryDao::load_from_env().await?;
+    let codec = &PageTokenCodec::load_from_env()?;
 
-    run_lambda!(move |event: LambdaEvent<Request>| async move {
-        dao.list_inventory(
-            event.payload.customer_id,
-            event.payload.vessel_id,
-            event.payload.page_token,
-        )
-        .await
-        .map(Response::from)
-    })
+    run_lambda!(move |event: LambdaEvent<Request>| handle(dao, codec, event.payload))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{InventoryResponse, Request, Response};
+    use crate::{handle, InventoryResponse, Request, Response, Scope};
     use chrono::{FixedOffset, TimeZone, Utc};
-    use inventory_core::{DynamoResultsPage, Inventory};
+    use inventory_core::{
+        ApiError, DynamoResultsPage, Inventory, InventoryStatus, MemoryRepository, PageTokenCodec, SortOrder,
+    };
     use serde_json::{from_str, to_string};
+    use std::collections::HashMap;
+    use tokio::test as tokio_test;
     use uuid::{uuid, Uuid};
 
     const CUSTOMER_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
@@ -88,6 +166,54 @@ mod tests {
     const INVENTORY_ID: &str = "0";
     const SERIAL_NUMBER: &str = "q1w2e3r4";
     const PAGE_TOKEN: &str = "abc";
+    const OTHER_VESSEL_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000002");
+
+    fn inventory(vessel_id: Uuid, inventory_type: &str, inventory_id: &str, status: InventoryStatus) -> Inventory {
+        Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id,
+            inventory_type: inventory_type.to_string(),
+            inventory_id: inventory_id.to_string(),
+            serial_number: None,
+            aws_instance_id: None,
+            attributes: HashMap::new(),
+            status,
+            status_change: None,
+            created_at: Utc
+                .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+                .unwrap()
+                .with_timezone(&FixedOffset::east_opt(3600).unwrap()),
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 1,
+        }
+    }
+
+    fn repository() -> MemoryRepository {
+        MemoryRepository::from(vec![
+            inventory(VESSEL_ID, INVENTORY_TYPE, "0", InventoryStatus::InService),
+            inventory(VESSEL_ID, INVENTORY_TYPE, "1", InventoryStatus::Faulty),
+            inventory(VESSEL_ID, "radar", "0", InventoryStatus::InService),
+            inventory(OTHER_VESSEL_ID, INVENTORY_TYPE, "2", InventoryStatus::InService),
+        ])
+    }
+
+    fn codec() -> PageTokenCodec {
+        PageTokenCodec::new(b"secret".to_vec())
+    }
+
+    fn request(json: &str) -> Request {
+        from_str(&format!("{{\"customerId\":\"{CUSTOMER_ID}\",{json}}}")).unwrap()
+    }
+
+    fn ids(response: &Response) -> Vec<String> {
+        response
+            .inventory
+            .iter()
+            .map(|inventory| format!("{}:{}", inventory.inventory_type, inventory.inventory_id))
+            .collect()
+    }
 
     #[test]
     fn deserialize_request() {
@@ -96,8 +222,65 @@ mod tests {
         let request: Request = from_str(&input).unwrap();
 
         assert_eq!(CUSTOMER_ID, request.customer_id);
-        assert_eq!(VESSEL_ID, request.vessel_id);
+        assert_eq!(Some(VESSEL_ID), request.vessel_id);
+        assert_eq!(Scope::Vessel, request.scope);
         assert_eq!(Some(PAGE_TOKEN.to_string()), request.page_token);
+        assert!(request.inventory_type.is_none());
+        assert!(request.status.is_none());
+        assert!(!request.include_deleted);
+        assert!(request.limit.is_none());
+        assert!(request.order.is_none());
+    }
+
+    #[test]
+    fn deserialize_request_limit_order() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"limit\":10,\"order\":\"desc\"}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(Some(10), request.limit);
+        assert_eq!(Some(SortOrder::Desc), request.order);
+    }
+
+    #[test]
+    fn deserialize_request_customer_scope() {
+        let input = format!("{{\"customerId\":\"{CUSTOMER_ID}\",\"scope\":\"customer\"}}");
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(CUSTOMER_ID, request.customer_id);
+        assert_eq!(Scope::Customer, request.scope);
+        assert!(request.vessel_id.is_none());
+    }
+
+    #[test]
+    fn deserialize_request_by_type() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\"}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(Some(VESSEL_ID), request.vessel_id);
+        assert_eq!(Some(INVENTORY_TYPE.to_string()), request.inventory_type);
+        assert!(request.page_token.is_none());
+    }
+
+    #[test]
+    fn deserialize_request_by_status() {
+        let input =
+            format!("{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"status\":\"underRepair\"}}");
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(Some(InventoryStatus::UnderRepair), request.status);
+    }
+
+    #[test]
+    fn deserialize_request_include_deleted() {
+        let input =
+            format!("{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"includeDeleted\":true}}");
+        let request: Request = from_str(&input).unwrap();
+
+        assert!(request.include_deleted);
     }
 
     #[test]
@@ -106,7 +289,7 @@ mod tests {
         let request: Request = from_str(&input).unwrap();
 
         assert_eq!(CUSTOMER_ID, request.customer_id);
-        assert_eq!(VESSEL_ID, request.vessel_id);
+        assert_eq!(Some(VESSEL_ID), request.vessel_id);
         assert!(request.page_token.is_none());
     }
 
@@ -119,18 +302,26 @@ mod tests {
 
         let output = to_string(&Response {
             inventory: vec![InventoryResponse {
+                vessel_id: VESSEL_ID,
                 inventory_type: INVENTORY_TYPE.to_string(),
                 inventory_id: INVENTORY_ID.to_string(),
                 serial_number: Some(SERIAL_NUMBER.to_string()),
                 aws_instance_id: None,
+                attributes: HashMap::new(),
+                status: InventoryStatus::InService,
                 created_at,
+                deleted_at: None,
+                version: 1,
             }],
             page_token: Some(PAGE_TOKEN.to_string()),
         })
         .unwrap();
 
+        assert!(output.contains(&format!("\"{VESSEL_ID}\"")));
         assert!(output.contains(&format!("\"{SERIAL_NUMBER}\"")));
         assert!(output.contains(&format!("\"{PAGE_TOKEN}\"")));
+        assert!(output.contains("\"status\":\"inService\""));
+        assert!(!output.contains("\"deletedAt\""));
     }
 
     #[test]
@@ -142,11 +333,16 @@ mod tests {
 
         let output = to_string(&Response {
             inventory: vec![InventoryResponse {
+                vessel_id: VESSEL_ID,
                 inventory_type: INVENTORY_TYPE.to_string(),
                 inventory_id: INVENTORY_ID.to_string(),
                 serial_number: None,
                 aws_instance_id: None,
+                attributes: HashMap::new(),
+                status: InventoryStatus::InService,
                 created_at,
+                deleted_at: None,
+                version: 1,
             }],
             page_token: None,
         })
@@ -169,7 +365,14 @@ mod tests {
             inventory_id: INVENTORY_ID.to_string(),
             serial_number: Some(SERIAL_NUMBER.to_string()),
             aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
             created_at,
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 0,
         });
 
         assert_eq!(Some(SERIAL_NUMBER.to_string()), response.serial_number);
@@ -190,7 +393,14 @@ mod tests {
                 inventory_id: INVENTORY_ID.to_string(),
                 serial_number: Some(SERIAL_NUMBER.to_string()),
                 aws_instance_id: None,
+                attributes: HashMap::new(),
+                status: InventoryStatus::InService,
+                status_change: None,
                 created_at,
+                deleted_at: None,
+                deleted_by: None,
+                transferred_at: None,
+                version: 0,
             }],
             last_evaluated_key: Some(PAGE_TOKEN.to_string()),
         });
@@ -199,4 +409,187 @@ mod tests {
         assert_eq!(Some(SERIAL_NUMBER.to_string()), response.inventory[0].serial_number);
         assert_eq!(Some(PAGE_TOKEN.to_string()), response.page_token);
     }
+
+    #[tokio_test]
+    async fn handle_vessel_pages() {
+        let repository = repository();
+
+        let response = handle(
+            &repository,
+            &codec(),
+            request(&format!("\"vesselId\":\"{VESSEL_ID}\",\"limit\":2")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(vec!["pc:0", "pc:1"], ids(&response));
+        let page_token = response.page_token.unwrap();
+
+        let response = handle(
+            &repository,
+            &codec(),
+            request(&format!(
+                "\"vesselId\":\"{VESSEL_ID}\",\"limit\":2,\"pageToken\":\"{page_token}\""
+            )),
+        )
+        .await
+        .unwrap();
+        assert_eq!(vec!["radar:0"], ids(&response));
+        assert!(response.page_token.is_none());
+    }
+
+    #[tokio_test]
+    async fn handle_vessel_descending() {
+        let response = handle(
+            &repository(),
+            &codec(),
+            request(&format!("\"vesselId\":\"{VESSEL_ID}\",\"order\":\"desc\"")),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(vec!["radar:0", "pc:1", "pc:0"], ids(&response));
+    }
+
+    #[tokio_test]
+    async fn handle_filtered() {
+        let response = handle(
+            &repository(),
+            &codec(),
+            request(&format!(
+                "\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"status\":\"faulty\""
+            )),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(vec!["pc:1"], ids(&response));
+    }
+
+    #[tokio_test]
+    async fn handle_customer_scope() {
+        let response = handle(
+            &repository(),
+            &codec(),
+            request(&format!(
+                "\"scope\":\"customer\",\"inventoryType\":\"{INVENTORY_TYPE}\""
+            )),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(vec!["pc:0", "pc:1", "pc:2"], ids(&response));
+        assert_eq!(OTHER_VESSEL_ID, response.inventory[2].vessel_id);
+    }
+
+    #[tokio_test]
+    async fn handle_vessel_id_required() {
+        match handle(&repository(), &codec(), request("\"scope\":\"vessel\"")).await {
+            Err(ApiError::VesselIdRequired) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_invalid_page_token() {
+        match handle(
+            &repository(),
+            &codec(),
+            request(&format!("\"vesselId\":\"{VESSEL_ID}\",\"pageToken\":\"{PAGE_TOKEN}\"")),
+        )
+        .await
+        {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_page_token_of_other_vessel() {
+        let repository = repository();
+        let response = handle(
+            &repository,
+            &codec(),
+            request(&format!("\"vesselId\":\"{VESSEL_ID}\",\"limit\":1")),
+        )
+        .await
+        .unwrap();
+        let page_token = response.page_token.unwrap();
+
+        match handle(
+            &repository,
+            &codec(),
+            request(&format!(
+                "\"vesselId\":\"{OTHER_VESSEL_ID}\",\"pageToken\":\"{page_token}\""
+            )),
+        )
+        .await
+        {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_page_token_of_other_filter() {
+        let repository = repository();
+        let response = handle(
+            &repository,
+            &codec(),
+            request(&format!("\"vesselId\":\"{VESSEL_ID}\",\"limit\":1")),
+        )
+        .await
+        .unwrap();
+        let page_token = response.page_token.unwrap();
+
+        match handle(
+            &repository,
+            &codec(),
+            request(&format!(
+                "\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"radar\",\"pageToken\":\"{page_token}\""
+            )),
+        )
+        .await
+        {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_page_token_of_other_scope() {
+        let repository = repository();
+        let response = handle(
+            &repository,
+            &codec(),
+            request(&format!("\"vesselId\":\"{VESSEL_ID}\",\"limit\":1")),
+        )
+        .await
+        .unwrap();
+        let page_token = response.page_token.unwrap();
+
+        match handle(
+            &repository,
+            &codec(),
+            request(&format!("\"scope\":\"customer\",\"pageToken\":\"{page_token}\"")),
+        )
+        .await
+        {
+            Err(ApiError::InvalidPageToken) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_invalid_type() {
+        match handle(
+            &repository(),
+            &codec(),
+            request(&format!("\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"pc:0\"")),
+        )
+        .await
+        {
+            Err(ApiError::ValidationFailed { field, .. }) => assert_eq!("inventoryType", field),
+            _ => panic!("Invalid error type."),
+        }
+    }
 }
@@ -0,0 +1,233 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+#![feature(future_join)]
+
+use chrono::{DateTime, FixedOffset};
+use inventory_core::{run_lambda, validate_value, ApiError, Inventory, InventoryDao, InventoryRepository};
+use lambda_runtime::{Error, LambdaEvent};
+use serde::{Deserialize, Serialize};
+use tokio::main as tokio_main;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    customer_id: Uuid,
+    serial_number: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InventoryResponse {
+    vessel_id: Uuid,
+    inventory_type: String,
+    inventory_id: String,
+    serial_number: Option<String>,
+    aws_instance_id: Option<String>,
+    created_at: DateTime<FixedOffset>,
+    version: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    inventory: Vec<InventoryResponse>,
+}
+
+impl From<Inventory> for InventoryResponse {
+    fn from(model: Inventory) -> Self {
+        Self {
+            vessel_id: model.vessel_id,
+            inventory_type: model.inventory_type,
+            inventory_id: model.inventory_id,
+            serial_number: model.serial_number,
+            aws_instance_id: model.aws_instance_id,
+            created_at: model.created_at,
+            version: model.version,
+        }
+    }
+}
+
+impl From<Vec<Inventory>> for Response {
+    fn from(value: Vec<Inventory>) -> Self {
+        Self {
+            inventory: value.into_iter().map(InventoryResponse::from).collect(),
+        }
+    }
+}
+
+async fn handle(repository: &dyn InventoryRepository, request: Request) -> Result<Response, ApiError> {
+    validate_value("serialNumber", &request.serial_number)?;
+
+    repository
+        .find_by_serial_number(request.customer_id, request.serial_number)
+        .await
+        .map(Response::from)
+        .map_err(ApiError::from)
+}
+
+#[tokio_main]
+async fn main() -> Result<(), Error> {
+    let dao = &InventoryDao::load_from_env().await?;
+
+    run_lambda!(move |event: LambdaEvent<Request>| handle(dao, event.payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{handle, InventoryResponse, Request, Response};
+    use chrono::{FixedOffset, TimeZone, Utc};
+    use inventory_core::{ApiError, FailingRepository, Inventory, InventoryStatus, MemoryRepository};
+    use serde_json::{from_str, to_string};
+    use std::collections::HashMap;
+    use tokio::test as tokio_test;
+    use uuid::{uuid, Uuid};
+
+    const CUSTOMER_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
+    const VESSEL_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000001");
+    const INVENTORY_TYPE: &str = "radar";
+    const INVENTORY_ID: &str = "0";
+    const SERIAL_NUMBER: &str = "q1w2e3r4";
+
+    fn inventory(vessel_id: Uuid, inventory_id: &str) -> Inventory {
+        Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: inventory_id.to_string(),
+            serial_number: Some(SERIAL_NUMBER.to_string()),
+            aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at: Utc::now().fixed_offset(),
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 1,
+        }
+    }
+
+    fn request(serial_number: &str) -> Request {
+        from_str(&format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"serialNumber\":\"{serial_number}\"}}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn deserialize_request() {
+        let input = format!("{{\"customerId\":\"{CUSTOMER_ID}\",\"serialNumber\":\"{SERIAL_NUMBER}\"}}");
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(CUSTOMER_ID, request.customer_id);
+        assert_eq!(SERIAL_NUMBER, request.serial_number);
+    }
+
+    #[test]
+    fn serialize_response() {
+        let created_at = Utc
+            .with_ymd_and_hms(2009, 3, 23, 10, 0, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+
+        let output = to_string(&Response {
+            inventory: vec![InventoryResponse {
+                vessel_id: VESSEL_ID,
+                inventory_type: INVENTORY_TYPE.to_string(),
+                inventory_id: INVENTORY_ID.to_string(),
+                serial_number: Some(SERIAL_NUMBER.to_string()),
+                aws_instance_id: None,
+                created_at,
+                version: 1,
+            }],
+        })
+        .unwrap();
+
+        assert!(output.contains(&format!("\"{VESSEL_ID}\"")));
+        assert!(output.contains(&format!("\"{SERIAL_NUMBER}\"")));
+    }
+
+    #[test]
+    fn response_from_model() {
+        let created_at = Utc
+            .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(3600).unwrap());
+
+        let response = Response::from(vec![Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id: VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: Some(SERIAL_NUMBER.to_string()),
+            aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at,
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 2,
+        }]);
+
+        assert_eq!(1, response.inventory.len());
+        assert_eq!(VESSEL_ID, response.inventory[0].vessel_id);
+        assert_eq!(INVENTORY_ID, response.inventory[0].inventory_id);
+    }
+
+    #[tokio_test]
+    async fn handle_locate() {
+        let repository = MemoryRepository::from(vec![inventory(VESSEL_ID, INVENTORY_ID)]);
+
+        let response = handle(&repository, request(SERIAL_NUMBER)).await.unwrap();
+        assert_eq!(1, response.inventory.len());
+        assert_eq!(VESSEL_ID, response.inventory[0].vessel_id);
+        assert_eq!(INVENTORY_ID, response.inventory[0].inventory_id);
+    }
+
+    #[tokio_test]
+    async fn handle_locate_multiple() {
+        let other_vessel = uuid!("00000000-0000-0000-0000-000000000002");
+        let repository = MemoryRepository::from(vec![
+            inventory(VESSEL_ID, INVENTORY_ID),
+            inventory(other_vessel, INVENTORY_ID),
+            Inventory {
+                deleted_at: Some(Utc::now().fixed_offset()),
+                ..inventory(VESSEL_ID, "1")
+            },
+        ]);
+
+        // duplicated serial numbers are all reported, deleted inventory is skipped
+        let response = handle(&repository, request(SERIAL_NUMBER)).await.unwrap();
+        assert_eq!(2, response.inventory.len());
+        assert_eq!(VESSEL_ID, response.inventory[0].vessel_id);
+        assert_eq!(other_vessel, response.inventory[1].vessel_id);
+    }
+
+    #[tokio_test]
+    async fn handle_locate_none() {
+        let repository = MemoryRepository::from(vec![inventory(VESSEL_ID, INVENTORY_ID)]);
+
+        let response = handle(&repository, request("z0x9c8")).await.unwrap();
+        assert!(response.inventory.is_empty());
+    }
+
+    #[tokio_test]
+    async fn handle_locate_missing_index() {
+        match handle(
+            &FailingRepository::new("ResourceNotFoundException"),
+            request(SERIAL_NUMBER),
+        )
+        .await
+        {
+            Err(ApiError::ResourceNotFound(_)) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+#![feature(future_join)]
+
+use inventory_core::{run_lambda, validate_value, ApiError, Inventory, InventoryDao, InventoryRepository};
+use lambda_runtime::{Error, LambdaEvent};
+use serde::{Deserialize, Serialize};
+use tokio::main as tokio_main;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    aws_instance_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    customer_id: Uuid,
+    vessel_id: Uuid,
+    inventory_type: String,
+    inventory_id: String,
+}
+
+impl From<Inventory> for Response {
+    fn from(model: Inventory) -> Self {
+        Self {
+            customer_id: model.customer_id,
+            vessel_id: model.vessel_id,
+            inventory_type: model.inventory_type,
+            inventory_id: model.inventory_id,
+        }
+    }
+}
+
+async fn handle(repository: &dyn InventoryRepository, request: Request) -> Result<Response, ApiError> {
+    validate_value("awsInstanceId", &request.aws_instance_id)?;
+
+    match repository
+        .get_by_aws_instance_id(request.aws_instance_id.clone())
+        .await?
+    {
+        None => Err(ApiError::AwsInstanceNotFound(request.aws_instance_id)),
+        Some(inventory) => Ok(Response::from(inventory)),
+    }
+}
+
+#[tokio_main]
+async fn main() -> Result<(), Error> {
+    let dao = &InventoryDao::load_from_env().await?;
+
+    run_lambda!(move |event: LambdaEvent<Request>| handle(dao, event.payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{handle, Request, Response};
+    use chrono::{FixedOffset, TimeZone, Utc};
+    use inventory_core::{ApiError, FailingRepository, Inventory, InventoryStatus, MemoryRepository};
+    use serde_json::{from_str, to_string};
+    use std::collections::HashMap;
+    use tokio::test as tokio_test;
+    use uuid::{uuid, Uuid};
+
+    const CUSTOMER_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
+    const VESSEL_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000001");
+    const INVENTORY_TYPE: &str = "pc";
+    const INVENTORY_ID: &str = "0";
+    const AWS_INSTANCE_ID: &str = "mi-0123";
+
+    fn repository(deleted: bool) -> MemoryRepository {
+        let created_at = Utc
+            .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(3600).unwrap());
+
+        MemoryRepository::from(vec![Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id: VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: None,
+            aws_instance_id: Some(AWS_INSTANCE_ID.to_string()),
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at,
+            deleted_at: deleted.then_some(created_at),
+            deleted_by: None,
+            transferred_at: None,
+            version: 1,
+        }])
+    }
+
+    fn request(aws_instance_id: &str) -> Request {
+        from_str(&format!("{{\"awsInstanceId\":\"{aws_instance_id}\"}}")).unwrap()
+    }
+
+    #[test]
+    fn deserialize_request() {
+        let input = format!("{{\"awsInstanceId\":\"{AWS_INSTANCE_ID}\"}}");
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(AWS_INSTANCE_ID, request.aws_instance_id);
+    }
+
+    #[test]
+    fn serialize_response() {
+        let output = to_string(&Response {
+            customer_id: CUSTOMER_ID,
+            vessel_id: VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+        })
+        .unwrap();
+
+        assert!(output.contains(&format!("\"customerId\":\"{CUSTOMER_ID}\"")));
+        assert!(output.contains(&format!("\"vesselId\":\"{VESSEL_ID}\"")));
+        assert!(output.contains(&format!("\"inventoryType\":\"{INVENTORY_TYPE}\"")));
+        assert!(output.contains(&format!("\"inventoryId\":\"{INVENTORY_ID}\"")));
+    }
+
+    #[test]
+    fn response_from_model() {
+        let created_at = Utc
+            .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(3600).unwrap());
+
+        let response = Response::from(Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id: VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: None,
+            aws_instance_id: Some(AWS_INSTANCE_ID.to_string()),
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at,
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 1,
+        });
+
+        assert_eq!(CUSTOMER_ID, response.customer_id);
+        assert_eq!(VESSEL_ID, response.vessel_id);
+        assert_eq!(INVENTORY_TYPE, response.inventory_type);
+        assert_eq!(INVENTORY_ID, response.inventory_id);
+    }
+
+    #[tokio_test]
+    async fn handle_resolve() {
+        let response = handle(&repository(false), request(AWS_INSTANCE_ID)).await.unwrap();
+
+        assert_eq!(CUSTOMER_ID, response.customer_id);
+        assert_eq!(VESSEL_ID, response.vessel_id);
+        assert_eq!(INVENTORY_TYPE, response.inventory_type);
+        assert_eq!(INVENTORY_ID, response.inventory_id);
+    }
+
+    #[tokio_test]
+    async fn handle_resolve_unknown() {
+        match handle(&repository(false), request("mi-4567")).await {
+            Err(ApiError::AwsInstanceNotFound(aws_instance_id)) => assert_eq!("mi-4567", aws_instance_id),
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_resolve_deleted() {
+        match handle(&repository(true), request(AWS_INSTANCE_ID)).await {
+            Err(ApiError::AwsInstanceNotFound(aws_instance_id)) => assert_eq!(AWS_INSTANCE_ID, aws_instance_id),
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_resolve_missing_index() {
+        match handle(
+            &FailingRepository::new("ResourceNotFoundException"),
+            request(AWS_INSTANCE_ID),
+        )
+        .await
+        {
+            Err(ApiError::ResourceNotFound(_)) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+}
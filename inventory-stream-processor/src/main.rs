@@ -0,0 +1,143 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+#![feature(future_join)]
+
+use inventory_core::{load_event_sink_from_env, run_lambda, EventSink, InventoryEvent, RuntimeError, StreamRecord};
+use lambda_runtime::{Error, LambdaEvent};
+use log::error;
+use serde::{Deserialize, Serialize};
+use tokio::main as tokio_main;
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(rename = "Records")]
+    records: Vec<StreamRecord>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchItemFailure {
+    item_identifier: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    batch_item_failures: Vec<BatchItemFailure>,
+}
+
+async fn publish(sink: &dyn EventSink, record: &StreamRecord) -> Result<(), RuntimeError> {
+    match InventoryEvent::from_stream_record(record)? {
+        Some(event) => sink.publish(&event).await,
+        None => Ok(()),
+    }
+}
+
+// records need to be published in order - first failure stops the batch and it is retried from that record
+async fn process(sink: &dyn EventSink, records: Vec<StreamRecord>) -> Response {
+    for record in records {
+        if let Err(error) = publish(sink, &record).await {
+            error!("Failed to publish record {}: {error:?}", record.event_id);
+
+            return Response {
+                batch_item_failures: vec![BatchItemFailure {
+                    item_identifier: record.dynamodb.sequence_number,
+                }],
+            };
+        }
+    }
+
+    Response {
+        batch_item_failures: vec![],
+    }
+}
+
+#[tokio_main]
+async fn main() -> Result<(), Error> {
+    let sink = load_event_sink_from_env().await?;
+    let sink = sink.as_ref();
+
+    run_lambda!(move |event: LambdaEvent<Request>| async move {
+        Ok::<Response, RuntimeError>(process(sink, event.payload.records).await)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{process, Request, Response};
+    use inventory_core::{InventoryEventType, MemorySink};
+    use serde_json::{from_str, to_string};
+    use tokio::test as tokio_test;
+
+    static KEYS: &str = r#"{"customerAndVesselId":{"S":"00000000-0000-0000-0000-000000000000:00000000-0000-0000-0000-000000000001"},"inventoryKey":{"S":"pc:012"}}"#;
+    static MARKER_KEYS: &str =
+        r#"{"customerAndVesselId":{"S":"awsInstanceId:i-0123"},"inventoryKey":{"S":"awsInstanceId"}}"#;
+    static IMAGE: &str = r#"{"customerId":{"S":"00000000-0000-0000-0000-000000000000"},"vesselId":{"S":"00000000-0000-0000-0000-000000000001"},"inventoryType":{"S":"pc"},"inventoryId":{"S":"012"},"createdAt":{"S":"2011-01-30T14:58:00+01:00"},"version":{"N":"1"}}"#;
+
+    fn record(event_id: &str, event_name: &str, keys: &str, new_image: &str, sequence_number: &str) -> String {
+        format!(
+            "{{\"eventID\":\"{event_id}\",\"eventName\":\"{event_name}\",\"dynamodb\":{{\"ApproximateCreationDateTime\":1600000000,\"Keys\":{keys},\"NewImage\":{new_image},\"SequenceNumber\":\"{sequence_number}\"}}}}"
+        )
+    }
+
+    #[test]
+    fn deserialize_request() {
+        let input = format!("{{\"Records\":[{}]}}", record("abc", "INSERT", KEYS, IMAGE, "111"));
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(1, request.records.len());
+        assert_eq!("abc", request.records[0].event_id);
+        assert_eq!("111", request.records[0].dynamodb.sequence_number);
+    }
+
+    #[test]
+    fn serialize_response() {
+        let output = to_string(&Response {
+            batch_item_failures: vec![],
+        })
+        .unwrap();
+
+        assert_eq!("{\"batchItemFailures\":[]}", output);
+    }
+
+    #[tokio_test]
+    async fn process_records() {
+        let input = format!(
+            "{{\"Records\":[{},{}]}}",
+            record("abc", "INSERT", KEYS, IMAGE, "111"),
+            record("def", "INSERT", MARKER_KEYS, MARKER_KEYS, "112"),
+        );
+        let request: Request = from_str(&input).unwrap();
+        let sink = MemorySink::default();
+
+        let response = process(&sink, request.records).await;
+
+        assert!(response.batch_item_failures.is_empty());
+        let events = sink.events();
+        assert_eq!(1, events.len());
+        assert_eq!("abc", events[0].event_id);
+        assert_eq!(InventoryEventType::InventoryCreated, events[0].event_type);
+    }
+
+    #[tokio_test]
+    async fn process_malformed_record() {
+        let input = format!(
+            "{{\"Records\":[{},{},{}]}}",
+            record("abc", "INSERT", KEYS, IMAGE, "111"),
+            record("def", "INSERT", KEYS, KEYS, "112"),
+            record("ghi", "INSERT", KEYS, IMAGE, "113"),
+        );
+        let request: Request = from_str(&input).unwrap();
+        let sink = MemorySink::default();
+
+        let response = process(&sink, request.records).await;
+
+        assert_eq!(1, response.batch_item_failures.len());
+        assert_eq!("112", response.batch_item_failures[0].item_identifier);
+        assert_eq!(1, sink.events().len());
+    }
+}
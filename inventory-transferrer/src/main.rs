@@ -0,0 +1,302 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+#![feature(future_join)]
+
+use chrono::{DateTime, FixedOffset};
+use inventory_core::{
+    run_lambda, validate_inventory_key, ApiError, Inventory, InventoryAttribute, InventoryDao, InventoryRepository,
+    InventoryStatus, StatusChange,
+};
+use lambda_runtime::{Error, LambdaEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::main as tokio_main;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    customer_id: Uuid,
+    vessel_id: Uuid,
+    inventory_type: String,
+    inventory_id: String,
+    target_customer_id: Uuid,
+    target_vessel_id: Uuid,
+    expected_version: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    customer_id: Uuid,
+    vessel_id: Uuid,
+    inventory_type: String,
+    inventory_id: String,
+    serial_number: Option<String>,
+    aws_instance_id: Option<String>,
+    attributes: HashMap<String, InventoryAttribute>,
+    status: InventoryStatus,
+    status_change: Option<StatusChange>,
+    created_at: DateTime<FixedOffset>,
+    transferred_at: Option<DateTime<FixedOffset>>,
+    version: u64,
+}
+
+impl From<Inventory> for Response {
+    fn from(model: Inventory) -> Self {
+        Self {
+            customer_id: model.customer_id,
+            vessel_id: model.vessel_id,
+            inventory_type: model.inventory_type,
+            inventory_id: model.inventory_id,
+            serial_number: model.serial_number,
+            aws_instance_id: model.aws_instance_id,
+            attributes: model.attributes,
+            status: model.status,
+            status_change: model.status_change,
+            created_at: model.created_at,
+            transferred_at: model.transferred_at,
+            version: model.version,
+        }
+    }
+}
+
+// inventory can only be moved within its owner's fleet
+fn validate_target(request: &Request) -> Result<(), ApiError> {
+    if request.target_customer_id == request.customer_id {
+        Ok(())
+    } else {
+        Err(ApiError::TransferAcrossCustomers)
+    }
+}
+
+async fn handle(repository: &dyn InventoryRepository, request: Request) -> Result<Response, ApiError> {
+    validate_inventory_key(&request.inventory_type, &request.inventory_id)?;
+    validate_target(&request)?;
+
+    repository
+        .transfer_inventory(
+            request.customer_id,
+            request.vessel_id,
+            request.inventory_type,
+            request.inventory_id,
+            request.target_vessel_id,
+            request.expected_version,
+        )
+        .await
+        .map(Response::from)
+}
+
+#[tokio_main]
+async fn main() -> Result<(), Error> {
+    let dao = &InventoryDao::load_from_env().await?;
+
+    run_lambda!(move |event: LambdaEvent<Request>| handle(dao, event.payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{handle, validate_target, Request, Response};
+    use chrono::{FixedOffset, TimeZone, Utc};
+    use inventory_core::{ApiError, Inventory, InventoryRepository, InventoryStatus, MemoryRepository};
+    use serde_json::{from_str, to_string};
+    use std::collections::HashMap;
+    use tokio::test as tokio_test;
+    use uuid::{uuid, Uuid};
+
+    const CUSTOMER_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
+    const VESSEL_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000001");
+    const TARGET_VESSEL_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000002");
+    const OTHER_CUSTOMER_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000003");
+    const INVENTORY_TYPE: &str = "pc";
+    const INVENTORY_ID: &str = "test0";
+    const SERIAL_NUMBER: &str = "abc";
+
+    fn repository() -> MemoryRepository {
+        MemoryRepository::from(vec![Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id: VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: Some(SERIAL_NUMBER.to_string()),
+            aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at: Utc
+                .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+                .unwrap()
+                .with_timezone(&FixedOffset::east_opt(3600).unwrap()),
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 2,
+        }])
+    }
+
+    fn request(target_customer_id: Uuid) -> Request {
+        from_str(&format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"targetCustomerId\":\"{target_customer_id}\",\"targetVesselId\":\"{TARGET_VESSEL_ID}\"}}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn deserialize_request() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"targetCustomerId\":\"{CUSTOMER_ID}\",\"targetVesselId\":\"{TARGET_VESSEL_ID}\"}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(CUSTOMER_ID, request.customer_id);
+        assert_eq!(VESSEL_ID, request.vessel_id);
+        assert_eq!(INVENTORY_TYPE, request.inventory_type);
+        assert_eq!(INVENTORY_ID, request.inventory_id);
+        assert_eq!(CUSTOMER_ID, request.target_customer_id);
+        assert_eq!(TARGET_VESSEL_ID, request.target_vessel_id);
+        assert!(request.expected_version.is_none());
+    }
+
+    #[test]
+    fn deserialize_request_full() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"targetCustomerId\":\"{OTHER_CUSTOMER_ID}\",\"targetVesselId\":\"{TARGET_VESSEL_ID}\",\"expectedVersion\":3}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(OTHER_CUSTOMER_ID, request.target_customer_id);
+        assert_eq!(Some(3), request.expected_version);
+    }
+
+    #[test]
+    fn deserialize_request_without_target_customer() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"targetVesselId\":\"{TARGET_VESSEL_ID}\"}}"
+        );
+
+        assert!(from_str::<Request>(&input).is_err());
+    }
+
+    #[test]
+    fn target_same_customer() {
+        assert!(validate_target(&request(CUSTOMER_ID)).is_ok());
+    }
+
+    #[test]
+    fn target_other_customer() {
+        match validate_target(&request(OTHER_CUSTOMER_ID)) {
+            Err(ApiError::TransferAcrossCustomers) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[tokio_test]
+    async fn handle_transfer() {
+        let repository = repository();
+
+        let response = handle(&repository, request(CUSTOMER_ID)).await.unwrap();
+        assert_eq!(TARGET_VESSEL_ID, response.vessel_id);
+        assert!(response.transferred_at.is_some());
+        assert_eq!(3, response.version);
+
+        assert!(repository
+            .get_inventory(
+                CUSTOMER_ID,
+                TARGET_VESSEL_ID,
+                INVENTORY_TYPE.into(),
+                INVENTORY_ID.into()
+            )
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio_test]
+    async fn handle_transfer_across_customers() {
+        let repository = repository();
+
+        match handle(&repository, request(OTHER_CUSTOMER_ID)).await {
+            Err(ApiError::TransferAcrossCustomers) => {}
+            _ => panic!("Invalid error type."),
+        }
+
+        // nothing was moved
+        let stored = repository
+            .get_inventory(CUSTOMER_ID, VESSEL_ID, INVENTORY_TYPE.into(), INVENTORY_ID.into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(2, stored.version);
+        assert!(stored.transferred_at.is_none());
+    }
+
+    #[test]
+    fn serialize_response() {
+        let created_at = Utc
+            .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(3600).unwrap());
+        let transferred_at = Utc
+            .with_ymd_and_hms(2019, 5, 12, 9, 30, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+
+        let output = to_string(&Response {
+            customer_id: CUSTOMER_ID,
+            vessel_id: TARGET_VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: Some(SERIAL_NUMBER.to_string()),
+            aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at,
+            transferred_at: Some(transferred_at),
+            version: 4,
+        })
+        .unwrap();
+
+        assert!(output.contains(&format!("\"vesselId\":\"{TARGET_VESSEL_ID}\"")));
+        assert!(output.contains(&format!("\"{SERIAL_NUMBER}\"")));
+        assert!(output.contains("\"createdAt\":\"2011-01-30T14:58:00+01:00\""));
+        assert!(output.contains("\"transferredAt\":\"2019-05-12T11:30:00+02:00\""));
+        assert!(output.contains("\"version\":4"));
+    }
+
+    #[test]
+    fn response_from_model() {
+        let created_at = Utc
+            .with_ymd_and_hms(2015, 7, 2, 1, 20, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+
+        let response = Response::from(Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id: TARGET_VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: Some(SERIAL_NUMBER.to_string()),
+            aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::Faulty,
+            status_change: None,
+            created_at,
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: Some(created_at),
+            version: 2,
+        });
+
+        assert_eq!(CUSTOMER_ID, response.customer_id);
+        assert_eq!(TARGET_VESSEL_ID, response.vessel_id);
+        assert_eq!(INVENTORY_ID, response.inventory_id);
+        assert_eq!(created_at, response.created_at);
+        assert_eq!(Some(created_at), response.transferred_at);
+        assert_eq!(2, response.version);
+    }
+}
@@ -0,0 +1,249 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+#![feature(future_join)]
+
+use chrono::Utc;
+use inventory_core::{
+    actor_of, run_lambda, validate_inventory_key, validate_optional_value, ApiError, Inventory, InventoryDao,
+    InventoryRepository, InventoryStatus, StatusChange,
+};
+use lambda_runtime::{Error, LambdaEvent};
+use serde::{Deserialize, Serialize};
+use tokio::main as tokio_main;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    customer_id: Uuid,
+    vessel_id: Uuid,
+    inventory_type: String,
+    inventory_id: String,
+    status: InventoryStatus,
+    reason: Option<String>,
+    expected_version: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    inventory_type: String,
+    inventory_id: String,
+    status: InventoryStatus,
+    status_change: Option<StatusChange>,
+    version: u64,
+}
+
+impl From<Inventory> for Response {
+    fn from(model: Inventory) -> Self {
+        Self {
+            inventory_type: model.inventory_type,
+            inventory_id: model.inventory_id,
+            status: model.status,
+            status_change: model.status_change,
+            version: model.version,
+        }
+    }
+}
+
+// change is attributed to the authenticated caller, never to the value claimed by the request
+async fn handle(
+    repository: &dyn InventoryRepository,
+    request: Request,
+    actor: Option<String>,
+) -> Result<Response, ApiError> {
+    validate_inventory_key(&request.inventory_type, &request.inventory_id)?;
+    validate_optional_value("reason", request.reason.as_ref())?;
+
+    repository
+        .transition_inventory(
+            request.customer_id,
+            request.vessel_id,
+            request.inventory_type,
+            request.inventory_id,
+            StatusChange {
+                status: request.status,
+                changed_at: Utc::now().fixed_offset(),
+                changed_by: actor,
+                reason: request.reason,
+            },
+            request.expected_version,
+        )
+        .await
+        .map(Response::from)
+}
+
+#[tokio_main]
+async fn main() -> Result<(), Error> {
+    let dao = &InventoryDao::load_from_env().await?;
+
+    run_lambda!(move |event: LambdaEvent<Request>| handle(dao, event.payload, actor_of(&event.context)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{handle, Request, Response};
+    use chrono::{FixedOffset, TimeZone, Utc};
+    use inventory_core::{ApiError, Inventory, InventoryRepository, InventoryStatus, MemoryRepository, StatusChange};
+    use serde_json::{from_str, to_string};
+    use std::collections::HashMap;
+    use tokio::test as tokio_test;
+    use uuid::{uuid, Uuid};
+
+    const CUSTOMER_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
+    const VESSEL_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000001");
+    const INVENTORY_TYPE: &str = "radar";
+    const INVENTORY_ID: &str = "test0";
+    const CHANGED_BY: &str = "technician";
+
+    fn repository() -> MemoryRepository {
+        MemoryRepository::from(vec![Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id: VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: None,
+            aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at: Utc::now().fixed_offset(),
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 1,
+        }])
+    }
+
+    fn request(status: &str) -> Request {
+        from_str(&format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"status\":\"{status}\",\"changedBy\":\"impostor\",\"reason\":\"Radar is down.\"}}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn deserialize_request() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"status\":\"underRepair\",\"reason\":\"Scheduled service.\"}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(CUSTOMER_ID, request.customer_id);
+        assert_eq!(VESSEL_ID, request.vessel_id);
+        assert_eq!(INVENTORY_TYPE, request.inventory_type);
+        assert_eq!(INVENTORY_ID, request.inventory_id);
+        assert_eq!(InventoryStatus::UnderRepair, request.status);
+        assert_eq!(Some("Scheduled service.".to_string()), request.reason);
+        assert!(request.expected_version.is_none());
+    }
+
+    #[test]
+    fn deserialize_request_version() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"status\":\"faulty\",\"expectedVersion\":3}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert!(request.reason.is_none());
+        assert_eq!(Some(3), request.expected_version);
+    }
+
+    #[test]
+    fn serialize_response() {
+        let changed_at = Utc
+            .with_ymd_and_hms(2019, 5, 12, 9, 30, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+
+        let output = to_string(&Response {
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            status: InventoryStatus::Faulty,
+            status_change: Some(StatusChange {
+                status: InventoryStatus::Faulty,
+                changed_at,
+                changed_by: Some(CHANGED_BY.to_string()),
+                reason: None,
+            }),
+            version: 2,
+        })
+        .unwrap();
+
+        assert!(output.contains("\"status\":\"faulty\""));
+        assert!(output.contains(&format!("\"changedBy\":\"{CHANGED_BY}\"")));
+        assert!(output.contains("\"changedAt\":\"2019-05-12T11:30:00+02:00\""));
+        assert!(!output.contains("\"reason\""));
+    }
+
+    #[test]
+    fn response_from_model() {
+        let created_at = Utc
+            .with_ymd_and_hms(2015, 7, 2, 1, 20, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+
+        let response = Response::from(Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id: VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: None,
+            aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::Installed,
+            status_change: None,
+            created_at,
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 4,
+        });
+
+        assert_eq!(INVENTORY_ID, response.inventory_id);
+        assert_eq!(InventoryStatus::Installed, response.status);
+        assert_eq!(4, response.version);
+    }
+
+    #[tokio_test]
+    async fn handle_transition() {
+        let repository = repository();
+
+        let response = handle(&repository, request("faulty"), Some(CHANGED_BY.into()))
+            .await
+            .unwrap();
+        assert_eq!(InventoryStatus::Faulty, response.status);
+        assert_eq!(2, response.version);
+
+        let status_change = repository
+            .get_inventory(CUSTOMER_ID, VESSEL_ID, INVENTORY_TYPE.into(), INVENTORY_ID.into())
+            .await
+            .unwrap()
+            .unwrap()
+            .status_change
+            .unwrap();
+        assert_eq!(InventoryStatus::Faulty, status_change.status);
+        assert_eq!(Some(CHANGED_BY.to_string()), status_change.changed_by);
+        assert_eq!(Some("Radar is down.".to_string()), status_change.reason);
+    }
+
+    #[tokio_test]
+    async fn handle_transition_anonymous() {
+        let response = handle(&repository(), request("faulty"), None).await.unwrap();
+
+        // claimed identity is never taken into account
+        assert!(response.status_change.unwrap().changed_by.is_none());
+    }
+
+    #[tokio_test]
+    async fn handle_transition_invalid() {
+        match handle(&repository(), request("ordered"), Some(CHANGED_BY.into())).await {
+            Err(ApiError::InvalidStatusTransition(InventoryStatus::InService, InventoryStatus::Ordered)) => {}
+            _ => panic!("Invalid error type."),
+        }
+    }
+}
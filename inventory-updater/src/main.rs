@@ -0,0 +1,336 @@
+/*
+ * This file is part of the IVMS Online.
+ *
+ * @copyright 2023 © by Rafał Wrzeszcz - Wrzasq.pl.
+ */
+
+#![feature(future_join)]
+
+use chrono::{DateTime, FixedOffset};
+use inventory_core::{
+    run_lambda, validate_attributes, validate_inventory_key, validate_optional_value, ApiError, Inventory,
+    InventoryAttribute, InventoryChanges, InventoryDao, InventoryRepository, TypeRegistry,
+};
+use lambda_runtime::{Error, LambdaEvent};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use tokio::main as tokio_main;
+use uuid::Uuid;
+
+// distinguishes between field not sent at all (`None`) and explicit `null` (`Some(None)`)
+fn deserialize_change<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    customer_id: Uuid,
+    vessel_id: Uuid,
+    inventory_type: String,
+    inventory_id: String,
+    #[serde(default, deserialize_with = "deserialize_change")]
+    serial_number: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_change")]
+    aws_instance_id: Option<Option<String>>,
+    attributes: Option<HashMap<String, InventoryAttribute>>,
+    expected_version: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    inventory_type: String,
+    inventory_id: String,
+    serial_number: Option<String>,
+    aws_instance_id: Option<String>,
+    created_at: DateTime<FixedOffset>,
+    version: u64,
+}
+
+impl From<Inventory> for Response {
+    fn from(model: Inventory) -> Self {
+        Self {
+            inventory_type: model.inventory_type,
+            inventory_id: model.inventory_id,
+            serial_number: model.serial_number,
+            aws_instance_id: model.aws_instance_id,
+            created_at: model.created_at,
+            version: model.version,
+        }
+    }
+}
+
+async fn handle(
+    repository: &dyn InventoryRepository,
+    registry: &TypeRegistry,
+    request: Request,
+) -> Result<Response, ApiError> {
+    validate_inventory_key(&request.inventory_type, &request.inventory_id)?;
+    validate_optional_value("serialNumber", request.serial_number.as_ref().and_then(Option::as_ref))?;
+    validate_optional_value(
+        "awsInstanceId",
+        request.aws_instance_id.as_ref().and_then(Option::as_ref),
+    )?;
+    if let Some(attributes) = &request.attributes {
+        validate_attributes(attributes)?;
+    }
+
+    let changes = InventoryChanges {
+        serial_number: request.serial_number,
+        aws_instance_id: request.aws_instance_id,
+        attributes: request.attributes,
+    };
+    registry.validate_changes(&request.inventory_type, &changes)?;
+
+    repository
+        .update_inventory(
+            request.customer_id,
+            request.vessel_id,
+            request.inventory_type,
+            request.inventory_id,
+            changes,
+            request.expected_version,
+        )
+        .await
+        .map(Response::from)
+}
+
+#[tokio_main]
+async fn main() -> Result<(), Error> {
+    let dao = &InventoryDao::load_from_env().await?;
+    let registry = &TypeRegistry::load_from_env()?;
+
+    run_lambda!(move |event: LambdaEvent<Request>| handle(dao, registry, event.payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{handle, Request, Response};
+    use chrono::{FixedOffset, TimeZone, Utc};
+    use inventory_core::{
+        ApiError, Inventory, InventoryAttribute, InventoryRepository, InventoryStatus, MemoryRepository, TypeRegistry,
+    };
+    use serde_json::{from_str, to_string};
+    use std::collections::HashMap;
+    use tokio::test as tokio_test;
+    use uuid::{uuid, Uuid};
+
+    const CUSTOMER_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000000");
+    const VESSEL_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000001");
+    const INVENTORY_TYPE: &str = "pc";
+    const INVENTORY_ID: &str = "test0";
+    const SERIAL_NUMBER: &str = "abc";
+    const AWS_INSTANCE_ID: &str = "mi-0123";
+
+    fn repository() -> MemoryRepository {
+        MemoryRepository::from(vec![Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id: VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: None,
+            aws_instance_id: Some(AWS_INSTANCE_ID.to_string()),
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at: Utc::now().fixed_offset(),
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 1,
+        }])
+    }
+
+    fn request(inventory_id: &str, changes: &str) -> Request {
+        from_str(&format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{inventory_id}\",{changes}}}"
+        ))
+        .unwrap()
+    }
+
+    fn assert_failure(result: Result<Response, ApiError>, expected_field: &str) {
+        match result {
+            Err(ApiError::ValidationFailed { field, .. }) => assert_eq!(expected_field, field),
+            _ => panic!("Invalid error type."),
+        }
+    }
+
+    #[test]
+    fn deserialize_request() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"serialNumber\":\"{SERIAL_NUMBER}\"}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(CUSTOMER_ID, request.customer_id);
+        assert_eq!(VESSEL_ID, request.vessel_id);
+        assert_eq!(INVENTORY_TYPE, request.inventory_type);
+        assert_eq!(INVENTORY_ID, request.inventory_id);
+        assert_eq!(Some(Some(SERIAL_NUMBER.to_string())), request.serial_number);
+        assert!(request.aws_instance_id.is_none());
+        assert!(request.attributes.is_none());
+    }
+
+    #[test]
+    fn deserialize_request_attributes() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"attributes\":{{\"rackPosition\":{{\"number\":3}}}}}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert!(request.serial_number.is_none());
+        assert_eq!(
+            Some(HashMap::from([(
+                "rackPosition".to_string(),
+                InventoryAttribute::Number(3.0)
+            )])),
+            request.attributes
+        );
+    }
+
+    #[test]
+    fn deserialize_request_clear() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"awsInstanceId\":null}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert!(request.serial_number.is_none());
+        assert_eq!(Some(None), request.aws_instance_id);
+        assert!(request.expected_version.is_none());
+    }
+
+    #[test]
+    fn deserialize_request_version() {
+        let input = format!(
+            "{{\"customerId\":\"{CUSTOMER_ID}\",\"vesselId\":\"{VESSEL_ID}\",\"inventoryType\":\"{INVENTORY_TYPE}\",\"inventoryId\":\"{INVENTORY_ID}\",\"serialNumber\":\"{SERIAL_NUMBER}\",\"expectedVersion\":2}}"
+        );
+        let request: Request = from_str(&input).unwrap();
+
+        assert_eq!(Some(2), request.expected_version);
+    }
+
+    #[test]
+    fn serialize_response() {
+        let created_at = Utc
+            .with_ymd_and_hms(2011, 1, 30, 13, 58, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(3600).unwrap());
+
+        let output = to_string(&Response {
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: Some(SERIAL_NUMBER.to_string()),
+            aws_instance_id: None,
+            created_at,
+            version: 1,
+        })
+        .unwrap();
+
+        assert!(output.contains(&format!("\"{INVENTORY_ID}\"")));
+        assert!(output.contains(&format!("\"{SERIAL_NUMBER}\"")));
+        assert!(output.contains("\"2011-01-30T14:58:00+01:00\""));
+    }
+
+    #[test]
+    fn response_from_model() {
+        let created_at = Utc
+            .with_ymd_and_hms(2015, 7, 2, 1, 20, 0)
+            .unwrap()
+            .with_timezone(&FixedOffset::east_opt(7200).unwrap());
+
+        let response = Response::from(Inventory {
+            customer_id: CUSTOMER_ID,
+            vessel_id: VESSEL_ID,
+            inventory_type: INVENTORY_TYPE.to_string(),
+            inventory_id: INVENTORY_ID.to_string(),
+            serial_number: Some(SERIAL_NUMBER.to_string()),
+            aws_instance_id: None,
+            attributes: HashMap::new(),
+            status: InventoryStatus::InService,
+            status_change: None,
+            created_at,
+            deleted_at: None,
+            deleted_by: None,
+            transferred_at: None,
+            version: 3,
+        });
+
+        assert_eq!(INVENTORY_ID, response.inventory_id);
+        assert_eq!(Some(SERIAL_NUMBER.to_string()), response.serial_number);
+        assert!(response.aws_instance_id.is_none());
+        assert_eq!(3, response.version);
+    }
+
+    #[tokio_test]
+    async fn handle_update() {
+        let repository = repository();
+
+        let response = handle(
+            &repository,
+            &TypeRegistry::bundled().unwrap(),
+            request(
+                INVENTORY_ID,
+                &format!("\"serialNumber\":\"{SERIAL_NUMBER}\",\"attributes\":{{\"rackPosition\":{{\"number\":3}}}},\"expectedVersion\":1"),
+            ),
+        )
+        .await
+        .unwrap();
+        assert_eq!(Some(SERIAL_NUMBER.to_string()), response.serial_number);
+        assert_eq!(Some(AWS_INSTANCE_ID.to_string()), response.aws_instance_id);
+        assert_eq!(2, response.version);
+
+        let updated = repository
+            .get_inventory(CUSTOMER_ID, VESSEL_ID, INVENTORY_TYPE.into(), INVENTORY_ID.into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(Some(SERIAL_NUMBER.to_string()), updated.serial_number);
+        assert_eq!(InventoryAttribute::Number(3.0), updated.attributes["rackPosition"]);
+    }
+
+    #[tokio_test]
+    async fn handle_update_invalid_attributes() {
+        assert_failure(
+            handle(
+                &repository(),
+                &TypeRegistry::bundled().unwrap(),
+                request(INVENTORY_ID, "\"attributes\":{\"rackPosition\":{\"string\":\"top\"}}"),
+            )
+            .await,
+            "attributes.rackPosition",
+        );
+    }
+
+    #[tokio_test]
+    async fn handle_update_clear_required() {
+        assert_failure(
+            handle(
+                &repository(),
+                &TypeRegistry::bundled().unwrap(),
+                request(INVENTORY_ID, "\"awsInstanceId\":null"),
+            )
+            .await,
+            "awsInstanceId",
+        );
+    }
+
+    #[tokio_test]
+    async fn handle_update_unexisting() {
+        match handle(
+            &repository(),
+            &TypeRegistry::bundled().unwrap(),
+            request("test1", &format!("\"serialNumber\":\"{SERIAL_NUMBER}\"")),
+        )
+        .await
+        {
+            Err(ApiError::InventoryNotFound(_, inventory_id)) => assert_eq!("test1", inventory_id),
+            _ => panic!("Invalid error type."),
+        }
+    }
+}